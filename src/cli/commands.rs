@@ -1,18 +1,44 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::path::{Path, PathBuf};
 
+use petgraph::graph::NodeIndex;
+
+use crate::cli::config::{LoadedConfig, SettingSource};
+use crate::cli::init::scaffold;
 use crate::emit::dot::emit_dot;
+use crate::emit::json::emit_json_plans;
 use crate::emit::junit::emit_junit;
 use crate::emit::markdown::emit_markdown;
 use crate::emit::mermaid::emit_mermaid;
 use crate::emit::yaml::emit_yaml;
-use crate::graph::builder::build;
-use crate::graph::traversal::{TraversalStrategy, extract_subgraph, shortest_path};
+use crate::graph::analysis::{ancestors, descendants, impact_closure, transitive_reduction};
+use crate::graph::builder::{TestGraph, build};
+use crate::graph::dataflow::ForwardMode;
+use crate::graph::paths::{PathLimits, all_paths, paths_between};
+use crate::graph::query::{Query, parse_query};
+use crate::graph::schedule::{CoverageMetric, select_within_budget, shard};
+use crate::graph::stats::{GraphStats, Thresholds};
+use crate::graph::traversal::{
+    Condensation, TraversalStrategy, edge_cover, extract_subgraph, find_nodes_matching,
+    shortest_path,
+};
+use crate::ir::diff::Diff;
+use crate::ir::params::bind_upstream_parameters;
 use crate::ir::resolve::{ImportResolver, resolve_cross_graph_edges};
+use crate::ir::snapshot::{ChangeSet, Snapshot};
+use crate::ir::tag::split_tag;
 use crate::ir::{IrGraph, lower};
+use crate::lint::{LintSettings, Severity, lint_graph};
 use crate::parser::ast;
 use crate::parser::parse::parse;
-use crate::plan::compiler::compile_with_strategy;
-use crate::plan::filter::{filter_plan, parse_filter};
+use crate::plan::compiler::{compile, compile_paths, compile_permutations, compile_with_strategy};
+use crate::plan::filter::{FilterMode, filter_plan, parse_filter};
+use crate::plan::merge::merge_plans;
+use crate::plan::schema::plan_schema;
+use crate::plan::types::{BudgetEntry, Inclusion, ShardEntry, TestPlan};
+use crate::util::duration::{format_duration, parse_duration};
+use crate::util::glob::is_glob;
+use crate::util::rng::fresh_seed;
 
 /// Options for the `plan` command.
 pub struct PlanOptions {
@@ -20,19 +46,85 @@ pub struct PlanOptions {
     pub strategy: String,
     pub format: String,
     pub filter: Option<String>,
+    /// How `filter` treats dependencies of the steps it keeps: `drop`,
+    /// `keep-deps` or `prune`.
+    pub filter_mode: String,
     pub from: Option<String>,
     pub to: Option<String>,
+    /// Fail on unresolved step parameters instead of warning.
+    pub strict: bool,
+    /// Keep each step's raw `<placeholder>` template next to its rendered text.
+    pub keep_template: bool,
+    /// Path count cap for the `paths` strategy.
+    pub max_paths: usize,
+    /// Path length cap (in nodes) for the `paths` strategy.
+    pub max_path_length: usize,
+    /// Plan only these nodes, everything downstream of them, and the
+    /// ancestors those need for setup.
+    pub affected_by: Vec<String>,
+    /// Snapshot file (see `tast snapshot`); plan only what changed since it
+    /// was taken, selected like `affected_by`.
+    pub since: Option<PathBuf>,
+    /// Node name or glob; keep the matching nodes with their ancestors and
+    /// descendants.
+    pub containing: Option<String>,
+    /// Node name or glob; keep the matching nodes and their ancestors.
+    pub upstream_of: Option<String>,
+    /// Node name or glob; keep the matching nodes and their descendants.
+    pub downstream_of: Option<String>,
+    /// Node selection query (see [`crate::graph::query`]); plan only the
+    /// selected nodes.
+    pub select: Option<String>,
+    /// Seed for the `random` strategy; a fresh one is drawn when unset.
+    pub seed: Option<u64>,
+    /// Number of distinct random orders to plan with the `random` strategy.
+    pub permutations: usize,
+    /// Time budget such as `10m`; plan only the nodes that fit, chosen for
+    /// coverage, with the ancestors they need.
+    pub budget: Option<String>,
+    /// What a budgeted plan keeps as much of: `edges`, `tags` or `priority`.
+    pub budget_by: String,
+    /// Plan only one shard of each graph, as `I/N`.
+    pub shard: Option<String>,
+    /// Merge the plans of all graphs into one, prefixing node names with
+    /// their graph's name.
+    pub merge: bool,
 }
 
+/// Output formats accepted by the `plan` command.
+pub const PLAN_FORMATS: &[&str] = &[
+    "yaml",
+    "json",
+    "json-compact",
+    "markdown",
+    "md",
+    "junit",
+    "xml",
+];
+
 impl PlanOptions {
+    /// The strategy to plan with, carrying the seed for `random`.
     fn parse_strategy(&self) -> Result<TraversalStrategy, String> {
-        match self.strategy.as_str() {
-            "topological" => Ok(TraversalStrategy::Topological),
-            "dfs" => Ok(TraversalStrategy::DepthFirst),
-            "bfs" => Ok(TraversalStrategy::BreadthFirst),
-            other => Err(format!(
-                "unknown strategy '{other}' (expected: topological, dfs, bfs)"
-            )),
+        if self.permutations == 0 {
+            return Err("--permutations must be at least 1".to_owned());
+        }
+        if self.max_paths == 0 {
+            return Err("--max-paths must be at least 1".to_owned());
+        }
+        if self.max_path_length == 0 {
+            return Err("--max-path-length must be at least 1".to_owned());
+        }
+        match self.strategy.parse()? {
+            TraversalStrategy::Random { .. } => Ok(TraversalStrategy::Random {
+                seed: self.seed.unwrap_or_else(fresh_seed),
+            }),
+            _ if self.seed.is_some() => {
+                Err("--seed can only be used with the random strategy".to_owned())
+            }
+            _ if self.permutations > 1 => {
+                Err("--permutations can only be used with the random strategy".to_owned())
+            }
+            strategy => Ok(strategy),
         }
     }
 }
@@ -44,8 +136,25 @@ impl Default for PlanOptions {
             strategy: "topological".to_owned(),
             format: "yaml".to_owned(),
             filter: None,
+            filter_mode: FilterMode::default().to_string(),
             from: None,
             to: None,
+            strict: false,
+            keep_template: false,
+            max_paths: PathLimits::default().max_paths,
+            max_path_length: PathLimits::default().max_length,
+            affected_by: Vec::new(),
+            since: None,
+            containing: None,
+            upstream_of: None,
+            downstream_of: None,
+            select: None,
+            seed: None,
+            permutations: 1,
+            budget: None,
+            budget_by: CoverageMetric::default().to_string(),
+            shard: None,
+            merge: false,
         }
     }
 }
@@ -67,14 +176,74 @@ fn lower_with_imports(graph: &ast::Graph, file: &Path) -> Result<IrGraph, String
     Ok(ir)
 }
 
+/// Result of the `plan` command.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlanOutcome {
+    /// The emitted plans, or a note naming the file they were written to.
+    pub output: String,
+    /// Problems that did not stop planning, such as unresolved step
+    /// parameters, paths cut off by the path limits, or dependencies dropped
+    /// by `--filter-mode prune`, each prefixed with the file it concerns.
+    pub warnings: Vec<String>,
+}
+
 /// Run the `plan` command: parse .tast files and output a YAML test plan.
 ///
 /// # Errors
 ///
 /// Returns an error string if parsing, lowering, building, compiling, or emitting fails.
-pub fn run_plan(files: &[PathBuf], options: &PlanOptions) -> Result<String, String> {
+pub fn run_plan(files: &[PathBuf], options: &PlanOptions) -> Result<PlanOutcome, String> {
     let strategy = options.parse_strategy()?;
-    let mut all_yaml = String::new();
+    let filter_mode: FilterMode = options.filter_mode.parse()?;
+    if options.from.is_some() != options.to.is_some() {
+        return Err("--from and --to must be used together".to_owned());
+    }
+    let snapshot = options
+        .since
+        .as_ref()
+        .map(|path| {
+            let input = std::fs::read_to_string(path)
+                .map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+            Snapshot::from_yaml(&input).map_err(|e| format!("{}: {e}", path.display()))
+        })
+        .transpose()?;
+    let select = options.select.as_deref().map(parse_select).transpose()?;
+    let budget = options
+        .budget
+        .as_deref()
+        .map(|b| parse_duration(b).map_err(|e| format!("--budget: {e}")))
+        .transpose()?;
+    let budget_by: CoverageMetric = options.budget_by.parse()?;
+    let shard_spec = options.shard.as_deref().map(parse_shard).transpose()?;
+    if shard_spec.is_some()
+        && (options.from.is_some()
+            || matches!(
+                strategy,
+                TraversalStrategy::Paths | TraversalStrategy::EdgeCover
+            ))
+    {
+        return Err(
+            "--shard cannot be combined with --from/--to or the paths and edge-cover strategies"
+                .to_owned(),
+        );
+    }
+    if options.merge
+        && (options.permutations > 1
+            || matches!(
+                strategy,
+                TraversalStrategy::Paths | TraversalStrategy::EdgeCover
+            ))
+    {
+        return Err(
+            "--merge cannot be combined with --permutations or the paths and edge-cover strategies"
+                .to_owned(),
+        );
+    }
+    let mut seen_affected = HashSet::new();
+    let mut matched_queries = HashSet::new();
+    let mut matched_names = HashSet::new();
+    let mut all_plans = Vec::new();
+    let mut warnings = Vec::new();
 
     for file in files {
         let input = std::fs::read_to_string(file)
@@ -85,46 +254,724 @@ pub fn run_plan(files: &[PathBuf], options: &PlanOptions) -> Result<String, Stri
         for graph in &graphs {
             let ir = lower_with_imports(graph, file)?;
             let mut tg = build(&ir);
-
-            // Handle --from/--to path query
-            if let (Some(from), Some(to)) = (&options.from, &options.to) {
-                let path = shortest_path(&tg, from, to)
-                    .map_err(|e| format!("{}:{}", file.display(), e))?;
-                tg = extract_subgraph(&tg, &path);
-            } else if options.from.is_some() || options.to.is_some() {
-                return Err("--from and --to must be used together".to_owned());
+            let mode = ForwardMode::from_config(&ir.config).unwrap_or_default();
+            bind_upstream_parameters(&mut tg, mode);
+            if let Some(query) = &select {
+                match_query_names(query, &tg, &mut matched_names);
             }
 
-            let mut plan = compile_with_strategy(&tg, strategy)
-                .map_err(|e| format!("{}:{}", file.display(), e))?;
-
-            // Handle --filter
-            if let Some(filter_str) = &options.filter {
-                let predicate = parse_filter(filter_str)?;
-                plan = filter_plan(&plan, &predicate);
+            if let Some(changed) =
+                changed_nodes(&ir, &tg, options, snapshot.as_ref(), &mut seen_affected)
+            {
+                if changed.is_empty() {
+                    all_plans.push(empty_plan(&tg, strategy));
+                    continue;
+                }
+                tg = extract_subgraph(&tg, &impact_closure(&tg, &changed));
             }
-
-            let output = match options.format.as_str() {
-                "yaml" => emit_yaml(&plan)?,
-                "markdown" | "md" => emit_markdown(&plan),
-                "junit" | "xml" => emit_junit(&plan),
-                other => {
-                    return Err(format!(
-                        "unknown format '{other}' (expected: yaml, markdown, junit)"
+            if let Some(related) = related_nodes(&tg, options, &mut matched_queries) {
+                if related.is_empty() {
+                    all_plans.push(empty_plan(&tg, strategy));
+                    continue;
+                }
+                tg = extract_subgraph(&tg, &related);
+            }
+            if let Some(query) = &select {
+                let selected = query.select(&tg);
+                if selected.is_empty() {
+                    all_plans.push(empty_plan(&tg, strategy));
+                    continue;
+                }
+                tg = extract_subgraph(&tg, &selected);
+            }
+            let mut budget_entry = None;
+            if let Some(limit) = budget {
+                let selection = select_within_budget(&tg, limit, budget_by)
+                    .map_err(|e| format!("{}: graph '{}': {e}", file.display(), tg.name))?;
+                let names = |nodes: &[NodeIndex]| -> Vec<String> {
+                    nodes.iter().map(|&n| tg.graph[n].name.clone()).collect()
+                };
+                if !selection.unestimated.is_empty() {
+                    warnings.push(format!(
+                        "{}: graph '{}': --budget counted {} node(s) without an estimated_duration as free: {}",
+                        file.display(),
+                        tg.name,
+                        selection.unestimated.len(),
+                        names(&selection.unestimated).join(", ")
                     ));
                 }
+                budget_entry = Some(BudgetEntry {
+                    limit: format_duration(limit),
+                    coverage_by: budget_by.to_string(),
+                    coverage: selection.coverage,
+                    selected: names(&selection.selected),
+                    dropped: names(&selection.dropped),
+                });
+                tg = extract_subgraph(&tg, &selection.selected);
+            }
+            let mut shard_part = None;
+            if let Some((index, total)) = shard_spec {
+                let (part, entry, setup) = shard_graph(&tg, index, total);
+                tg = part;
+                shard_part = Some((entry, setup));
+            }
+
+            let plans = match strategy {
+                TraversalStrategy::Paths => compile_path_plans(&tg, file, options, &mut warnings)?,
+                TraversalStrategy::EdgeCover => {
+                    if options.from.is_some() {
+                        return Err(
+                            "--from and --to cannot be combined with the edge-cover strategy"
+                                .to_owned(),
+                        );
+                    }
+                    let paths = edge_cover(&tg).map_err(|e| format!("{}:{}", file.display(), e))?;
+                    compile_paths(&tg, &paths, strategy)
+                }
+                _ => {
+                    // Handle --from/--to path query
+                    if let (Some(from), Some(to)) = (&options.from, &options.to) {
+                        let path = shortest_path(&tg, from, to)
+                            .map_err(|e| format!("{}:{}", file.display(), e))?;
+                        tg = extract_subgraph(&tg, &path);
+                    }
+                    match strategy {
+                        TraversalStrategy::Random { seed } => {
+                            compile_permutations(&tg, seed, options.permutations)
+                                .map_err(|e| format!("{}:{}", file.display(), e))?
+                        }
+                        _ => vec![
+                            compile_with_strategy(&tg, strategy)
+                                .map_err(|e| format!("{}:{}", file.display(), e))?,
+                        ],
+                    }
+                }
             };
-            all_yaml.push_str(&output);
+
+            if plans.iter().any(|p| p.plan.uniform == Some(false)) {
+                warnings.push(format!(
+                    "{}: graph '{}': too many orders to count, so random orders are not drawn uniformly",
+                    file.display(),
+                    tg.name
+                ));
+            }
+            for mut plan in plans {
+                plan.plan.budget.clone_from(&budget_entry);
+                if let Some((entry, setup)) = &shard_part {
+                    plan.plan.shard = Some(entry.clone());
+                    for step in &mut plan.steps {
+                        if setup.contains(&step.node) {
+                            step.included_for = Some(Inclusion::Dependency);
+                        }
+                    }
+                }
+                // Handle --filter
+                if let Some(filter_str) = &options.filter {
+                    let predicate = parse_filter(filter_str)?;
+                    let filtered = filter_plan(&plan, &tg, &predicate, filter_mode);
+                    warnings.extend(
+                        filtered
+                            .pruned
+                            .iter()
+                            .map(|message| format!("{}: {message}", file.display())),
+                    );
+                    plan = filtered.plan;
+                }
+
+                warnings.extend(report_unresolved(&plan, file, options.strict)?);
+                all_plans.push(plan);
+            }
         }
     }
 
-    if let Some(out_path) = &options.output {
-        std::fs::write(out_path, &all_yaml)
+    if let Some(unknown) = options
+        .affected_by
+        .iter()
+        .find(|name| !seen_affected.contains(*name))
+    {
+        return Err(format!("--affected-by: unknown node '{unknown}'"));
+    }
+    if let Some((flag, pattern)) = subgraph_queries(options)
+        .into_iter()
+        .find(|(flag, _)| !matched_queries.contains(flag))
+    {
+        return Err(if is_glob(pattern) {
+            format!("--{flag}: no node matches '{pattern}'")
+        } else {
+            format!("--{flag}: unknown node '{pattern}'")
+        });
+    }
+    if let Some(query) = &select {
+        check_query_names(query, &matched_names)?;
+    }
+
+    if options.merge && !all_plans.is_empty() {
+        all_plans = vec![merge_plans(all_plans)?];
+    }
+    let output = emit_plans(&all_plans, &options.format, options.keep_template)?;
+
+    let output = if let Some(out_path) = &options.output {
+        std::fs::write(out_path, &output)
             .map_err(|e| format!("failed to write {}: {e}", out_path.display()))?;
-        Ok(format!("plan written to {}", out_path.display()))
+        format!("plan written to {}", out_path.display())
     } else {
-        Ok(all_yaml)
+        output
+    };
+    Ok(PlanOutcome { output, warnings })
+}
+
+/// The nodes of one graph that `--affected-by` names or that changed since
+/// the `--since` snapshot, or `None` when neither option is given.
+///
+/// A graph missing from the snapshot counts as entirely changed. Names found
+/// in this graph are added to `seen`, so unknown names can be reported once
+/// every graph has been checked.
+fn changed_nodes(
+    ir: &IrGraph,
+    tg: &TestGraph,
+    options: &PlanOptions,
+    snapshot: Option<&Snapshot>,
+    seen: &mut HashSet<String>,
+) -> Option<Vec<NodeIndex>> {
+    if options.affected_by.is_empty() && snapshot.is_none() {
+        return None;
+    }
+    let mut names: Vec<String> = options
+        .affected_by
+        .iter()
+        .filter(|name| ir.nodes.iter().any(|n| n.name == **name))
+        .cloned()
+        .collect();
+    seen.extend(names.iter().cloned());
+    if let Some(snapshot) = snapshot {
+        match snapshot.graph(&ir.name) {
+            Some(old) => names.extend(ChangeSet::between(old, ir).touched_nodes(ir)),
+            None => names.extend(ir.nodes.iter().map(|n| n.name.clone())),
+        }
+    }
+    Some(
+        tg.node_indices
+            .iter()
+            .filter(|&&idx| names.contains(&tg.graph[idx].name))
+            .copied()
+            .collect(),
+    )
+}
+
+/// Parse a `--select` query, pointing at the error position on failure.
+fn parse_select(query: &str) -> Result<Query, String> {
+    parse_query(query).map_err(|e| {
+        format!(
+            "--select: invalid query at column {}: {}\n  {query}\n  {}^",
+            e.span.col,
+            e.message,
+            " ".repeat(e.span.col - 1)
+        )
+    })
+}
+
+/// Add the node name patterns of `query` that match a node of `tg` to
+/// `matched`, so patterns matching no graph can be reported afterwards.
+fn match_query_names<'q>(query: &'q Query, tg: &TestGraph, matched: &mut HashSet<&'q str>) {
+    for pattern in query.node_patterns() {
+        if !find_nodes_matching(tg, pattern).is_empty() {
+            matched.insert(pattern);
+        }
+    }
+}
+
+/// Fail on the first node name pattern of a `--select` query that matched
+/// no node in any graph, as a typo would.
+fn check_query_names(query: &Query, matched: &HashSet<&str>) -> Result<(), String> {
+    match query
+        .node_patterns()
+        .into_iter()
+        .find(|pattern| !matched.contains(pattern))
+    {
+        Some(pattern) if is_glob(pattern) => Err(format!("--select: no node matches '{pattern}'")),
+        Some(pattern) => Err(format!("--select: unknown node '{pattern}'")),
+        None => Ok(()),
+    }
+}
+
+/// The plan of a graph where nothing is selected, as `--filter` gives when
+/// no step matches.
+fn empty_plan(tg: &TestGraph, strategy: TraversalStrategy) -> TestPlan {
+    let order = match strategy {
+        TraversalStrategy::Paths | TraversalStrategy::EdgeCover => TraversalStrategy::Topological,
+        other => other,
+    };
+    let mut plan = compile_with_strategy(&extract_subgraph(tg, &[]), order)
+        .expect("an empty graph has no cycle");
+    plan.plan.traversal = strategy.to_string();
+    plan
+}
+
+/// Parse a `--shard` spec `I/N` into the 1-based shard number and the
+/// shard count.
+fn parse_shard(spec: &str) -> Result<(usize, usize), String> {
+    let parsed = spec
+        .split_once('/')
+        .and_then(|(i, n)| Some((i.trim().parse().ok()?, n.trim().parse().ok()?)));
+    match parsed {
+        Some((index, total)) if 1 <= index && index <= total => Ok((index, total)),
+        _ => Err(format!(
+            "--shard: expected I/N with 1 <= I <= N, got '{spec}'"
+        )),
+    }
+}
+
+/// One shard of `tg` with the ancestors it needs, its plan metadata, and
+/// the names of the nodes it only runs as setup for its own.
+fn shard_graph(
+    tg: &TestGraph,
+    index: usize,
+    total: usize,
+) -> (TestGraph, ShardEntry, HashSet<String>) {
+    let sharding = shard(tg, total);
+    let part = &sharding.shards[index - 1];
+    let entry = ShardEntry {
+        index,
+        total,
+        balanced_by: if sharding.by_duration {
+            "duration"
+        } else {
+            "nodes"
+        }
+        .to_owned(),
+        owned: part.owned.len(),
+        setup: part.setup.len(),
+        overhead: sharding.overhead,
+    };
+    let setup = part
+        .setup
+        .iter()
+        .map(|&n| tg.graph[n].name.clone())
+        .collect();
+    let nodes: Vec<NodeIndex> = tg
+        .node_indices
+        .iter()
+        .filter(|n| part.owned.contains(n) || part.setup.contains(n))
+        .copied()
+        .collect();
+    (extract_subgraph(tg, &nodes), entry, setup)
+}
+
+/// The `--containing`, `--upstream-of` and `--downstream-of` patterns that
+/// were given, with their flag names.
+fn subgraph_queries(options: &PlanOptions) -> Vec<(&'static str, &str)> {
+    [
+        ("containing", &options.containing),
+        ("upstream-of", &options.upstream_of),
+        ("downstream-of", &options.downstream_of),
+    ]
+    .into_iter()
+    .filter_map(|(flag, pattern)| pattern.as_deref().map(|p| (flag, p)))
+    .collect()
+}
+
+/// The nodes of one graph selected by the subgraph queries, in declaration
+/// order, or `None` when no query is given.
+///
+/// Each query keeps the nodes matching its pattern plus their ancestors,
+/// descendants, or both. Several queries narrow each other, so
+/// `--downstream-of A --upstream-of B` keeps the nodes between A and B. Flags
+/// whose pattern matched here are added to `matched`.
+fn related_nodes(
+    tg: &TestGraph,
+    options: &PlanOptions,
+    matched: &mut HashSet<&'static str>,
+) -> Option<Vec<NodeIndex>> {
+    let queries = subgraph_queries(options);
+    if queries.is_empty() {
+        return None;
+    }
+    let mut selected: Option<HashSet<NodeIndex>> = None;
+    for (flag, pattern) in queries {
+        let hits = find_nodes_matching(tg, pattern);
+        if !hits.is_empty() {
+            matched.insert(flag);
+        }
+        let mut related: HashSet<NodeIndex> = HashSet::new();
+        if flag != "downstream-of" {
+            related.extend(ancestors(tg, &hits));
+        }
+        if flag != "upstream-of" {
+            related.extend(descendants(tg, &hits));
+        }
+        selected = Some(match selected {
+            None => related,
+            Some(acc) => acc.intersection(&related).copied().collect(),
+        });
+    }
+    let selected = selected.unwrap_or_default();
+    Some(
+        tg.node_indices
+            .iter()
+            .filter(|idx| selected.contains(idx))
+            .copied()
+            .collect(),
+    )
+}
+
+/// Enumerate paths for the `paths` strategy — root to leaf, or between
+/// `--from` and `--to` — and compile one plan per path. Paths left out by
+/// the `--max-paths` and `--max-path-length` limits are reported in
+/// `warnings`. Like the other strategies, a cycle without a loop bound is an
+/// error.
+fn compile_path_plans(
+    tg: &TestGraph,
+    file: &Path,
+    options: &PlanOptions,
+    warnings: &mut Vec<String>,
+) -> Result<Vec<TestPlan>, String> {
+    Condensation::of(tg).map_err(|e| format!("{}:{}", file.display(), e))?;
+    let limits = PathLimits {
+        max_paths: options.max_paths,
+        max_length: options.max_path_length,
+    };
+    let set = match (&options.from, &options.to) {
+        (Some(from), Some(to)) => {
+            paths_between(tg, from, to, limits).map_err(|e| format!("{}:{}", file.display(), e))?
+        }
+        _ => all_paths(tg, limits),
+    };
+
+    if set.hit_max_paths {
+        warnings.push(format!(
+            "{}: graph '{}': stopped after {} paths (raise --max-paths)",
+            file.display(),
+            tg.name,
+            limits.max_paths
+        ));
+    }
+    if set.truncated > 0 {
+        warnings.push(format!(
+            "{}: graph '{}': {} branch(es) truncated at {} nodes (raise --max-path-length)",
+            file.display(),
+            tg.name,
+            set.truncated,
+            limits.max_length
+        ));
+    }
+    if set.paths.is_empty() {
+        return Err(match (&options.from, &options.to) {
+            (Some(from), Some(to)) => {
+                format!("{}: no path from '{from}' to '{to}'", file.display())
+            }
+            _ => format!(
+                "{}: graph '{}' has no root-to-leaf paths",
+                file.display(),
+                tg.name
+            ),
+        });
+    }
+    Ok(compile_paths(tg, &set.paths, TraversalStrategy::Paths))
+}
+
+/// Warnings for step parameters that no data source could bind, or an error
+/// in strict mode.
+fn report_unresolved(plan: &TestPlan, file: &Path, strict: bool) -> Result<Vec<String>, String> {
+    let unresolved: Vec<String> = plan
+        .steps
+        .iter()
+        .flat_map(|step| {
+            step.preconditions
+                .iter()
+                .chain(&step.actions)
+                .chain(&step.assertions)
+                .flat_map(|entry| &entry.parameters)
+                .filter(|p| p.source == "unresolved")
+                .map(move |p| {
+                    format!(
+                        "{}: node '{}': parameter <{}> is unresolved",
+                        file.display(),
+                        step.node,
+                        p.name
+                    )
+                })
+        })
+        .collect();
+
+    if strict && !unresolved.is_empty() {
+        return Err(unresolved.join("\n"));
+    }
+    Ok(unresolved)
+}
+
+/// Emit the compiled plans in one of the [`PLAN_FORMATS`].
+///
+/// Several YAML plans are separate documents. JSON is always one array of
+/// plans, however many there are, so consumers parse a single shape.
+fn emit_plans(plans: &[TestPlan], format: &str, show_templates: bool) -> Result<String, String> {
+    match format {
+        "json" => emit_json_plans(plans, true),
+        "json-compact" => emit_json_plans(plans, false),
+        _ => {
+            let emitted = plans
+                .iter()
+                .map(|plan| emit_plan(plan, format, show_templates))
+                .collect::<Result<Vec<_>, _>>()?;
+            let separator = if format == "yaml" { "---\n" } else { "" };
+            Ok(emitted.join(separator))
+        }
+    }
+}
+
+/// Emit a compiled plan in one of the [`PLAN_FORMATS`] other than JSON.
+/// `show_templates` only affects the Markdown and JUnit views; YAML always
+/// carries both the template and its rendering.
+fn emit_plan(plan: &TestPlan, format: &str, show_templates: bool) -> Result<String, String> {
+    match format {
+        "yaml" => emit_yaml(plan),
+        "markdown" | "md" => Ok(emit_markdown(plan, show_templates)),
+        "junit" | "xml" => Ok(emit_junit(plan, show_templates)),
+        other => Err(format!(
+            "unknown format '{other}' (expected: yaml, json, json-compact, markdown, junit)"
+        )),
+    }
+}
+
+/// Find `.tast` files under the configured test directory, sorted by path.
+///
+/// # Errors
+///
+/// Returns an error if the test directory does not exist or cannot be read.
+pub fn discover_files(loaded: &LoadedConfig) -> Result<Vec<PathBuf>, String> {
+    let project = &loaded.config.project;
+    let dir = loaded.root.join(&project.test_dir);
+    if !dir.is_dir() {
+        return Err(format!(
+            "no input files provided and test directory {} does not exist",
+            dir.display()
+        ));
+    }
+    find_files(&dir, &project.file_extension)
+}
+
+/// Every file under `dir` with the given extension, recursively, sorted.
+fn find_files(dir: &Path, extension: &str) -> Result<Vec<PathBuf>, String> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let entries = std::fs::read_dir(&dir)
+            .map_err(|e| format!("failed to read {}: {e}", dir.display()))?;
+        for entry in entries {
+            let path = entry
+                .map_err(|e| format!("failed to read {}: {e}", dir.display()))?
+                .path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if path.extension().is_some_and(|ext| ext == extension) {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Run the `config show` command: print the effective configuration as TOML,
+/// each setting followed by a comment saying whether it is a default or
+/// comes from the config file or an environment variable.
+///
+/// # Errors
+///
+/// Returns an error if the configuration cannot be serialized.
+pub fn run_config_show(loaded: &LoadedConfig) -> Result<String, String> {
+    let mut out = match &loaded.path {
+        Some(path) => format!("# source: {}\n", path.display()),
+        None => "# source: built-in defaults (no .tastrc.toml found)\n".to_owned(),
+    };
+    let file = loaded
+        .path
+        .as_deref()
+        .and_then(Path::file_name)
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut section = String::new();
+    for line in loaded.config.to_toml()?.lines() {
+        if let Some(header) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = header.to_owned();
+        }
+        let Some((key, _)) = line.split_once(" = ").filter(|_| !line.starts_with('[')) else {
+            out.push_str(line);
+            out.push('\n');
+            continue;
+        };
+        let origin = match loaded.source(&format!("{section}.{}", key.trim())) {
+            SettingSource::Default => "default".to_owned(),
+            SettingSource::File => format!("from {file}"),
+            SettingSource::Env(var) => format!("from {var}"),
+        };
+        out.push_str(&format!("{line}  # {origin}\n"));
+    }
+    Ok(out)
+}
+
+/// Run the `schema` command: print the JSON Schema of the plan format.
+///
+/// # Errors
+///
+/// Returns an error if the schema cannot be serialized.
+pub fn run_schema() -> Result<String, String> {
+    serde_json::to_string_pretty(&plan_schema())
+        .map(|json| json + "\n")
+        .map_err(|e| format!("json serialization failed: {e}"))
+}
+
+/// Run the `init` command: write a `.tastrc.toml` and an example graph into `dir`.
+///
+/// Existing files are left untouched unless `force` is set; the check happens
+/// before anything is written, so a refused init changes nothing.
+///
+/// # Errors
+///
+/// Returns an error if a target file already exists without `force`, or if
+/// a directory or file cannot be written.
+pub fn run_init(dir: &Path, force: bool) -> Result<String, String> {
+    let plan = scaffold(dir);
+    let shown = |path: &Path| match path.strip_prefix(dir) {
+        Ok(rel) if !rel.as_os_str().is_empty() => rel.display().to_string(),
+        _ => path.display().to_string(),
+    };
+
+    if !force {
+        let existing: Vec<String> = plan
+            .files
+            .iter()
+            .filter(|(path, _)| path.exists())
+            .map(|(path, _)| shown(path))
+            .collect();
+        if !existing.is_empty() {
+            return Err(format!(
+                "refusing to overwrite existing files (use --force): {}",
+                existing.join(", ")
+            ));
+        }
     }
+
+    let mut report = vec![format!("Detected {}", plan.kind.label())];
+    for (path, contents) in &plan.files {
+        if let Some(parent) = path.parent()
+            && !parent.is_dir()
+        {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("failed to create {}: {e}", parent.display()))?;
+            report.push(format!("Created {}/", shown(parent)));
+        }
+        let verb = if path.exists() {
+            "Overwrote"
+        } else {
+            "Created"
+        };
+        std::fs::write(path, contents)
+            .map_err(|e| format!("failed to write {}: {e}", path.display()))?;
+        report.push(format!("{verb} {}", shown(path)));
+    }
+
+    Ok(report.join("\n"))
+}
+
+/// Result of the `lint` command.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LintOutcome {
+    /// One line per diagnostic, followed by a summary line.
+    pub report: String,
+    /// Whether the run should exit with a failure status.
+    pub failed: bool,
+}
+
+/// Run the `lint` command: check .tast files against the lint rule registry.
+///
+/// The run fails if any diagnostic is an error, or if `deny_warnings` is set
+/// and any diagnostic is a warning.
+///
+/// # Errors
+///
+/// Returns an error string if a file cannot be read, parsed, or lowered.
+pub fn run_lint(
+    files: &[PathBuf],
+    settings: &LintSettings,
+    deny_warnings: bool,
+) -> Result<LintOutcome, String> {
+    let mut lines = Vec::new();
+    let mut counts = [0usize; 3];
+
+    for file in files {
+        let input = std::fs::read_to_string(file)
+            .map_err(|e| format!("failed to read {}: {e}", file.display()))?;
+        let graphs = parse(&input).map_err(|e| format!("{}:{}", file.display(), e))?;
+
+        for graph in &graphs {
+            let ir = lower_with_imports(graph, file)?;
+            for diag in lint_graph(&ir, &input, settings) {
+                match diag.severity {
+                    Severity::Error => counts[0] += 1,
+                    Severity::Warning => counts[1] += 1,
+                    Severity::Info => counts[2] += 1,
+                    Severity::Off => {}
+                }
+                lines.push(format!("{}:{diag}", file.display()));
+            }
+        }
+    }
+
+    let [errors, warnings, infos] = counts;
+    lines.push(format!(
+        "{} checked: {errors} error(s), {warnings} warning(s), {infos} info",
+        if files.len() == 1 {
+            "1 file".to_owned()
+        } else {
+            format!("{} files", files.len())
+        }
+    ));
+
+    Ok(LintOutcome {
+        report: lines.join("\n"),
+        failed: errors > 0 || (deny_warnings && warnings > 0),
+    })
+}
+
+/// Result of the `stats` command.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatsOutcome {
+    /// Metrics of every graph, each followed by the thresholds it exceeds,
+    /// then a summary line.
+    pub report: String,
+    /// Whether any graph exceeds a threshold.
+    pub failed: bool,
+}
+
+/// Run the `stats` command: report structural metrics of every graph and
+/// check them against `thresholds`.
+///
+/// # Errors
+///
+/// Returns an error string if a file cannot be read, parsed, or lowered.
+pub fn run_stats(files: &[PathBuf], thresholds: &Thresholds) -> Result<StatsOutcome, String> {
+    let mut report = String::new();
+    let mut graphs = 0;
+    let mut exceeded = 0;
+    for ir in lower_files(files)? {
+        let mut tg = build(&ir);
+        let mode = ForwardMode::from_config(&ir.config).unwrap_or_default();
+        bind_upstream_parameters(&mut tg, mode);
+        let stats = GraphStats::of(&tg);
+        report.push_str(&stats.report());
+        for violation in stats.violations(thresholds) {
+            report.push_str(&format!("  exceeded: {violation}\n"));
+            exceeded += 1;
+        }
+        graphs += 1;
+    }
+    report.push_str(&format!(
+        "{graphs} graph(s) checked: {exceeded} threshold(s) exceeded\n"
+    ));
+    Ok(StatsOutcome {
+        report,
+        failed: exceeded > 0,
+    })
 }
 
 /// Run the `validate` command: parse .tast files and report validity.
@@ -163,14 +1010,23 @@ pub fn run_validate(files: &[PathBuf]) -> Result<String, String> {
 
 /// Run the `visualize` command: parse .tast files and output a graph diagram.
 ///
+/// With a `select` query, only the selected nodes and the edges between them
+/// are drawn. With `reduced`, edges implied by a longer path that also
+/// delivers their data are left out (see [`transitive_reduction`]).
+///
 /// # Errors
 ///
-/// Returns an error string if parsing, lowering, building, or emitting fails.
+/// Returns an error string if the query is invalid, or if parsing, lowering,
+/// building, or emitting fails.
 pub fn run_visualize(
     files: &[PathBuf],
     format: &str,
     output: Option<&PathBuf>,
+    select: Option<&str>,
+    reduced: bool,
 ) -> Result<String, String> {
+    let select = select.map(parse_select).transpose()?;
+    let mut matched_names = HashSet::new();
     let mut all_output = String::new();
 
     for file in files {
@@ -181,7 +1037,15 @@ pub fn run_visualize(
 
         for graph in &graphs {
             let ir = lower_with_imports(graph, file)?;
-            let tg = build(&ir);
+            let mut tg = build(&ir);
+            if let Some(query) = &select {
+                match_query_names(query, &tg, &mut matched_names);
+                tg = extract_subgraph(&tg, &query.select(&tg));
+            }
+            if reduced {
+                let mode = ForwardMode::from_config(&ir.config).unwrap_or_default();
+                tg = transitive_reduction(&tg, mode);
+            }
 
             let diagram = match format {
                 "dot" => emit_dot(&tg),
@@ -191,6 +1055,9 @@ pub fn run_visualize(
             all_output.push_str(&diagram);
         }
     }
+    if let Some(query) = &select {
+        check_query_names(query, &matched_names)?;
+    }
 
     if let Some(out_path) = output {
         std::fs::write(out_path, &all_output)
@@ -201,12 +1068,87 @@ pub fn run_visualize(
     }
 }
 
+/// Run the `snapshot` command: record content fingerprints of every graph,
+/// for later use with `tast plan --since`.
+///
+/// # Errors
+///
+/// Returns an error string if parsing or lowering fails, or if the snapshot
+/// cannot be written.
+pub fn run_snapshot(files: &[PathBuf], output: Option<&PathBuf>) -> Result<String, String> {
+    let yaml = Snapshot::of(&lower_files(files)?).to_yaml()?;
+
+    if let Some(out_path) = output {
+        std::fs::write(out_path, &yaml)
+            .map_err(|e| format!("failed to write {}: {e}", out_path.display()))?;
+        Ok(format!("snapshot written to {}", out_path.display()))
+    } else {
+        Ok(yaml)
+    }
+}
+
+/// Parse and lower every graph in `files`.
+fn lower_files(files: &[PathBuf]) -> Result<Vec<IrGraph>, String> {
+    let mut graphs = Vec::new();
+    for file in files {
+        let input = std::fs::read_to_string(file)
+            .map_err(|e| format!("failed to read {}: {e}", file.display()))?;
+        for graph in &parse(&input).map_err(|e| format!("{}:{}", file.display(), e))? {
+            graphs.push(lower_with_imports(graph, file)?);
+        }
+    }
+    Ok(graphs)
+}
+
+/// Run the `diff` command: compare two versions of a set of graphs, each
+/// given as a file or a directory searched for files with `extension`.
+///
+/// `format` is `text` for a human-readable report or `json`.
+///
+/// # Errors
+///
+/// Returns an error string if either version fails to load, or if the format
+/// is unknown. A graph that fails to compile is still compared, without its
+/// plan order.
+pub fn run_diff(old: &Path, new: &Path, format: &str, extension: &str) -> Result<String, String> {
+    let load = |path: &Path| -> Result<Vec<IrGraph>, String> {
+        if path.is_dir() {
+            lower_files(&find_files(path, extension)?)
+        } else {
+            lower_files(&[path.to_path_buf()])
+        }
+    };
+    let (old_graphs, new_graphs) = (load(old)?, load(new)?);
+    let mut diff = Diff::between(&old_graphs, &new_graphs);
+
+    diff.compare_plan_order(&old_graphs, &new_graphs, |ir| {
+        let plan = compile(&build(ir))?;
+        Ok(plan.steps.into_iter().map(|step| step.node).collect())
+    });
+
+    match format {
+        "text" => Ok(diff.report()),
+        "json" => serde_json::to_string_pretty(&diff)
+            .map(|json| json + "\n")
+            .map_err(|e| format!("json serialization failed: {e}")),
+        other => Err(format!(
+            "unknown diff format '{other}' (expected: text, json)"
+        )),
+    }
+}
+
 /// Run the `list` command: list nodes, edges, or tags from .tast files.
 ///
+/// With a `select` query, nodes, edges and tags are limited to the selected
+/// nodes; fixtures are listed regardless.
+///
 /// # Errors
 ///
-/// Returns an error string if parsing or lowering fails, or if `what` is invalid.
-pub fn run_list(what: &str, files: &[PathBuf]) -> Result<String, String> {
+/// Returns an error string if parsing or lowering fails, or if `what` or the
+/// query is invalid.
+pub fn run_list(what: &str, files: &[PathBuf], select: Option<&str>) -> Result<String, String> {
+    let select = select.map(parse_select).transpose()?;
+    let mut matched_names = HashSet::new();
     let mut lines = Vec::new();
 
     for file in files {
@@ -217,7 +1159,11 @@ pub fn run_list(what: &str, files: &[PathBuf]) -> Result<String, String> {
 
         for graph in &graphs {
             let ir = lower_with_imports(graph, file)?;
-            let tg = build(&ir);
+            let mut tg = build(&ir);
+            if let Some(query) = &select {
+                match_query_names(query, &tg, &mut matched_names);
+                tg = extract_subgraph(&tg, &query.select(&tg));
+            }
 
             match what {
                 "nodes" => {
@@ -245,14 +1191,25 @@ pub fn run_list(what: &str, files: &[PathBuf]) -> Result<String, String> {
                     }
                 }
                 "tags" => {
-                    let mut all_tags = std::collections::BTreeSet::new();
+                    // Plain tags one per line, then key-value tags grouped by key
+                    let mut plain = BTreeSet::new();
+                    let mut by_key: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
                     for &idx in &tg.node_indices {
                         for tag in &tg.graph[idx].tags {
-                            all_tags.insert(tag.clone());
+                            match split_tag(tag) {
+                                (Some(key), value) => {
+                                    by_key.entry(key).or_default().insert(value);
+                                }
+                                (None, _) => {
+                                    plain.insert(tag.clone());
+                                }
+                            }
                         }
                     }
-                    for tag in all_tags {
-                        lines.push(tag);
+                    lines.extend(plain);
+                    for (key, values) in by_key {
+                        let values: Vec<&str> = values.into_iter().collect();
+                        lines.push(format!("{key}: {}", values.join(", ")));
                     }
                 }
                 "fixtures" => {
@@ -277,6 +1234,9 @@ pub fn run_list(what: &str, files: &[PathBuf]) -> Result<String, String> {
             }
         }
     }
+    if let Some(query) = &select {
+        check_query_names(query, &matched_names)?;
+    }
 
     Ok(lines.join("\n") + "\n")
 }
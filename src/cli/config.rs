@@ -0,0 +1,655 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::cli::commands::{PLAN_FORMATS, PlanOptions};
+use crate::graph::stats::Thresholds;
+use crate::graph::traversal::TraversalStrategy;
+use crate::lint::{LintSettings, Severity};
+use crate::plan::filter::FilterMode;
+use crate::util::duration::parse_duration;
+
+/// File name searched for when discovering project configuration.
+pub const CONFIG_FILE_NAME: &str = ".tastrc.toml";
+
+/// Project configuration loaded from `.tastrc.toml`.
+///
+/// Every field has a default, so an empty or missing file yields a usable config.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub project: ProjectConfig,
+    pub output: OutputConfig,
+    pub plan: PlanConfig,
+    pub runner: RunnerConfig,
+    /// The `[stats]` table: thresholds that fail `tast stats`.
+    pub stats: Thresholds,
+    /// The `[lint]` table: rule ID → severity overrides.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub lint: BTreeMap<String, Severity>,
+}
+
+/// The `[project]` section.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProjectConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Directory searched for `.tast` files when none are given on the command line.
+    pub test_dir: String,
+    pub file_extension: String,
+}
+
+/// The `[output]` section.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OutputConfig {
+    pub format: String,
+}
+
+/// The `[plan]` section: defaults for `tast plan`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PlanConfig {
+    pub strategy: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<String>,
+    /// How `filter` treats dependencies: `drop`, `keep-deps` or `prune`.
+    pub filter_mode: String,
+    /// Fail on unresolved step parameters instead of warning.
+    pub strict: bool,
+    /// Show raw step templates next to rendered step text in Markdown and
+    /// JUnit output.
+    pub keep_template: bool,
+}
+
+/// The `[runner]` section. Backend-specific tables such as `[runner.rust]`
+/// are collected into `backends`; any other unknown key is rejected.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "RawRunnerConfig")]
+pub struct RunnerConfig {
+    pub enabled: bool,
+    pub backend: String,
+    pub timeout: String,
+    pub parallel: usize,
+    #[serde(flatten)]
+    pub backends: BTreeMap<String, BackendConfig>,
+}
+
+/// `[runner]` as written, before keys that are not settings are checked to
+/// be backend tables.
+#[derive(Deserialize)]
+struct RawRunnerConfig {
+    enabled: Option<bool>,
+    backend: Option<String>,
+    timeout: Option<String>,
+    parallel: Option<usize>,
+    #[serde(flatten)]
+    tables: BTreeMap<String, toml::Value>,
+}
+
+impl TryFrom<RawRunnerConfig> for RunnerConfig {
+    type Error = String;
+
+    fn try_from(raw: RawRunnerConfig) -> Result<Self, Self::Error> {
+        let mut backends = BTreeMap::new();
+        for (name, value) in raw.tables {
+            if !value.is_table() {
+                return Err(format!(
+                    "unknown field `{name}`, expected `enabled`, `backend`, `timeout`, `parallel` or a [runner.<backend>] table"
+                ));
+            }
+            let backend = value
+                .try_into()
+                .map_err(|e| format!("[runner.{name}] {}", e.message()))?;
+            backends.insert(name, backend);
+        }
+        let defaults = Self::default();
+        Ok(Self {
+            enabled: raw.enabled.unwrap_or(defaults.enabled),
+            backend: raw.backend.unwrap_or(defaults.backend),
+            timeout: raw.timeout.unwrap_or(defaults.timeout),
+            parallel: raw.parallel.unwrap_or(defaults.parallel),
+            backends,
+        })
+    }
+}
+
+/// A `[runner.<backend>]` table.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BackendConfig {
+    pub command: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub test_args: Vec<String>,
+}
+
+impl Default for ProjectConfig {
+    fn default() -> Self {
+        Self {
+            name: None,
+            test_dir: "tests/tast".to_owned(),
+            file_extension: "tast".to_owned(),
+        }
+    }
+}
+
+impl Default for OutputConfig {
+    fn default() -> Self {
+        Self {
+            format: "yaml".to_owned(),
+        }
+    }
+}
+
+impl Default for PlanConfig {
+    fn default() -> Self {
+        Self {
+            strategy: "topological".to_owned(),
+            filter: None,
+            filter_mode: FilterMode::default().to_string(),
+            strict: false,
+            keep_template: false,
+        }
+    }
+}
+
+impl Default for RunnerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            backend: "rust".to_owned(),
+            timeout: "60s".to_owned(),
+            parallel: 1,
+            backends: BTreeMap::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Parse and validate a config from TOML source.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error prefixed with `path` if the TOML is malformed or a value is invalid.
+    pub fn from_toml(source: &str, path: &Path) -> Result<Self, String> {
+        let config: Config =
+            toml::from_str(source).map_err(|e| format!("{}: {e}", path.display()))?;
+        config
+            .validate()
+            .map_err(|e| format!("{}: {e}", path.display()))?;
+        Ok(config)
+    }
+
+    /// Read, parse, and validate a config file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or fails [`Config::from_toml`].
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let source = std::fs::read_to_string(path)
+            .map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+        Self::from_toml(&source, path)
+    }
+
+    /// Check that every value is one the CLI understands.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the offending `[section] key`.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.project.test_dir.trim().is_empty() {
+            return Err("[project] test_dir: must not be empty".to_owned());
+        }
+        if self.project.file_extension.trim().is_empty()
+            || self.project.file_extension.starts_with('.')
+        {
+            return Err(format!(
+                "[project] file_extension: expected an extension without a leading dot, found '{}'",
+                self.project.file_extension
+            ));
+        }
+        if !PLAN_FORMATS.contains(&self.output.format.as_str()) {
+            return Err(format!(
+                "[output] format: unknown format '{}' (expected: {})",
+                self.output.format,
+                PLAN_FORMATS.join(", ")
+            ));
+        }
+        self.plan
+            .strategy
+            .parse::<TraversalStrategy>()
+            .map_err(|e| format!("[plan] strategy: {e}"))?;
+        self.plan
+            .filter_mode
+            .parse::<FilterMode>()
+            .map_err(|e| format!("[plan] filter_mode: {e}"))?;
+        parse_duration(&self.runner.timeout).map_err(|e| format!("[runner] timeout: {e}"))?;
+        if self.runner.parallel == 0 {
+            return Err("[runner] parallel: must be at least 1".to_owned());
+        }
+        for (name, backend) in &self.runner.backends {
+            if backend.command.trim().is_empty() {
+                return Err(format!("[runner.{name}] command: must not be empty"));
+            }
+        }
+        if let Some(max) = self.stats.max_unresolved
+            && !(0.0..=100.0).contains(&max)
+        {
+            return Err(format!(
+                "[stats] max_unresolved: expected a percentage from 0 to 100, found {max}"
+            ));
+        }
+        self.lint_settings().map(|_| ())
+    }
+
+    /// Lint severity overrides from the `[lint]` table.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the table names a rule that does not exist.
+    pub fn lint_settings(&self) -> Result<LintSettings, String> {
+        LintSettings::new(&self.lint).map_err(|e| format!("[lint] {e}"))
+    }
+
+    /// Override values from `TAST_*` environment variables.
+    ///
+    /// `env` looks up a variable by name; pass `|k| std::env::var(k).ok()` for the
+    /// process environment.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the variable if a value cannot be interpreted.
+    pub fn apply_env(&mut self, env: impl Fn(&str) -> Option<String>) -> Result<(), String> {
+        if let Some(v) = env("TAST_TEST_DIR") {
+            self.project.test_dir = v;
+        }
+        if let Some(v) = env("TAST_FORMAT") {
+            self.output.format = v;
+        }
+        if let Some(v) = env("TAST_STRATEGY") {
+            self.plan.strategy = v;
+        }
+        if let Some(v) = env("TAST_FILTER") {
+            self.plan.filter = Some(v);
+        }
+        self.validate().map_err(|e| format!("environment: {e}"))
+    }
+
+    /// Serialize the config back to TOML.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if TOML serialization fails.
+    pub fn to_toml(&self) -> Result<String, String> {
+        toml::to_string(self).map_err(|e| format!("toml serialization failed: {e}"))
+    }
+
+    /// Build `plan` command options from the configured defaults.
+    pub fn plan_options(&self) -> PlanOptions {
+        PlanOptions {
+            strategy: self.plan.strategy.clone(),
+            format: self.output.format.clone(),
+            filter: self.plan.filter.clone(),
+            filter_mode: self.plan.filter_mode.clone(),
+            strict: self.plan.strict,
+            keep_template: self.plan.keep_template,
+            ..PlanOptions::default()
+        }
+    }
+}
+
+/// The `TAST_*` variables [`Config::apply_env`] reads, with the setting each
+/// overrides as `section.key`.
+pub const ENV_SETTINGS: &[(&str, &str)] = &[
+    ("TAST_TEST_DIR", "project.test_dir"),
+    ("TAST_FORMAT", "output.format"),
+    ("TAST_STRATEGY", "plan.strategy"),
+    ("TAST_FILTER", "plan.filter"),
+];
+
+/// Where the effective value of a setting came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingSource {
+    Default,
+    File,
+    /// The named environment variable.
+    Env(&'static str),
+}
+
+/// A config together with the file it was read from, if any.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadedConfig {
+    pub config: Config,
+    pub path: Option<PathBuf>,
+    /// Directory that relative paths such as `test_dir` are resolved against.
+    pub root: PathBuf,
+    /// Settings that do not have their default value, as `section.key`, and
+    /// where they were set. Tables such as `[runner.rust]` give
+    /// `runner.rust.command`.
+    pub sources: BTreeMap<String, SettingSource>,
+}
+
+impl LoadedConfig {
+    /// Where the setting `key`, written as `section.key`, got its value.
+    pub fn source(&self, key: &str) -> SettingSource {
+        self.sources
+            .get(key)
+            .copied()
+            .unwrap_or(SettingSource::Default)
+    }
+}
+
+/// Walk up from `start` looking for a `.tastrc.toml`.
+pub fn discover(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join(CONFIG_FILE_NAME))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Load the effective configuration for a run started in `start`.
+///
+/// Precedence, lowest to highest: built-in defaults, the discovered (or
+/// `TAST_CONFIG`-named) config file, then `TAST_*` environment variables.
+/// Command-line flags are applied on top by the caller.
+///
+/// # Errors
+///
+/// Returns an error if the config file or an environment override is invalid.
+pub fn load_effective(
+    start: &Path,
+    env: impl Fn(&str) -> Option<String>,
+) -> Result<LoadedConfig, String> {
+    let path = match env("TAST_CONFIG") {
+        Some(explicit) => Some(start.join(explicit)),
+        None => discover(start),
+    };
+
+    let mut sources = BTreeMap::new();
+    let mut config = match &path {
+        Some(p) => {
+            let source = std::fs::read_to_string(p)
+                .map_err(|e| format!("failed to read {}: {e}", p.display()))?;
+            let config = Config::from_toml(&source, p)?;
+            for key in file_settings(&source) {
+                sources.insert(key, SettingSource::File);
+            }
+            config
+        }
+        None => Config::default(),
+    };
+    config.apply_env(&env)?;
+    for &(var, key) in ENV_SETTINGS {
+        if env(var).is_some() {
+            sources.insert(key.to_owned(), SettingSource::Env(var));
+        }
+    }
+
+    let root = path
+        .as_deref()
+        .and_then(Path::parent)
+        .map_or_else(|| start.to_owned(), Path::to_owned);
+
+    Ok(LoadedConfig {
+        config,
+        path,
+        root,
+        sources,
+    })
+}
+
+/// Every setting a config file assigns, as `section.key`.
+fn file_settings(source: &str) -> Vec<String> {
+    fn walk(table: &toml::Table, prefix: &str, keys: &mut Vec<String>) {
+        for (key, value) in table {
+            let path = if prefix.is_empty() {
+                key.clone()
+            } else {
+                format!("{prefix}.{key}")
+            };
+            match value.as_table() {
+                Some(table) => walk(table, &path, keys),
+                None => keys.push(path),
+            }
+        }
+    }
+    let mut keys = Vec::new();
+    if let Ok(table) = source.parse::<toml::Table>() {
+        walk(&table, "", &mut keys);
+    }
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source: &str) -> Result<Config, String> {
+        Config::from_toml(source, Path::new(".tastrc.toml"))
+    }
+
+    fn no_env(_: &str) -> Option<String> {
+        None
+    }
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("target")
+            .join("config_tests")
+            .join(name);
+        std::fs::remove_dir_all(&dir).ok();
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn empty_file_yields_defaults() {
+        assert_eq!(parse("").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn parses_all_documented_sections() {
+        let config = parse(
+            r#"
+            [project]
+            name = "my-project"
+            test_dir = "spec/tast"
+            file_extension = "tast"
+
+            [output]
+            format = "markdown"
+
+            [plan]
+            strategy = "bfs"
+            filter = "smoke"
+
+            [runner]
+            enabled = true
+            backend = "rust"
+            timeout = "90s"
+            parallel = 4
+
+            [runner.rust]
+            command = "cargo test"
+            test_args = ["--", "--nocapture"]
+
+            [stats]
+            max_depth = 8
+            max_unresolved = 5.0
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.project.name.as_deref(), Some("my-project"));
+        assert_eq!(config.project.test_dir, "spec/tast");
+        assert_eq!(config.output.format, "markdown");
+        assert_eq!(config.plan.strategy, "bfs");
+        assert_eq!(config.plan.filter.as_deref(), Some("smoke"));
+        assert_eq!(config.runner.parallel, 4);
+        assert_eq!(config.runner.backends["rust"].command, "cargo test");
+        assert_eq!(
+            config.runner.backends["rust"].test_args,
+            vec!["--", "--nocapture"]
+        );
+        assert_eq!(config.stats.max_depth, Some(8));
+        assert_eq!(config.stats.max_unresolved, Some(5.0));
+        assert_eq!(config.stats.max_fan_in, None);
+    }
+
+    #[test]
+    fn syntax_error_names_the_file() {
+        let err = parse("[output\nformat = 1").unwrap_err();
+        assert!(err.starts_with(".tastrc.toml: "), "got: {err}");
+    }
+
+    #[test]
+    fn unknown_key_is_rejected() {
+        let err = parse("[output]\nfromat = \"yaml\"").unwrap_err();
+        assert!(err.contains("fromat"), "got: {err}");
+    }
+
+    #[test]
+    fn unknown_runner_key_is_not_taken_for_a_backend() {
+        let err = parse("[runner]\ntimout = \"5s\"").unwrap_err();
+        assert!(
+            err.contains("unknown field `timout`, expected `enabled`"),
+            "got: {err}"
+        );
+        let err = parse("[runner.rust]\ncommand = \"cargo test\"\nargs = []").unwrap_err();
+        assert!(
+            err.contains("[runner.rust] unknown field `args`"),
+            "got: {err}"
+        );
+        let config =
+            parse("[runner]\nparallel = 2\n[runner.rust]\ncommand = \"cargo test\"").unwrap();
+        assert_eq!(config.runner.parallel, 2);
+        assert_eq!(config.runner.timeout, "60s");
+        assert_eq!(config.runner.backends["rust"].command, "cargo test");
+    }
+
+    #[test]
+    fn invalid_format_points_at_section() {
+        let err = parse("[output]\nformat = \"html\"").unwrap_err();
+        assert!(
+            err.contains("[output] format: unknown format 'html'"),
+            "got: {err}"
+        );
+    }
+
+    #[test]
+    fn invalid_strategy_and_timeout_rejected() {
+        let err = parse("[plan]\nstrategy = \"random-walk\"").unwrap_err();
+        assert!(err.contains("[plan] strategy"), "got: {err}");
+        let err = parse("[runner]\ntimeout = \"soon\"").unwrap_err();
+        assert!(err.contains("[runner] timeout"), "got: {err}");
+    }
+
+    #[test]
+    fn stats_threshold_must_be_a_percentage() {
+        let err = parse("[stats]\nmax_unresolved = 150.0").unwrap_err();
+        assert!(err.contains("[stats] max_unresolved"), "got: {err}");
+    }
+
+    #[test]
+    fn lint_table_sets_severities() {
+        let config = parse("[lint]\norphan-node = \"error\"\nmissing-then = \"off\"").unwrap();
+        assert_eq!(config.lint["orphan-node"], Severity::Error);
+        assert!(config.lint_settings().is_ok());
+        assert_eq!(parse(&config.to_toml().unwrap()).unwrap(), config);
+    }
+
+    #[test]
+    fn lint_table_rejects_unknown_rule_and_severity() {
+        let err = parse("[lint]\nno-such-rule = \"off\"").unwrap_err();
+        assert!(
+            err.contains("[lint] unknown lint rule 'no-such-rule'"),
+            "got: {err}"
+        );
+        let err = parse("[lint]\norphan-node = \"fatal\"").unwrap_err();
+        assert!(err.contains("fatal"), "got: {err}");
+    }
+
+    #[test]
+    fn env_overrides_file_values() {
+        let mut config = parse("[output]\nformat = \"markdown\"").unwrap();
+        config
+            .apply_env(|k| (k == "TAST_FORMAT").then(|| "junit".to_owned()))
+            .unwrap();
+        assert_eq!(config.output.format, "junit");
+    }
+
+    #[test]
+    fn invalid_env_value_names_the_source() {
+        let mut config = Config::default();
+        let err = config
+            .apply_env(|k| (k == "TAST_STRATEGY").then(|| "sideways".to_owned()))
+            .unwrap_err();
+        assert!(
+            err.starts_with("environment: [plan] strategy"),
+            "got: {err}"
+        );
+    }
+
+    #[test]
+    fn plan_options_carry_config_defaults() {
+        let config = parse("[output]\nformat = \"junit\"\n[plan]\nstrategy = \"dfs\"").unwrap();
+        let options = config.plan_options();
+        assert_eq!(options.format, "junit");
+        assert_eq!(options.strategy, "dfs");
+        assert!(options.output.is_none());
+    }
+
+    #[test]
+    fn round_trips_through_toml() {
+        let config = parse(
+            "[runner]\nparallel = 2\n[runner.shell]\ncommand = \"bash\"\n[plan]\nfilter = \"smoke\"",
+        )
+        .unwrap();
+        let text = config.to_toml().unwrap();
+        assert_eq!(parse(&text).unwrap(), config);
+    }
+
+    #[test]
+    fn discovers_config_in_parent_directory() {
+        let root = scratch_dir("discover");
+        let nested = root.join("a").join("b");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(root.join(CONFIG_FILE_NAME), "[plan]\nstrategy = \"bfs\"\n").unwrap();
+
+        let loaded = load_effective(&nested, no_env).unwrap();
+        assert_eq!(loaded.path, Some(root.join(CONFIG_FILE_NAME)));
+        assert_eq!(loaded.root, root);
+        assert_eq!(loaded.config.plan.strategy, "bfs");
+    }
+
+    #[test]
+    fn records_where_settings_come_from() {
+        let root = scratch_dir("sources");
+        std::fs::write(
+            root.join(CONFIG_FILE_NAME),
+            "[output]\nformat = \"markdown\"\n[plan]\nstrategy = \"bfs\"\n[runner.rust]\ncommand = \"cargo test\"\n",
+        )
+        .unwrap();
+        let loaded =
+            load_effective(&root, |k| (k == "TAST_STRATEGY").then(|| "dfs".to_owned())).unwrap();
+        assert_eq!(loaded.config.plan.strategy, "dfs");
+        assert_eq!(loaded.source("output.format"), SettingSource::File);
+        assert_eq!(
+            loaded.source("plan.strategy"),
+            SettingSource::Env("TAST_STRATEGY")
+        );
+        assert_eq!(loaded.source("runner.rust.command"), SettingSource::File);
+        assert_eq!(loaded.source("project.test_dir"), SettingSource::Default);
+    }
+
+    #[test]
+    fn explicit_config_path_must_exist() {
+        let dir = scratch_dir("explicit");
+        let err = load_effective(&dir, |k| {
+            (k == "TAST_CONFIG").then(|| "nonexistent.toml".to_owned())
+        })
+        .unwrap_err();
+        assert!(err.contains("failed to read"), "got: {err}");
+    }
+}
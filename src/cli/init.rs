@@ -0,0 +1,258 @@
+use std::path::{Path, PathBuf};
+
+use crate::cli::commands::PLAN_FORMATS;
+use crate::cli::config::{CONFIG_FILE_NAME, Config, ProjectConfig};
+use crate::graph::traversal::TraversalStrategy;
+
+/// File name of the example graph written by `tast init`.
+pub const EXAMPLE_FILE_NAME: &str = "example.tast";
+
+/// Example graph written into the test directory. It must always pass `tast validate`.
+pub const EXAMPLE_GRAPH: &str = r#"graph Example {
+
+  node CreateAccount {
+    describe "A new user signs up"
+    tags [smoke]
+
+    given a user with {
+      email: "user@example.com"
+    }
+
+    when the user submits the sign-up form
+    then the system creates a new account
+  }
+
+  node SignIn {
+    describe "The new user signs in"
+    tags [smoke]
+    requires { email }
+
+    when the user submits valid credentials
+    then the system returns a session token
+  }
+
+  CreateAccount -> SignIn {
+    passes { email }
+    describe "A created account can sign in"
+  }
+}
+"#;
+
+/// The kind of host project `tast init` is run in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectKind {
+    Rust,
+    Elixir,
+    Node,
+    Go,
+    Unknown,
+}
+
+impl ProjectKind {
+    /// Detect the project kind from the manifest files present in `dir`.
+    ///
+    /// Manifests are checked in a fixed order, so a directory with both
+    /// `Cargo.toml` and `package.json` is treated as a Rust project.
+    pub fn detect(dir: &Path) -> Self {
+        [
+            ("Cargo.toml", Self::Rust),
+            ("mix.exs", Self::Elixir),
+            ("package.json", Self::Node),
+            ("go.mod", Self::Go),
+        ]
+        .into_iter()
+        .find(|(manifest, _)| dir.join(manifest).is_file())
+        .map_or(Self::Unknown, |(_, kind)| kind)
+    }
+
+    /// Runner backend name written to `[runner] backend`.
+    pub fn backend(self) -> &'static str {
+        match self {
+            Self::Rust => "rust",
+            Self::Elixir => "elixir",
+            Self::Node => "node",
+            Self::Go => "go",
+            Self::Unknown => "shell",
+        }
+    }
+
+    /// Test command and arguments for the `[runner.<backend>]` table.
+    fn runner_command(self) -> (&'static str, &'static [&'static str]) {
+        match self {
+            Self::Rust => ("cargo test", &["--", "--nocapture"]),
+            Self::Elixir => ("mix test", &[]),
+            Self::Node => ("npm test", &[]),
+            Self::Go => ("go test", &["./..."]),
+            Self::Unknown => ("bash", &[]),
+        }
+    }
+
+    /// Human-readable label used in `tast init` output.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Rust => "Rust (Cargo.toml)",
+            Self::Elixir => "Elixir (mix.exs)",
+            Self::Node => "Node.js (package.json)",
+            Self::Go => "Go (go.mod)",
+            Self::Unknown => "unknown project type",
+        }
+    }
+}
+
+/// Render a commented `.tastrc.toml` pre-filled for `kind`.
+///
+/// Values are written as TOML, and the lists of formats and strategies in
+/// the comments come from [`PLAN_FORMATS`] and [`TraversalStrategy::NAMES`].
+pub fn render_config(kind: ProjectKind, name: &str) -> String {
+    let defaults = Config::default();
+    let backend = kind.backend();
+    let (command, args) = kind.runner_command();
+    let formats = PLAN_FORMATS.join(", ");
+    let strategies = TraversalStrategy::NAMES.join(", ");
+
+    let mut out = String::from("[project]\n");
+    out += &setting("name", name, "");
+    out += &setting(
+        "test_dir",
+        defaults.project.test_dir,
+        "where .tast files live",
+    );
+    out += &setting("file_extension", defaults.project.file_extension, "");
+    out += "\n[output]\n";
+    out += &setting("format", defaults.output.format, &formats);
+    out += "\n[plan]\n";
+    out += &setting("strategy", defaults.plan.strategy, &strategies);
+    out += "\n[runner]\n";
+    out += &setting(
+        "enabled",
+        defaults.runner.enabled,
+        "planning only, no execution",
+    );
+    out += &setting("backend", backend, "");
+    out += &setting("timeout", defaults.runner.timeout, "");
+    out += &setting("parallel", defaults.runner.parallel as i64, "");
+    out += &format!("\n[runner.{backend}]\n");
+    out += &setting("command", command, "");
+    if !args.is_empty() {
+        out += &setting("test_args", args.to_vec(), "");
+    }
+    out
+}
+
+/// One `key = value` line, with `comment` aligned after the value if given.
+fn setting(key: &str, value: impl Into<toml::Value>, comment: &str) -> String {
+    let line = format!("{key} = {}", value.into());
+    if comment.is_empty() {
+        line + "\n"
+    } else {
+        format!("{line:<28}# {comment}\n")
+    }
+}
+
+/// The detected project kind and the files `tast init` intends to write.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scaffold {
+    pub kind: ProjectKind,
+    pub files: Vec<(PathBuf, String)>,
+}
+
+/// Work out which files `tast init` should write into `dir`.
+pub fn scaffold(dir: &Path) -> Scaffold {
+    let kind = ProjectKind::detect(dir);
+    let name = dir
+        .canonicalize()
+        .ok()
+        .and_then(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| "my-project".to_owned());
+    let test_dir = dir.join(ProjectConfig::default().test_dir);
+
+    Scaffold {
+        kind,
+        files: vec![
+            (dir.join(CONFIG_FILE_NAME), render_config(kind, &name)),
+            (test_dir.join(EXAMPLE_FILE_NAME), EXAMPLE_GRAPH.to_owned()),
+        ],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::commands::run_validate;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("target")
+            .join("init_tests")
+            .join(name);
+        std::fs::remove_dir_all(&dir).ok();
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn detects_each_manifest() {
+        for (manifest, kind) in [
+            ("Cargo.toml", ProjectKind::Rust),
+            ("mix.exs", ProjectKind::Elixir),
+            ("package.json", ProjectKind::Node),
+            ("go.mod", ProjectKind::Go),
+        ] {
+            let dir = scratch_dir(&format!("detect_{}", kind.backend()));
+            std::fs::write(dir.join(manifest), "").unwrap();
+            assert_eq!(ProjectKind::detect(&dir), kind);
+        }
+    }
+
+    #[test]
+    fn detects_unknown_without_manifest() {
+        let dir = scratch_dir("detect_none");
+        assert_eq!(ProjectKind::detect(&dir), ProjectKind::Unknown);
+    }
+
+    #[test]
+    fn rendered_configs_are_valid() {
+        for kind in [
+            ProjectKind::Rust,
+            ProjectKind::Elixir,
+            ProjectKind::Node,
+            ProjectKind::Go,
+            ProjectKind::Unknown,
+        ] {
+            let text = render_config(kind, "demo");
+            let config = Config::from_toml(&text, Path::new(CONFIG_FILE_NAME))
+                .unwrap_or_else(|e| panic!("{kind:?}: {e}"));
+            assert_eq!(config.project.name.as_deref(), Some("demo"));
+            assert_eq!(config.runner.backend, kind.backend());
+            assert!(config.runner.backends.contains_key(kind.backend()));
+        }
+    }
+
+    #[test]
+    fn rust_config_uses_cargo_test() {
+        let config =
+            Config::from_toml(&render_config(ProjectKind::Rust, "demo"), Path::new("x")).unwrap();
+        assert_eq!(config.runner.backends["rust"].command, "cargo test");
+    }
+
+    #[test]
+    fn example_graph_validates() {
+        let path = scratch_dir("example").join(EXAMPLE_FILE_NAME);
+        std::fs::write(&path, EXAMPLE_GRAPH).unwrap();
+        let report = run_validate(&[path]).expect("example should validate");
+        assert!(
+            report.ends_with("Example is valid (2 nodes, 1 edges)"),
+            "got: {report}"
+        );
+    }
+
+    #[test]
+    fn rendered_config_escapes_names() {
+        let text = render_config(ProjectKind::Unknown, r#"my "quoted" \ project"#);
+        let config = Config::from_toml(&text, Path::new(CONFIG_FILE_NAME)).unwrap();
+        assert_eq!(
+            config.project.name.as_deref(),
+            Some(r#"my "quoted" \ project"#)
+        );
+    }
+}
@@ -15,12 +15,17 @@ pub fn emit_dot(tg: &TestGraph) -> String {
         let edge = &tg.graph[edge_idx];
         let src_name = &tg.graph[src].name;
         let dst_name = &tg.graph[dst].name;
-        if let Some(desc) = &edge.description {
-            out.push_str(&format!(
+        match (&edge.description, edge.bound) {
+            (Some(desc), Some(bound)) => out.push_str(&format!(
+                "  \"{src_name}\" -> \"{dst_name}\" [label=\"{desc} ({bound})\", style=dashed];\n"
+            )),
+            (None, Some(bound)) => out.push_str(&format!(
+                "  \"{src_name}\" -> \"{dst_name}\" [label=\"{bound}\", style=dashed];\n"
+            )),
+            (Some(desc), None) => out.push_str(&format!(
                 "  \"{src_name}\" -> \"{dst_name}\" [label=\"{desc}\"];\n"
-            ));
-        } else {
-            out.push_str(&format!("  \"{src_name}\" -> \"{dst_name}\";\n"));
+            )),
+            (None, None) => out.push_str(&format!("  \"{src_name}\" -> \"{dst_name}\";\n")),
         }
     }
 
@@ -81,4 +86,18 @@ mod tests {
         let dot = emit_dot(&tg);
         assert!(dot.contains("[label=\"flows to\"]"));
     }
+
+    #[test]
+    fn dot_marks_loop_edges() {
+        let tg = build_one(
+            r#"graph G {
+                node A {}
+                node B {}
+                A -> B
+                B -> A repeat 3 { describe "retry" }
+            }"#,
+        );
+        let dot = emit_dot(&tg);
+        assert!(dot.contains("\"B\" -> \"A\" [label=\"retry (repeat 3)\", style=dashed]"));
+    }
 }
@@ -0,0 +1,68 @@
+use crate::plan::types::TestPlan;
+
+/// Emit a test plan as JSON, indented when `pretty` is set and on a single
+/// line otherwise.
+///
+/// The output follows the schema printed by `tast schema`.
+///
+/// # Errors
+///
+/// Returns an error if JSON serialization fails.
+pub fn emit_json(plan: &TestPlan, pretty: bool) -> Result<String, String> {
+    let json = if pretty {
+        serde_json::to_string_pretty(plan)
+    } else {
+        serde_json::to_string(plan)
+    };
+    json.map(|json| json + "\n")
+        .map_err(|e| format!("json serialization failed: {e}"))
+}
+
+/// Emit several test plans as one JSON array, so that the output stays a
+/// single JSON document.
+///
+/// # Errors
+///
+/// Returns an error if JSON serialization fails.
+pub fn emit_json_plans(plans: &[TestPlan], pretty: bool) -> Result<String, String> {
+    let json = if pretty {
+        serde_json::to_string_pretty(plans)
+    } else {
+        serde_json::to_string(plans)
+    };
+    json.map(|json| json + "\n")
+        .map_err(|e| format!("json serialization failed: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::emit::test_plans::{empty_plan, multi_step_plan};
+
+    #[test]
+    fn json_round_trips() {
+        let plan = multi_step_plan();
+        let json = emit_json(&plan, true).unwrap();
+        let parsed: TestPlan = serde_json::from_str(&json).expect("deserialization failed");
+        assert_eq!(parsed, plan);
+    }
+
+    #[test]
+    fn compact_json_is_one_line() {
+        let json = emit_json(&empty_plan(), false).unwrap();
+        assert_eq!(json.lines().count(), 1);
+        assert!(json.starts_with(r#"{"schema_version":1,"plan":{"name":"Empty","#));
+        let pretty = emit_json(&empty_plan(), true).unwrap();
+        assert!(pretty.starts_with("{\n  \"schema_version\": 1,\n"));
+    }
+
+    #[test]
+    fn several_plans_form_one_array() {
+        let plans = vec![empty_plan(), multi_step_plan()];
+        let json = emit_json_plans(&plans, false).unwrap();
+        assert_eq!(json.lines().count(), 1);
+        let parsed: Vec<TestPlan> = serde_json::from_str(&json).expect("deserialization failed");
+        assert_eq!(parsed, plans);
+        assert_eq!(emit_json_plans(&[], true).unwrap(), "[]\n");
+    }
+}
@@ -6,11 +6,12 @@ use crate::plan::types::TestPlan;
 /// Emit a test plan as JUnit XML.
 ///
 /// Test cases are emitted as "not run" since this is plan-time output.
-/// Steps are included in `<system-out>` for CI visibility.
-pub fn emit_junit(plan: &TestPlan) -> String {
+/// Steps are included in `<system-out>` for CI visibility, with their raw
+/// templates when `show_templates` is set.
+pub fn emit_junit(plan: &TestPlan, show_templates: bool) -> String {
     let mut out = String::new();
     let test_count = plan.steps.len();
-    let name = xml_escape(&plan.plan.name);
+    let name = xml_escape(&plan.plan.title());
 
     writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#).unwrap();
     writeln!(
@@ -25,19 +26,23 @@ pub fn emit_junit(plan: &TestPlan) -> String {
     .unwrap();
 
     for step in &plan.steps {
-        let node = xml_escape(&step.node);
+        // Unrolled loop passes share a node, so number them to keep names unique
+        let node = match step.iteration {
+            Some(it) => xml_escape(&format!("{} [{}/{}]", step.node, it.index, it.count)),
+            None => xml_escape(&step.node),
+        };
         writeln!(out, r#"    <testcase name="{node}" classname="{name}">"#).unwrap();
 
         // Collect all step texts into system-out
         let mut lines = Vec::new();
         for entry in &step.preconditions {
-            lines.push(format_step_line(entry));
+            lines.push(format_step_line(entry, show_templates));
         }
         for entry in &step.actions {
-            lines.push(format_step_line(entry));
+            lines.push(format_step_line(entry, show_templates));
         }
         for entry in &step.assertions {
-            lines.push(format_step_line(entry));
+            lines.push(format_step_line(entry, show_templates));
         }
 
         if !lines.is_empty() {
@@ -57,9 +62,12 @@ pub fn emit_junit(plan: &TestPlan) -> String {
     out
 }
 
-fn format_step_line(entry: &crate::plan::types::StepEntry) -> String {
+fn format_step_line(entry: &crate::plan::types::StepEntry, show_templates: bool) -> String {
     let label = capitalize(&entry.step_type);
-    format!("{label} {}", entry.text)
+    match entry.template().filter(|_| show_templates) {
+        Some(template) => format!("{label} {} (template: {template})", entry.display_text()),
+        None => format!("{label} {}", entry.display_text()),
+    }
 }
 
 fn xml_escape(s: &str) -> String {
@@ -77,20 +85,20 @@ mod tests {
 
     #[test]
     fn junit_empty_plan() {
-        let xml = emit_junit(&empty_plan());
+        let xml = emit_junit(&empty_plan(), false);
         assert!(xml.contains(r#"tests="0""#));
         assert!(xml.contains("</testsuites>"));
     }
 
     #[test]
     fn junit_single_test_case() {
-        let xml = emit_junit(&single_step_plan());
+        let xml = emit_junit(&single_step_plan(), false);
         assert!(xml.contains(r#"<testcase name="Login""#));
     }
 
     #[test]
     fn junit_multiple_test_cases() {
-        let xml = emit_junit(&multi_step_plan());
+        let xml = emit_junit(&multi_step_plan(), false);
         assert!(xml.contains(r#"<testcase name="Register""#));
         assert!(xml.contains(r#"<testcase name="Login""#));
     }
@@ -99,32 +107,32 @@ mod tests {
     fn junit_escapes_xml_special_chars() {
         let mut plan = single_step_plan();
         plan.plan.name = "Test & <Suite>".into();
-        let xml = emit_junit(&plan);
+        let xml = emit_junit(&plan, false);
         assert!(xml.contains("Test &amp; &lt;Suite&gt;"));
         assert!(!xml.contains("Test & <Suite>"));
     }
 
     #[test]
     fn junit_includes_testsuite_name() {
-        let xml = emit_junit(&single_step_plan());
+        let xml = emit_junit(&single_step_plan(), false);
         assert!(xml.contains(r#"<testsuite name="Auth""#));
     }
 
     #[test]
     fn junit_includes_test_count() {
-        let xml = emit_junit(&multi_step_plan());
+        let xml = emit_junit(&multi_step_plan(), false);
         assert!(xml.contains(r#"tests="2""#));
     }
 
     #[test]
     fn junit_testcase_classname_is_graph_name() {
-        let xml = emit_junit(&single_step_plan());
+        let xml = emit_junit(&single_step_plan(), false);
         assert!(xml.contains(r#"classname="Auth""#));
     }
 
     #[test]
     fn junit_includes_steps_in_system_out() {
-        let xml = emit_junit(&single_step_plan());
+        let xml = emit_junit(&single_step_plan(), false);
         assert!(xml.contains("<system-out>"));
         assert!(xml.contains("Given a registered user"));
         assert!(xml.contains("When the user submits credentials"));
@@ -133,7 +141,7 @@ mod tests {
 
     #[test]
     fn junit_valid_xml_structure() {
-        let xml = emit_junit(&single_step_plan());
+        let xml = emit_junit(&single_step_plan(), false);
         // Check proper nesting by finding closing tags
         let testcase_close = xml.find("</testcase>").unwrap();
         let testsuite_close = xml.find("</testsuite>").unwrap();
@@ -150,7 +158,36 @@ mod tests {
 
     #[test]
     fn junit_includes_xml_declaration() {
-        let xml = emit_junit(&single_step_plan());
+        let xml = emit_junit(&single_step_plan(), false);
         assert!(xml.starts_with(r#"<?xml version="1.0" encoding="UTF-8"?>"#));
     }
+
+    #[test]
+    fn junit_uses_rendered_text() {
+        let mut plan = single_step_plan();
+        let entry = &mut plan.steps[0].actions[0];
+        entry.text = "the user submits <email>".into();
+        entry.rendered_text = Some("the user submits <email: UNRESOLVED>".into());
+        let xml = emit_junit(&plan, true);
+        assert!(xml.contains(
+            "When the user submits &lt;email: UNRESOLVED&gt; (template: the user submits &lt;email&gt;)"
+        ));
+
+        let xml = emit_junit(&plan, false);
+        assert!(xml.contains("When the user submits &lt;email: UNRESOLVED&gt;\n"));
+    }
+
+    #[test]
+    fn junit_numbers_loop_iterations() {
+        use crate::plan::types::IterationEntry;
+
+        let mut plan = single_step_plan();
+        plan.steps[0].iteration = Some(IterationEntry {
+            index: 1,
+            count: 2,
+            optional: false,
+        });
+        let xml = emit_junit(&plan, false);
+        assert!(xml.contains(r#"<testcase name="Login [1/2]""#));
+    }
 }
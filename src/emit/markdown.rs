@@ -1,14 +1,17 @@
 use std::fmt::Write;
 
 use crate::emit::util::capitalize;
-use crate::plan::types::{PlanStep, StepEntry, TestPlan};
+use crate::plan::types::{Inclusion, PlanStep, StepEntry, TestPlan};
 
 /// Emit a test plan as human-readable Markdown.
-pub fn emit_markdown(plan: &TestPlan) -> String {
+///
+/// Step entries show their rendered text; with `show_templates` the raw
+/// template is listed below it.
+pub fn emit_markdown(plan: &TestPlan, show_templates: bool) -> String {
     let mut out = String::new();
 
     // Header
-    writeln!(out, "# Test Plan: {}", plan.plan.name).unwrap();
+    writeln!(out, "# Test Plan: {}", plan.plan.title()).unwrap();
     writeln!(out).unwrap();
     writeln!(
         out,
@@ -17,18 +20,170 @@ pub fn emit_markdown(plan: &TestPlan) -> String {
     )
     .unwrap();
 
+    if !plan.plan.stages.is_empty() {
+        let widths: Vec<String> = plan.plan.stages.iter().map(ToString::to_string).collect();
+        writeln!(out).unwrap();
+        writeln!(
+            out,
+            "**Stages:** {} (widths {}) | **Critical path:** {} step{}",
+            plan.plan.stages.len(),
+            widths.join(", "),
+            plan.plan.critical_path,
+            if plan.plan.critical_path == 1 {
+                ""
+            } else {
+                "s"
+            }
+        )
+        .unwrap();
+    }
+
+    if let Some(duration) = &plan.plan.estimated_duration {
+        writeln!(out).unwrap();
+        writeln!(out, "**Estimated duration:** {duration}").unwrap();
+    }
+
+    if let Some(budget) = &plan.plan.budget {
+        let dropped = if budget.dropped.is_empty() {
+            "none".to_owned()
+        } else {
+            budget.dropped.join(", ")
+        };
+        writeln!(out).unwrap();
+        writeln!(
+            out,
+            "**Budget:** {} ({}% of {} kept) | **Dropped:** {dropped}",
+            budget.limit, budget.coverage, budget.coverage_by
+        )
+        .unwrap();
+    }
+
+    if let Some(shard) = &plan.plan.shard {
+        writeln!(out).unwrap();
+        writeln!(
+            out,
+            "**Shard:** {}/{} by {} | **Nodes:** {} own, {} setup | **Overhead:** {}% across shards",
+            shard.index, shard.total, shard.balanced_by, shard.owned, shard.setup, shard.overhead
+        )
+        .unwrap();
+    }
+
+    if !plan.plan.graphs.is_empty() {
+        let graphs: Vec<String> = plan
+            .plan
+            .graphs
+            .iter()
+            .map(|g| format!("{} ({} steps)", g.name, g.steps))
+            .collect();
+        writeln!(out).unwrap();
+        writeln!(out, "**Graphs:** {}", graphs.join(", ")).unwrap();
+    }
+
+    if let Some(path) = plan.plan.path {
+        let cov = path.coverage;
+        writeln!(out).unwrap();
+        writeln!(
+            out,
+            "**Coverage:** +{}% edges, +{}% nodes (total {}% edges, {}% nodes)",
+            cov.edges_added, cov.nodes_added, cov.edges_covered, cov.nodes_covered
+        )
+        .unwrap();
+    }
+
+    let shared = plan.plan.path.and_then(|p| p.shared_prefix);
+    if let Some(prefix) = shared {
+        writeln!(out).unwrap();
+        writeln!(
+            out,
+            "**Shared prefix:** steps 1-{} are the same as in path {}",
+            prefix.steps, prefix.with
+        )
+        .unwrap();
+    }
+
+    // Stage headings only make sense when each stage's steps are together;
+    // otherwise every step names its own stage, and the steps of a merged
+    // plan are headed by graph.
+    let by_stage = plan.in_stage_order();
+    let mut current_stage = None;
+    let mut current_graph = None;
     for step in &plan.steps {
         writeln!(out).unwrap();
         writeln!(out, "---").unwrap();
         writeln!(out).unwrap();
-        emit_step(&mut out, step);
+        if by_stage {
+            if current_stage != Some(step.stage) {
+                current_stage = Some(step.stage);
+                emit_stage_heading(&mut out, plan, step.stage);
+            }
+        } else if let Some(graph) = plan.graph_of(step)
+            && current_graph != Some(graph)
+        {
+            current_graph = Some(graph);
+            writeln!(out, "**Graph {graph}**").unwrap();
+            writeln!(out).unwrap();
+        }
+        let shared_with = shared.filter(|p| step.order <= p.steps).map(|p| p.with);
+        emit_step(&mut out, step, shared_with, !by_stage, show_templates);
     }
 
     out
 }
 
-fn emit_step(out: &mut String, step: &PlanStep) {
-    writeln!(out, "## Step {}: {}", step.order, step.node).unwrap();
+fn emit_stage_heading(out: &mut String, plan: &TestPlan, stage: usize) {
+    let width = stage
+        .checked_sub(1)
+        .and_then(|i| plan.plan.stages.get(i))
+        .copied()
+        .unwrap_or(1);
+    if width > 1 {
+        writeln!(
+            out,
+            "**Stage {stage}** ({width} steps, can run in parallel)"
+        )
+        .unwrap();
+    } else {
+        writeln!(out, "**Stage {stage}**").unwrap();
+    }
+    writeln!(out).unwrap();
+}
+
+fn emit_step(
+    out: &mut String,
+    step: &PlanStep,
+    shared_with: Option<usize>,
+    show_stage: bool,
+    show_templates: bool,
+) {
+    let mut notes = Vec::new();
+    if show_stage && step.stage > 0 {
+        notes.push(format!("stage {}", step.stage));
+    }
+    if let Some(it) = step.iteration {
+        let optional = if it.optional { ", optional" } else { "" };
+        notes.push(format!("iteration {}/{}{optional}", it.index, it.count));
+    }
+    if let Some(path) = shared_with {
+        notes.push(format!("shared with path {path}"));
+    }
+    if step.included_for == Some(Inclusion::Dependency) {
+        notes.push("included as a dependency".to_owned());
+    }
+    if let (Some(duration), Some(done)) = (&step.estimated_duration, &step.cumulative_duration) {
+        notes.push(format!("estimated {duration}, done at {done}"));
+    }
+    if notes.is_empty() {
+        writeln!(out, "## Step {}: {}", step.order, step.node).unwrap();
+    } else {
+        writeln!(
+            out,
+            "## Step {}: {} ({})",
+            step.order,
+            step.node,
+            notes.join(", ")
+        )
+        .unwrap();
+    }
     writeln!(out).unwrap();
 
     if let Some(desc) = &step.description {
@@ -50,7 +205,7 @@ fn emit_step(out: &mut String, step: &PlanStep) {
     if !step.preconditions.is_empty() {
         writeln!(out, "### Preconditions").unwrap();
         for entry in &step.preconditions {
-            emit_step_entry(out, entry);
+            emit_step_entry(out, entry, show_templates);
         }
         writeln!(out).unwrap();
     }
@@ -58,7 +213,7 @@ fn emit_step(out: &mut String, step: &PlanStep) {
     if !step.actions.is_empty() {
         writeln!(out, "### Actions").unwrap();
         for entry in &step.actions {
-            emit_step_entry(out, entry);
+            emit_step_entry(out, entry, show_templates);
         }
         writeln!(out).unwrap();
     }
@@ -66,7 +221,7 @@ fn emit_step(out: &mut String, step: &PlanStep) {
     if !step.assertions.is_empty() {
         writeln!(out, "### Assertions").unwrap();
         for entry in &step.assertions {
-            emit_step_entry(out, entry);
+            emit_step_entry(out, entry, show_templates);
         }
         writeln!(out).unwrap();
     }
@@ -88,9 +243,12 @@ fn emit_step(out: &mut String, step: &PlanStep) {
     }
 }
 
-fn emit_step_entry(out: &mut String, entry: &StepEntry) {
+fn emit_step_entry(out: &mut String, entry: &StepEntry, show_templates: bool) {
     let label = capitalize(&entry.step_type);
-    writeln!(out, "- **{label}** {}", entry.text).unwrap();
+    writeln!(out, "- **{label}** {}", entry.display_text()).unwrap();
+    if show_templates && let Some(template) = entry.template() {
+        writeln!(out, "  - template: `{template}`").unwrap();
+    }
     for (key, val) in &entry.data {
         writeln!(out, "  - `{key}`: \"{val}\"").unwrap();
     }
@@ -103,27 +261,27 @@ mod tests {
 
     #[test]
     fn markdown_empty_plan() {
-        let md = emit_markdown(&empty_plan());
+        let md = emit_markdown(&empty_plan(), false);
         assert!(md.contains("# Test Plan: Empty"));
         assert!(md.contains("**Nodes:** 0"));
     }
 
     #[test]
     fn markdown_single_step() {
-        let md = emit_markdown(&single_step_plan());
+        let md = emit_markdown(&single_step_plan(), false);
         assert!(md.contains("## Step 1: Login"));
     }
 
     #[test]
     fn markdown_multi_step() {
-        let md = emit_markdown(&multi_step_plan());
+        let md = emit_markdown(&multi_step_plan(), false);
         assert!(md.contains("## Step 1: Register"));
         assert!(md.contains("## Step 2: Login"));
     }
 
     #[test]
     fn markdown_includes_plan_header() {
-        let md = emit_markdown(&single_step_plan());
+        let md = emit_markdown(&single_step_plan(), false);
         assert!(md.contains("# Test Plan: Auth"));
         assert!(md.contains("**Traversal:** topological"));
         assert!(md.contains("**Nodes:** 1"));
@@ -132,25 +290,25 @@ mod tests {
 
     #[test]
     fn markdown_includes_step_description() {
-        let md = emit_markdown(&single_step_plan());
+        let md = emit_markdown(&single_step_plan(), false);
         assert!(md.contains("> User logs in"));
     }
 
     #[test]
     fn markdown_includes_tags() {
-        let md = emit_markdown(&single_step_plan());
+        let md = emit_markdown(&single_step_plan(), false);
         assert!(md.contains("**Tags:** `smoke`"));
     }
 
     #[test]
     fn markdown_includes_depends_on() {
-        let md = emit_markdown(&multi_step_plan());
+        let md = emit_markdown(&multi_step_plan(), false);
         assert!(md.contains("**Depends on:** Register"));
     }
 
     #[test]
     fn markdown_includes_preconditions_actions_assertions() {
-        let md = emit_markdown(&single_step_plan());
+        let md = emit_markdown(&single_step_plan(), false);
         assert!(md.contains("### Preconditions"));
         assert!(md.contains("- **Given** a registered user"));
         assert!(md.contains("### Actions"));
@@ -161,7 +319,7 @@ mod tests {
 
     #[test]
     fn markdown_includes_data_flow() {
-        let md = emit_markdown(&multi_step_plan());
+        let md = emit_markdown(&multi_step_plan(), false);
         assert!(md.contains("### Data Flow"));
         assert!(md.contains("**Inputs:** user_id (from Register)"));
         assert!(md.contains("**Outputs:** user_id"));
@@ -169,7 +327,105 @@ mod tests {
 
     #[test]
     fn markdown_step_data_shown_as_list() {
-        let md = emit_markdown(&single_step_plan());
+        let md = emit_markdown(&single_step_plan(), false);
         assert!(md.contains("  - `email`: \"test@example.com\""));
     }
+
+    #[test]
+    fn markdown_shows_rendered_text_and_template_on_request() {
+        let mut plan = single_step_plan();
+        let entry = &mut plan.steps[0].actions[0];
+        entry.text = "the user submits <email>".into();
+        entry.rendered_text = Some("the user submits test@example.com".into());
+        let md = emit_markdown(&plan, true);
+        assert!(md.contains("- **When** the user submits test@example.com"));
+        assert!(md.contains("  - template: `the user submits <email>`"));
+
+        let md = emit_markdown(&plan, false);
+        assert!(md.contains("- **When** the user submits test@example.com"));
+        assert!(!md.contains("template:"));
+    }
+
+    #[test]
+    fn markdown_heading_shows_loop_iteration() {
+        use crate::plan::types::IterationEntry;
+
+        let mut plan = single_step_plan();
+        plan.steps[0].iteration = Some(IterationEntry {
+            index: 2,
+            count: 3,
+            optional: true,
+        });
+        let md = emit_markdown(&plan, false);
+        assert!(md.contains("## Step 1: Login (iteration 2/3, optional)"));
+    }
+
+    #[test]
+    fn markdown_marks_shared_path_prefix() {
+        use crate::plan::types::{CoverageEntry, PathEntry, SharedPrefix};
+
+        let mut plan = multi_step_plan();
+        plan.plan.path = Some(PathEntry {
+            index: 2,
+            total: 3,
+            shared_prefix: Some(SharedPrefix { with: 1, steps: 1 }),
+            coverage: CoverageEntry {
+                edges_added: 25.0,
+                nodes_added: 12.5,
+                edges_covered: 75.0,
+                nodes_covered: 100.0,
+            },
+        });
+        let md = emit_markdown(&plan, false);
+        assert!(md.contains("# Test Plan: AuthFlow (path 2/3)"));
+        assert!(
+            md.contains("**Coverage:** +25% edges, +12.5% nodes (total 75% edges, 100% nodes)")
+        );
+        assert!(md.contains("**Shared prefix:** steps 1-1 are the same as in path 1"));
+        assert!(md.contains("## Step 1: Register (shared with path 1)"));
+        assert!(md.contains("## Step 2: Login\n"));
+    }
+
+    #[test]
+    fn markdown_groups_steps_by_stage() {
+        let mut plan = multi_step_plan();
+        let md = emit_markdown(&plan, false);
+        assert!(md.contains("**Stages:** 2 (widths 1, 1) | **Critical path:** 2 steps"));
+        assert!(md.contains("**Stage 1**\n\n## Step 1: Register"));
+        assert!(md.contains("**Stage 2**\n\n## Step 2: Login"));
+
+        plan.steps[1].stage = 1;
+        plan.recount_stages();
+        let md = emit_markdown(&plan, false);
+        assert!(md.contains("**Stages:** 1 (widths 2) | **Critical path:** 1 step"));
+        assert!(md.contains("**Stage 1** (2 steps, can run in parallel)"));
+        assert!(!md.contains("**Stage 2**"));
+
+        plan.steps[0].stage = 2;
+        let md = emit_markdown(&plan, false);
+        assert!(!md.contains("**Stage 1**"), "got: {md}");
+        assert!(md.contains("## Step 1: Register (stage 2)"));
+        assert!(md.contains("## Step 2: Login (stage 1)"));
+    }
+
+    #[test]
+    fn markdown_marks_dependency_steps() {
+        let mut plan = multi_step_plan();
+        plan.steps[0].included_for = Some(Inclusion::Dependency);
+        let md = emit_markdown(&plan, false);
+        assert!(md.contains("## Step 1: Register (included as a dependency)"));
+        assert!(md.contains("## Step 2: Login\n"));
+    }
+
+    #[test]
+    fn markdown_shows_estimated_durations() {
+        let mut plan = multi_step_plan();
+        plan.steps[0].estimated_duration = Some("30s".to_owned());
+        plan.steps[1].estimated_duration = Some("1m".to_owned());
+        plan.recount_durations();
+        let md = emit_markdown(&plan, false);
+        assert!(md.contains("**Estimated duration:** 1m30s"));
+        assert!(md.contains("## Step 1: Register (estimated 30s, done at 30s)"));
+        assert!(md.contains("## Step 2: Login (estimated 1m, done at 1m30s)"));
+    }
 }
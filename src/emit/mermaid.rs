@@ -15,10 +15,19 @@ pub fn emit_mermaid(tg: &TestGraph) -> String {
         let edge = &tg.graph[edge_idx];
         let src_name = &tg.graph[src].name;
         let dst_name = &tg.graph[dst].name;
-        if let Some(desc) = &edge.description {
-            out.push_str(&format!("  {src_name} -->|\"{desc}\"| {dst_name}\n"));
-        } else {
-            out.push_str(&format!("  {src_name} --> {dst_name}\n"));
+        match (&edge.description, edge.bound) {
+            (Some(desc), Some(bound)) => {
+                out.push_str(&format!(
+                    "  {src_name} -.->|\"{desc} ({bound})\"| {dst_name}\n"
+                ));
+            }
+            (None, Some(bound)) => {
+                out.push_str(&format!("  {src_name} -.->|\"{bound}\"| {dst_name}\n"));
+            }
+            (Some(desc), None) => {
+                out.push_str(&format!("  {src_name} -->|\"{desc}\"| {dst_name}\n"));
+            }
+            (None, None) => out.push_str(&format!("  {src_name} --> {dst_name}\n")),
         }
     }
 
@@ -77,4 +86,18 @@ mod tests {
         let md = emit_mermaid(&tg);
         assert!(md.contains("|\"flows to\"|"));
     }
+
+    #[test]
+    fn mermaid_marks_loop_edges() {
+        let tg = build_one(
+            r#"graph G {
+                node A {}
+                node B {}
+                A -> B
+                B -> A max 4
+            }"#,
+        );
+        let md = emit_mermaid(&tg);
+        assert!(md.contains("B -.->|\"max 4\"| A"));
+    }
 }
@@ -1,4 +1,5 @@
 pub mod dot;
+pub mod json;
 pub mod junit;
 pub mod markdown;
 pub mod mermaid;
@@ -2,15 +2,27 @@
 ///
 /// These build `TestPlan` values that are rich enough (tags, data, inputs,
 /// outputs, descriptions) for every emitter's test suite to use.
-use crate::plan::types::{InputEntry, PlanMetadata, PlanStep, StepEntry, TestPlan};
+use crate::plan::types::{
+    InputEntry, PLAN_SCHEMA_VERSION, PlanMetadata, PlanStep, StepEntry, TestPlan,
+};
 
 pub fn empty_plan() -> TestPlan {
     TestPlan {
+        schema_version: PLAN_SCHEMA_VERSION,
         plan: PlanMetadata {
             name: "Empty".into(),
             traversal: "topological".into(),
             nodes_total: 0,
             edges_total: 0,
+            stages: vec![],
+            critical_path: 0,
+            path: None,
+            seed: None,
+            uniform: None,
+            estimated_duration: None,
+            budget: None,
+            shard: None,
+            graphs: Vec::new(),
         },
         steps: vec![],
     }
@@ -18,14 +30,25 @@ pub fn empty_plan() -> TestPlan {
 
 pub fn single_step_plan() -> TestPlan {
     TestPlan {
+        schema_version: PLAN_SCHEMA_VERSION,
         plan: PlanMetadata {
             name: "Auth".into(),
             traversal: "topological".into(),
             nodes_total: 1,
             edges_total: 0,
+            stages: vec![1],
+            critical_path: 1,
+            path: None,
+            seed: None,
+            uniform: None,
+            estimated_duration: None,
+            budget: None,
+            shard: None,
+            graphs: Vec::new(),
         },
         steps: vec![PlanStep {
             order: 1,
+            stage: 1,
             node: "Login".into(),
             description: Some("User logs in".into()),
             tags: vec!["smoke".into()],
@@ -33,38 +56,56 @@ pub fn single_step_plan() -> TestPlan {
             preconditions: vec![StepEntry {
                 step_type: "given".into(),
                 text: "a registered user".into(),
+                rendered_text: None,
                 data: vec![("email".into(), "test@example.com".into())],
                 parameters: vec![],
             }],
             actions: vec![StepEntry {
                 step_type: "when".into(),
                 text: "the user submits credentials".into(),
+                rendered_text: None,
                 data: vec![],
                 parameters: vec![],
             }],
             assertions: vec![StepEntry {
                 step_type: "then".into(),
                 text: "the system returns a token".into(),
+                rendered_text: None,
                 data: vec![],
                 parameters: vec![],
             }],
             inputs: vec![],
             outputs: vec!["auth_token".into()],
+            iteration: None,
+            included_for: None,
+            estimated_duration: None,
+            cumulative_duration: None,
         }],
     }
 }
 
 pub fn multi_step_plan() -> TestPlan {
     TestPlan {
+        schema_version: PLAN_SCHEMA_VERSION,
         plan: PlanMetadata {
             name: "AuthFlow".into(),
             traversal: "topological".into(),
             nodes_total: 2,
             edges_total: 1,
+            stages: vec![1, 1],
+            critical_path: 2,
+            path: None,
+            seed: None,
+            uniform: None,
+            estimated_duration: None,
+            budget: None,
+            shard: None,
+            graphs: Vec::new(),
         },
         steps: vec![
             PlanStep {
                 order: 1,
+                stage: 1,
                 node: "Register".into(),
                 description: Some("New user registers".into()),
                 tags: vec![],
@@ -72,6 +113,7 @@ pub fn multi_step_plan() -> TestPlan {
                 preconditions: vec![StepEntry {
                     step_type: "given".into(),
                     text: "a new user".into(),
+                    rendered_text: None,
                     data: vec![],
                     parameters: vec![],
                 }],
@@ -79,14 +121,20 @@ pub fn multi_step_plan() -> TestPlan {
                 assertions: vec![StepEntry {
                     step_type: "then".into(),
                     text: "the account is created".into(),
+                    rendered_text: None,
                     data: vec![],
                     parameters: vec![],
                 }],
                 inputs: vec![],
                 outputs: vec!["user_id".into()],
+                iteration: None,
+                included_for: None,
+                estimated_duration: None,
+                cumulative_duration: None,
             },
             PlanStep {
                 order: 2,
+                stage: 2,
                 node: "Login".into(),
                 description: None,
                 tags: vec![],
@@ -95,6 +143,7 @@ pub fn multi_step_plan() -> TestPlan {
                 actions: vec![StepEntry {
                     step_type: "when".into(),
                     text: "the user logs in".into(),
+                    rendered_text: None,
                     data: vec![],
                     parameters: vec![],
                 }],
@@ -104,6 +153,10 @@ pub fn multi_step_plan() -> TestPlan {
                     from: "Register".into(),
                 }],
                 outputs: vec![],
+                iteration: None,
+                included_for: None,
+                estimated_duration: None,
+                cumulative_duration: None,
             },
         ],
     }
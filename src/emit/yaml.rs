@@ -2,25 +2,75 @@ use crate::plan::types::TestPlan;
 
 /// Emit a test plan as YAML.
 ///
+/// Steps stay in plan order. When they are in stage order, a `# stage N`
+/// comment goes before each step that starts a new stage. Otherwise a merged
+/// plan gets a `# graph NAME` comment before the first step of each graph.
+///
 /// # Errors
 ///
 /// Returns an error if YAML serialization fails.
 pub fn emit_yaml(plan: &TestPlan) -> Result<String, String> {
-    serde_yaml::to_string(plan).map_err(|e| format!("yaml serialization failed: {e}"))
+    let yaml =
+        serde_yaml::to_string(plan).map_err(|e| format!("yaml serialization failed: {e}"))?;
+    let markers: Vec<Option<String>> = if plan.in_stage_order() {
+        plan.steps
+            .iter()
+            .map(|s| (s.stage > 0).then(|| format!("stage {}", s.stage)))
+            .collect()
+    } else {
+        plan.steps
+            .iter()
+            .map(|s| plan.graph_of(s).map(|g| format!("graph {g}")))
+            .collect()
+    };
+    Ok(mark_groups(&yaml, &markers))
+}
+
+/// Insert a `# <marker>` comment before each top-level step whose marker
+/// differs from the one before it.
+fn mark_groups(yaml: &str, markers: &[Option<String>]) -> String {
+    let mut markers = markers.iter();
+    let mut current = None;
+    let mut out = String::with_capacity(yaml.len());
+    for line in yaml.lines() {
+        // Steps are the only top-level sequence; nested items are indented
+        if line.starts_with("- order: ")
+            && let Some(marker) = markers.next()
+            && marker.as_ref() != current
+        {
+            current = marker.as_ref();
+            if let Some(marker) = marker {
+                out.push_str(&format!("# {marker}\n"));
+            }
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    out
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::plan::types::{InputEntry, PlanMetadata, PlanStep, StepEntry};
+    use crate::plan::types::{InputEntry, PLAN_SCHEMA_VERSION, PlanMetadata, PlanStep, StepEntry};
 
     fn empty_plan() -> TestPlan {
         TestPlan {
+            schema_version: PLAN_SCHEMA_VERSION,
             plan: PlanMetadata {
                 name: "Empty".into(),
                 traversal: "topological".into(),
                 nodes_total: 0,
                 edges_total: 0,
+                stages: vec![],
+                critical_path: 0,
+                path: None,
+                seed: None,
+                uniform: None,
+                estimated_duration: None,
+                budget: None,
+                shard: None,
+                graphs: Vec::new(),
             },
             steps: vec![],
         }
@@ -28,14 +78,25 @@ mod tests {
 
     fn single_step_plan() -> TestPlan {
         TestPlan {
+            schema_version: PLAN_SCHEMA_VERSION,
             plan: PlanMetadata {
                 name: "G".into(),
                 traversal: "topological".into(),
                 nodes_total: 1,
                 edges_total: 0,
+                stages: vec![],
+                critical_path: 0,
+                path: None,
+                seed: None,
+                uniform: None,
+                estimated_duration: None,
+                budget: None,
+                shard: None,
+                graphs: Vec::new(),
             },
             steps: vec![PlanStep {
                 order: 1,
+                stage: 1,
                 node: "A".into(),
                 description: Some("Node A".into()),
                 tags: vec![],
@@ -43,23 +104,30 @@ mod tests {
                 preconditions: vec![StepEntry {
                     step_type: "given".into(),
                     text: "a user".into(),
+                    rendered_text: None,
                     data: vec![],
                     parameters: vec![],
                 }],
                 actions: vec![StepEntry {
                     step_type: "when".into(),
                     text: "the user acts".into(),
+                    rendered_text: None,
                     data: vec![],
                     parameters: vec![],
                 }],
                 assertions: vec![StepEntry {
                     step_type: "then".into(),
                     text: "something happens".into(),
+                    rendered_text: None,
                     data: vec![],
                     parameters: vec![],
                 }],
                 inputs: vec![],
                 outputs: vec![],
+                iteration: None,
+                included_for: None,
+                estimated_duration: None,
+                cumulative_duration: None,
             }],
         }
     }
@@ -83,6 +151,7 @@ mod tests {
         let mut plan = single_step_plan();
         plan.steps.push(PlanStep {
             order: 2,
+            stage: 1,
             node: "B".into(),
             description: None,
             tags: vec![],
@@ -92,6 +161,10 @@ mod tests {
             assertions: vec![],
             inputs: vec![],
             outputs: vec![],
+            iteration: None,
+            included_for: None,
+            estimated_duration: None,
+            cumulative_duration: None,
         });
         plan.plan.nodes_total = 2;
         let yaml = emit_yaml(&plan).expect("emit failed");
@@ -131,15 +204,26 @@ mod tests {
     #[test]
     fn emits_step_with_inputs_and_outputs() {
         let plan = TestPlan {
+            schema_version: PLAN_SCHEMA_VERSION,
             plan: PlanMetadata {
                 name: "G".into(),
                 traversal: "topological".into(),
                 nodes_total: 2,
                 edges_total: 1,
+                stages: vec![],
+                critical_path: 0,
+                path: None,
+                seed: None,
+                uniform: None,
+                estimated_duration: None,
+                budget: None,
+                shard: None,
+                graphs: Vec::new(),
             },
             steps: vec![
                 PlanStep {
                     order: 1,
+                    stage: 1,
                     node: "A".into(),
                     description: None,
                     tags: vec![],
@@ -149,9 +233,14 @@ mod tests {
                     assertions: vec![],
                     inputs: vec![],
                     outputs: vec!["token".into()],
+                    iteration: None,
+                    included_for: None,
+                    estimated_duration: None,
+                    cumulative_duration: None,
                 },
                 PlanStep {
                     order: 2,
+                    stage: 1,
                     node: "B".into(),
                     description: None,
                     tags: vec![],
@@ -164,6 +253,10 @@ mod tests {
                         from: "A".into(),
                     }],
                     outputs: vec![],
+                    iteration: None,
+                    included_for: None,
+                    estimated_duration: None,
+                    cumulative_duration: None,
                 },
             ],
         };
@@ -176,14 +269,25 @@ mod tests {
     #[test]
     fn emits_step_with_depends_on() {
         let plan = TestPlan {
+            schema_version: PLAN_SCHEMA_VERSION,
             plan: PlanMetadata {
                 name: "G".into(),
                 traversal: "topological".into(),
                 nodes_total: 2,
                 edges_total: 1,
+                stages: vec![],
+                critical_path: 0,
+                path: None,
+                seed: None,
+                uniform: None,
+                estimated_duration: None,
+                budget: None,
+                shard: None,
+                graphs: Vec::new(),
             },
             steps: vec![PlanStep {
                 order: 1,
+                stage: 1,
                 node: "B".into(),
                 description: None,
                 tags: vec![],
@@ -193,6 +297,10 @@ mod tests {
                 assertions: vec![],
                 inputs: vec![],
                 outputs: vec![],
+                iteration: None,
+                included_for: None,
+                estimated_duration: None,
+                cumulative_duration: None,
             }],
         };
         let yaml = emit_yaml(&plan).expect("emit failed");
@@ -224,14 +332,25 @@ mod tests {
         use crate::plan::types::ParameterEntry;
 
         let plan = TestPlan {
+            schema_version: PLAN_SCHEMA_VERSION,
             plan: PlanMetadata {
                 name: "ParamTest".into(),
                 traversal: "topological".into(),
                 nodes_total: 1,
                 edges_total: 0,
+                stages: vec![],
+                critical_path: 0,
+                path: None,
+                seed: None,
+                uniform: None,
+                estimated_duration: None,
+                budget: None,
+                shard: None,
+                graphs: Vec::new(),
             },
             steps: vec![PlanStep {
                 order: 1,
+                stage: 1,
                 node: "A".into(),
                 description: None,
                 tags: vec![],
@@ -239,6 +358,7 @@ mod tests {
                 preconditions: vec![StepEntry {
                     step_type: "given".into(),
                     text: "a user with email <email>".into(),
+                    rendered_text: Some("a user with email test@example.com".into()),
                     data: vec![],
                     parameters: vec![ParameterEntry {
                         name: "email".into(),
@@ -250,11 +370,34 @@ mod tests {
                 assertions: vec![],
                 inputs: vec![],
                 outputs: vec![],
+                iteration: None,
+                included_for: None,
+                estimated_duration: None,
+                cumulative_duration: None,
             }],
         };
         let yaml = emit_yaml(&plan).expect("emit failed");
         assert!(yaml.contains("parameters:"));
         assert!(yaml.contains("email"));
         assert!(yaml.contains("test@example.com"));
+        assert!(yaml.contains("rendered_text: a user with email test@example.com"));
+    }
+
+    #[test]
+    fn yaml_marks_where_stages_start() {
+        let mut plan = crate::emit::test_plans::multi_step_plan();
+        let yaml = emit_yaml(&plan).expect("emit failed");
+        assert!(yaml.contains("steps:\n# stage 1\n- order: 1\n"), "{yaml}");
+        assert!(yaml.contains("# stage 2\n- order: 2\n"));
+
+        plan.steps[1].stage = 1;
+        let yaml = emit_yaml(&plan).expect("emit failed");
+        assert_eq!(yaml.matches("# stage").count(), 1);
+        let parsed: TestPlan = serde_yaml::from_str(&yaml).expect("deserialization failed");
+        assert_eq!(parsed, plan);
+
+        plan.steps[0].stage = 2;
+        let yaml = emit_yaml(&plan).expect("emit failed");
+        assert!(!yaml.contains("# stage"), "{yaml}");
     }
 }
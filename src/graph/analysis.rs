@@ -1,8 +1,14 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
 use petgraph::Direction;
-use petgraph::algo::toposort;
-use petgraph::graph::NodeIndex;
+use petgraph::algo::dominators::simple_fast;
+use petgraph::algo::{kosaraju_scc, toposort};
+use petgraph::graph::{DiGraph, EdgeIndex, NodeIndex};
+use petgraph::visit::EdgeRef;
 
 use crate::graph::builder::TestGraph;
+use crate::graph::dataflow::{ForwardMode, analyze};
+use crate::parser::ast::LoopBound;
 
 /// Returns `true` if the graph contains a cycle.
 pub fn has_cycle(tg: &TestGraph) -> bool {
@@ -12,8 +18,6 @@ pub fn has_cycle(tg: &TestGraph) -> bool {
 /// Find a cycle in the graph, returning the node names in the cycle path.
 /// Returns `None` if the graph is acyclic.
 pub fn find_cycle(tg: &TestGraph) -> Option<Vec<String>> {
-    use std::collections::HashSet;
-
     // DFS-based cycle detection with path tracking
     let mut visited = HashSet::new();
     let mut in_stack = HashSet::new();
@@ -34,8 +38,8 @@ pub fn find_cycle(tg: &TestGraph) -> Option<Vec<String>> {
 fn dfs_find_cycle(
     tg: &TestGraph,
     node: NodeIndex,
-    visited: &mut std::collections::HashSet<NodeIndex>,
-    in_stack: &mut std::collections::HashSet<NodeIndex>,
+    visited: &mut HashSet<NodeIndex>,
+    in_stack: &mut HashSet<NodeIndex>,
     stack_path: &mut Vec<NodeIndex>,
 ) -> Option<Vec<String>> {
     visited.insert(node);
@@ -91,6 +95,376 @@ pub fn leaf_nodes(tg: &TestGraph) -> Vec<NodeIndex> {
         .collect()
 }
 
+/// `nodes` plus every node that can reach one of them, in declaration order.
+pub fn ancestors(tg: &TestGraph, nodes: &[NodeIndex]) -> Vec<NodeIndex> {
+    closure(tg, nodes, Direction::Incoming)
+}
+
+/// `nodes` plus every node reachable from one of them, in declaration order.
+pub fn descendants(tg: &TestGraph, nodes: &[NodeIndex]) -> Vec<NodeIndex> {
+    closure(tg, nodes, Direction::Outgoing)
+}
+
+/// Nodes to rerun when `changed` nodes change: everything downstream of
+/// them, plus the ancestors those nodes need for setup. In declaration order.
+pub fn impact_closure(tg: &TestGraph, changed: &[NodeIndex]) -> Vec<NodeIndex> {
+    ancestors(tg, &descendants(tg, changed))
+}
+
+fn closure(tg: &TestGraph, nodes: &[NodeIndex], direction: Direction) -> Vec<NodeIndex> {
+    let mut seen: HashSet<NodeIndex> = nodes.iter().copied().collect();
+    let mut pending = nodes.to_vec();
+    while let Some(node) = pending.pop() {
+        for next in tg.graph.neighbors_directed(node, direction) {
+            if seen.insert(next) {
+                pending.push(next);
+            }
+        }
+    }
+    tg.node_indices
+        .iter()
+        .filter(|idx| seen.contains(idx))
+        .copied()
+        .collect()
+}
+
+/// A longest chain of nodes connected by edges, ignoring loop edges. Empty
+/// for an empty graph.
+///
+/// Of equally long chains, the one starting at the earliest declared node
+/// wins. Cycles without a loop edge are cut where the search re-enters them.
+pub fn longest_chain(tg: &TestGraph) -> Vec<NodeIndex> {
+    // Best chain length starting at each node, and the next node on it
+    let mut best: HashMap<NodeIndex, (usize, Option<NodeIndex>)> = HashMap::new();
+    let mut on_stack = HashSet::new();
+    for &start in &tg.node_indices {
+        chain_from(tg, start, &mut best, &mut on_stack);
+    }
+    let Some(mut node) = tg
+        .node_indices
+        .iter()
+        .copied()
+        .rev()
+        .max_by_key(|idx| best[idx].0)
+    else {
+        return Vec::new();
+    };
+    let mut chain = vec![node];
+    while let Some(next) = best[&node].1 {
+        chain.push(next);
+        node = next;
+    }
+    chain
+}
+
+/// A node whose chain is being worked out: its successors along non-loop
+/// edges in declaration order, the next one to visit, and the best chain
+/// found so far.
+struct ChainFrame {
+    node: NodeIndex,
+    targets: Vec<NodeIndex>,
+    next: usize,
+    best: (usize, Option<NodeIndex>),
+}
+
+impl ChainFrame {
+    fn new(tg: &TestGraph, node: NodeIndex) -> Self {
+        let mut edges: Vec<_> = tg
+            .graph
+            .edges_directed(node, Direction::Outgoing)
+            .filter(|e| !tg.loops.contains(&e.id()))
+            .collect();
+        edges.sort_by_key(|e| e.id());
+        Self {
+            node,
+            targets: edges.iter().map(|e| e.target()).collect(),
+            next: 0,
+            best: (1, None),
+        }
+    }
+
+    fn offer(&mut self, target: NodeIndex, len: usize) {
+        if len + 1 > self.best.0 {
+            self.best = (len + 1, Some(target));
+        }
+    }
+}
+
+/// Depth-first search from `start` filling in `best`, with an explicit stack
+/// so that long chains cannot overflow the call stack. It runs on graphs with
+/// unbounded cycles too, so it cannot rely on a topological order.
+fn chain_from(
+    tg: &TestGraph,
+    start: NodeIndex,
+    best: &mut HashMap<NodeIndex, (usize, Option<NodeIndex>)>,
+    on_stack: &mut HashSet<NodeIndex>,
+) {
+    if best.contains_key(&start) {
+        return;
+    }
+    on_stack.insert(start);
+    let mut stack = vec![ChainFrame::new(tg, start)];
+    while let Some(frame) = stack.last_mut() {
+        if let Some(&target) = frame.targets.get(frame.next) {
+            frame.next += 1;
+            if let Some(&(len, _)) = best.get(&target) {
+                frame.offer(target, len);
+            } else if on_stack.insert(target) {
+                stack.push(ChainFrame::new(tg, target));
+            }
+            continue;
+        }
+        let done = stack.pop().expect("stack is not empty");
+        on_stack.remove(&done.node);
+        best.insert(done.node, done.best);
+        if let Some(parent) = stack.last_mut() {
+            parent.offer(done.node, done.best.0);
+        }
+    }
+}
+
+/// Groups of nodes connected when edge direction is ignored, each in
+/// declaration order, ordered by their first node.
+pub fn weak_components(tg: &TestGraph) -> Vec<Vec<NodeIndex>> {
+    let mut seen = HashSet::new();
+    let mut components = Vec::new();
+    for &start in &tg.node_indices {
+        if !seen.insert(start) {
+            continue;
+        }
+        let mut members = HashSet::from([start]);
+        let mut pending = vec![start];
+        while let Some(node) = pending.pop() {
+            for next in tg.graph.neighbors_undirected(node) {
+                if seen.insert(next) {
+                    members.insert(next);
+                    pending.push(next);
+                }
+            }
+        }
+        components.push(
+            tg.node_indices
+                .iter()
+                .filter(|idx| members.contains(idx))
+                .copied()
+                .collect(),
+        );
+    }
+    components
+}
+
+/// Strongly connected components that form a cycle: more than one node, or
+/// a single node with an edge to itself. Each in declaration order, ordered
+/// by their first node.
+pub fn cyclic_components(tg: &TestGraph) -> Vec<Vec<NodeIndex>> {
+    let mut components: Vec<Vec<NodeIndex>> = kosaraju_scc(&tg.graph)
+        .into_iter()
+        .filter(|c| c.len() > 1 || tg.graph.contains_edge(c[0], c[0]))
+        .map(|c| {
+            tg.node_indices
+                .iter()
+                .filter(|idx| c.contains(idx))
+                .copied()
+                .collect()
+        })
+        .collect();
+    components.sort_by_key(|c: &Vec<NodeIndex>| c[0]);
+    components
+}
+
+/// For each node that dominates others, the number of nodes it dominates:
+/// nodes that can only be reached from the roots through it, so fail to run
+/// when it fails. Sorted by that number, largest first, then by declaration.
+///
+/// Dominators are computed from a virtual entry linked to every root, and to
+/// one node of each cycle that no root reaches.
+pub fn dominated_counts(tg: &TestGraph) -> Vec<(NodeIndex, usize)> {
+    let mut flow: DiGraph<(), ()> = tg.graph.map(|_, _| (), |_, _| ());
+    let entry = flow.add_node(());
+    let roots = root_nodes(tg);
+    let mut reached: HashSet<NodeIndex> = descendants(tg, &roots).into_iter().collect();
+    for &root in &roots {
+        flow.add_edge(entry, root, ());
+    }
+    for &idx in &tg.node_indices {
+        if !reached.contains(&idx) {
+            flow.add_edge(entry, idx, ());
+            reached.extend(descendants(tg, &[idx]));
+        }
+    }
+
+    let dominators = simple_fast(&flow, entry);
+    let mut counts: HashMap<NodeIndex, usize> = HashMap::new();
+    for &idx in &tg.node_indices {
+        let mut current = dominators.immediate_dominator(idx);
+        while let Some(dominator) = current.filter(|&d| d != entry) {
+            *counts.entry(dominator).or_default() += 1;
+            current = dominators.immediate_dominator(dominator);
+        }
+    }
+    let mut counts: Vec<(NodeIndex, usize)> = tg
+        .node_indices
+        .iter()
+        .filter_map(|idx| counts.get(idx).map(|&n| (*idx, n)))
+        .collect();
+    counts.sort_by_key(|&(_, n)| std::cmp::Reverse(n));
+    counts
+}
+
+/// The loop bound governing each node on a bounded cycle.
+///
+/// Every node in a strongly connected component that contains a loop edge
+/// gets that edge's bound. Validation allows one bounded edge per component,
+/// so loops never nest; should a graph that skipped validation have more,
+/// the largest bound wins. Loop edges that close no cycle are ignored.
+pub fn loop_bounds(tg: &TestGraph) -> HashMap<NodeIndex, LoopBound> {
+    let mut bounds = HashMap::new();
+    if tg.loops.is_empty() {
+        return bounds;
+    }
+    for component in kosaraju_scc(&tg.graph) {
+        let bound = tg
+            .loops
+            .iter()
+            .filter(|&&e| {
+                let (from, to) = tg.graph.edge_endpoints(e).unwrap();
+                component.contains(&from) && component.contains(&to)
+            })
+            .filter_map(|&e| tg.graph[e].bound)
+            .max_by_key(|b| b.count());
+        if let Some(bound) = bound {
+            bounds.extend(component.iter().map(|&n| (n, bound)));
+        }
+    }
+    bounds
+}
+
+/// Edges the transitive reduction drops, in declaration order.
+///
+/// An edge `A -> C` is redundant when a path through at least one other
+/// node, such as `A -> B -> C`, already orders `A` before `C`, and removing
+/// the edge changes no field available at `C` nor where any of them comes
+/// from: everything it `passes` or forwards also arrives along other paths,
+/// from the same origin nodes. Loop edges are never redundant, and nor are
+/// plain duplicates of an edge.
+///
+/// Edges are dropped one at a time, so the edges left over still order and
+/// feed every node as before.
+pub fn redundant_edges(tg: &TestGraph, mode: ForwardMode) -> Vec<EdgeIndex> {
+    let mut removed = HashSet::new();
+    let mut flow = analyze(tg, mode);
+    for edge in tg.graph.edge_indices() {
+        if tg.loops.contains(&edge) {
+            continue;
+        }
+        let (from, to) = tg.graph.edge_endpoints(edge).unwrap();
+        if from == to || !has_longer_path(tg, &removed, from, to) {
+            continue;
+        }
+        let fields = &flow.at(to).may;
+        let origins = field_origins(&without_edges(tg, &removed), to, fields, mode);
+        removed.insert(edge);
+        let reduced = without_edges(tg, &removed);
+        let reduced_flow = analyze(&reduced, mode);
+        if reduced_flow.at(to) == flow.at(to)
+            && field_origins(&reduced, to, fields, mode) == origins
+        {
+            flow = reduced_flow;
+        } else {
+            removed.remove(&edge);
+        }
+    }
+    let mut edges: Vec<EdgeIndex> = removed.into_iter().collect();
+    edges.sort();
+    edges
+}
+
+/// For each of `fields`, the sources of the edges that pass it towards
+/// `node`, found by walking back through nodes that forward it under `mode`.
+fn field_origins(
+    tg: &TestGraph,
+    node: NodeIndex,
+    fields: &BTreeSet<String>,
+    mode: ForwardMode,
+) -> BTreeMap<String, BTreeSet<NodeIndex>> {
+    fields
+        .iter()
+        .map(|field| {
+            let mut sources = BTreeSet::new();
+            let mut seen = HashSet::from([node]);
+            let mut pending = vec![node];
+            while let Some(current) = pending.pop() {
+                for edge in tg.graph.edges_directed(current, Direction::Incoming) {
+                    let source = edge.source();
+                    if edge.weight().passes.contains(field) {
+                        sources.insert(source);
+                    } else if mode.forwards(&tg.graph[source], field) && seen.insert(source) {
+                        pending.push(source);
+                    }
+                }
+            }
+            (field.clone(), sources)
+        })
+        .collect()
+}
+
+/// `tg` without its [`redundant_edges`].
+pub fn transitive_reduction(tg: &TestGraph, mode: ForwardMode) -> TestGraph {
+    let removed = redundant_edges(tg, mode).into_iter().collect();
+    without_edges(tg, &removed)
+}
+
+/// A copy of `tg` without the `removed` edges. Node indices are unchanged.
+fn without_edges(tg: &TestGraph, removed: &HashSet<EdgeIndex>) -> TestGraph {
+    let mut graph = DiGraph::with_capacity(tg.graph.node_count(), tg.graph.edge_count());
+    for idx in tg.graph.node_indices() {
+        graph.add_node(tg.graph[idx].clone());
+    }
+    let mut loops = Vec::new();
+    for edge in tg.graph.edge_references() {
+        if removed.contains(&edge.id()) {
+            continue;
+        }
+        let id = graph.add_edge(edge.source(), edge.target(), edge.weight().clone());
+        if tg.loops.contains(&edge.id()) {
+            loops.push(id);
+        }
+    }
+    TestGraph {
+        name: tg.name.clone(),
+        graph,
+        node_indices: tg.node_indices.clone(),
+        loops,
+    }
+}
+
+/// Whether `to` can be reached from `from` through some other node, without
+/// loop edges, `removed` edges or edges straight from `from` to `to`.
+fn has_longer_path(
+    tg: &TestGraph,
+    removed: &HashSet<EdgeIndex>,
+    from: NodeIndex,
+    to: NodeIndex,
+) -> bool {
+    let mut seen = HashSet::from([from]);
+    let mut pending = vec![from];
+    while let Some(node) = pending.pop() {
+        for edge in tg.graph.edges_directed(node, Direction::Outgoing) {
+            let direct = edge.source() == from && edge.target() == to;
+            if direct || tg.loops.contains(&edge.id()) || removed.contains(&edge.id()) {
+                continue;
+            }
+            if edge.target() == to {
+                return true;
+            }
+            if seen.insert(edge.target()) {
+                pending.push(edge.target());
+            }
+        }
+    }
+    false
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -118,6 +492,9 @@ mod tests {
                     steps: vec![],
                     tags: vec![],
                     requires: vec![],
+                    produces: vec![],
+                    forwards: vec![],
+                    config: vec![],
                     span: Span::default(),
                 },
                 IrNode {
@@ -126,6 +503,9 @@ mod tests {
                     steps: vec![],
                     tags: vec![],
                     requires: vec![],
+                    produces: vec![],
+                    forwards: vec![],
+                    config: vec![],
                     span: Span::default(),
                 },
             ],
@@ -137,6 +517,7 @@ mod tests {
                     to_index: 1,
                     passes: vec![],
                     description: None,
+                    bound: None,
                     span: Span::default(),
                 },
                 IrEdge {
@@ -146,10 +527,12 @@ mod tests {
                     to_index: 0,
                     passes: vec![],
                     description: None,
+                    bound: None,
                     span: Span::default(),
                 },
             ],
             fixtures: vec![],
+            config: vec![],
             span: Span::default(),
         };
         let tg = build(&ir);
@@ -227,6 +610,9 @@ mod tests {
                     steps: vec![],
                     tags: vec![],
                     requires: vec![],
+                    produces: vec![],
+                    forwards: vec![],
+                    config: vec![],
                     span: Span::default(),
                 },
                 IrNode {
@@ -235,6 +621,9 @@ mod tests {
                     steps: vec![],
                     tags: vec![],
                     requires: vec![],
+                    produces: vec![],
+                    forwards: vec![],
+                    config: vec![],
                     span: Span::default(),
                 },
             ],
@@ -246,6 +635,7 @@ mod tests {
                     to_index: 1,
                     passes: vec![],
                     description: None,
+                    bound: None,
                     span: Span::default(),
                 },
                 IrEdge {
@@ -255,10 +645,12 @@ mod tests {
                     to_index: 0,
                     passes: vec![],
                     description: None,
+                    bound: None,
                     span: Span::default(),
                 },
             ],
             fixtures: vec![],
+            config: vec![],
             span: Span::default(),
         };
         let tg = build(&ir);
@@ -280,6 +672,9 @@ mod tests {
                     steps: vec![],
                     tags: vec![],
                     requires: vec![],
+                    produces: vec![],
+                    forwards: vec![],
+                    config: vec![],
                     span: Span::default(),
                 },
                 IrNode {
@@ -288,6 +683,9 @@ mod tests {
                     steps: vec![],
                     tags: vec![],
                     requires: vec![],
+                    produces: vec![],
+                    forwards: vec![],
+                    config: vec![],
                     span: Span::default(),
                 },
                 IrNode {
@@ -296,6 +694,9 @@ mod tests {
                     steps: vec![],
                     tags: vec![],
                     requires: vec![],
+                    produces: vec![],
+                    forwards: vec![],
+                    config: vec![],
                     span: Span::default(),
                 },
             ],
@@ -307,6 +708,7 @@ mod tests {
                     to_index: 1,
                     passes: vec![],
                     description: None,
+                    bound: None,
                     span: Span::default(),
                 },
                 IrEdge {
@@ -316,6 +718,7 @@ mod tests {
                     to_index: 2,
                     passes: vec![],
                     description: None,
+                    bound: None,
                     span: Span::default(),
                 },
                 IrEdge {
@@ -325,10 +728,12 @@ mod tests {
                     to_index: 0,
                     passes: vec![],
                     description: None,
+                    bound: None,
                     span: Span::default(),
                 },
             ],
             fixtures: vec![],
+            config: vec![],
             span: Span::default(),
         };
         let tg = build(&ir);
@@ -348,6 +753,9 @@ mod tests {
                 steps: vec![],
                 tags: vec![],
                 requires: vec![],
+                produces: vec![],
+                forwards: vec![],
+                config: vec![],
                 span: Span::default(),
             }],
             edges: vec![IrEdge {
@@ -357,9 +765,11 @@ mod tests {
                 to_index: 0,
                 passes: vec![],
                 description: None,
+                bound: None,
                 span: Span::default(),
             }],
             fixtures: vec![],
+            config: vec![],
             span: Span::default(),
         };
         let tg = build(&ir);
@@ -367,4 +777,260 @@ mod tests {
         assert!(cycle.is_some());
         assert_eq!(cycle.unwrap(), vec!["A"]);
     }
+
+    // ── loop_bounds ────────────────────────────────────────
+
+    #[test]
+    fn loop_bounds_cover_the_whole_cycle() {
+        let tg = build_one(
+            r#"graph G {
+                node Start {}
+                node Submit {}
+                node Poll {}
+                node Done {}
+                Start -> Submit
+                Submit -> Poll
+                Poll -> Submit max 5
+                Poll -> Done
+            }"#,
+        );
+        let bounds = loop_bounds(&tg);
+        assert_eq!(bounds.len(), 2);
+        assert_eq!(bounds[&tg.node_indices[1]], LoopBound::Max(5));
+        assert_eq!(bounds[&tg.node_indices[2]], LoopBound::Max(5));
+    }
+
+    #[test]
+    fn loop_bounds_ignore_edges_outside_cycles() {
+        let mut tg = build_one(
+            r#"graph G {
+                node A {}
+                node B {}
+                B -> A repeat 3
+                A -> B
+            }"#,
+        );
+        assert_eq!(loop_bounds(&tg).len(), 2);
+        // Dropping the forward edge leaves the loop edge closing nothing
+        let forward = tg.graph.edge_indices().next_back().unwrap();
+        tg.graph.remove_edge(forward);
+        assert_eq!(tg.loops.len(), 1);
+        assert!(loop_bounds(&tg).is_empty());
+    }
+
+    // ── ancestors / descendants ────────────────────────────
+
+    const DIAMOND: &str = r#"graph G {
+                node Setup {}
+                node A {}
+                node B {}
+                node Join {}
+                node Side {}
+                node Lone {}
+                Setup -> A
+                Setup -> B
+                Setup -> Side
+                A -> Join
+                B -> Join
+            }"#;
+
+    fn names(tg: &TestGraph, nodes: &[NodeIndex]) -> Vec<String> {
+        nodes.iter().map(|&n| tg.graph[n].name.clone()).collect()
+    }
+
+    #[test]
+    fn ancestors_and_descendants_include_the_start_nodes() {
+        let tg = build_one(DIAMOND);
+        let a = tg.node_indices[1];
+        assert_eq!(names(&tg, &ancestors(&tg, &[a])), vec!["Setup", "A"]);
+        assert_eq!(names(&tg, &descendants(&tg, &[a])), vec!["A", "Join"]);
+        let lone = tg.node_indices[5];
+        assert_eq!(names(&tg, &descendants(&tg, &[lone])), vec!["Lone"]);
+    }
+
+    #[test]
+    fn impact_closure_adds_setup_for_every_affected_node() {
+        let tg = build_one(DIAMOND);
+        let a = tg.node_indices[1];
+        // Join also needs B, which needs Setup; Side and Lone are untouched
+        assert_eq!(
+            names(&tg, &impact_closure(&tg, &[a])),
+            vec!["Setup", "A", "B", "Join"]
+        );
+        let setup = tg.node_indices[0];
+        assert_eq!(impact_closure(&tg, &[setup]).len(), 5);
+    }
+
+    #[test]
+    fn longest_chain_of_long_chains() {
+        use std::fmt::Write;
+
+        let mut input = String::from("graph Chain {\n");
+        for i in 0..10_000 {
+            writeln!(input, "node N{i} {{}}").unwrap();
+        }
+        for i in 1..10_000 {
+            writeln!(input, "N{} -> N{i}", i - 1).unwrap();
+        }
+        input.push('}');
+        let tg = build_one(&input);
+        assert_eq!(longest_chain(&tg).len(), 10_000);
+    }
+
+    #[test]
+    fn longest_chain_ignores_loop_edges() {
+        let tg = build_one(DIAMOND);
+        assert_eq!(names(&tg, &longest_chain(&tg)), vec!["Setup", "A", "Join"]);
+
+        let tg = build_one(
+            r#"graph G {
+                node Submit {}
+                node Poll {}
+                node Done {}
+                Submit -> Poll
+                Poll -> Submit max 5
+                Poll -> Done
+            }"#,
+        );
+        assert_eq!(
+            names(&tg, &longest_chain(&tg)),
+            vec!["Submit", "Poll", "Done"]
+        );
+    }
+
+    #[test]
+    fn weak_and_cyclic_components() {
+        let tg = build_one(DIAMOND);
+        let components: Vec<Vec<String>> =
+            weak_components(&tg).iter().map(|c| names(&tg, c)).collect();
+        assert_eq!(
+            components,
+            vec![vec!["Setup", "A", "B", "Join", "Side"], vec!["Lone"]]
+        );
+        assert!(cyclic_components(&tg).is_empty());
+
+        let tg = build_one(
+            r#"graph G {
+                node Submit {}
+                node Poll {}
+                node Retry {}
+                Submit -> Poll
+                Poll -> Submit max 5
+                Retry -> Retry repeat 3
+            }"#,
+        );
+        let cycles: Vec<Vec<String>> = cyclic_components(&tg)
+            .iter()
+            .map(|c| names(&tg, c))
+            .collect();
+        assert_eq!(cycles, vec![vec!["Submit", "Poll"], vec!["Retry"]]);
+    }
+
+    #[test]
+    fn dominated_counts_find_single_points_of_failure() {
+        let tg = build_one(&DIAMOND.replace("A -> Join", "A -> Join\n A -> Side"));
+        let counts: Vec<(String, usize)> = dominated_counts(&tg)
+            .into_iter()
+            .map(|(n, count)| (tg.graph[n].name.clone(), count))
+            .collect();
+        // Side is reachable through A and directly from Setup, Join through A or B
+        assert_eq!(counts, vec![("Setup".to_owned(), 4)]);
+
+        let tg = build_one(&DIAMOND.replace("A -> Join", "A -> Join\n A -> Deep\n node Deep {}"));
+        let counts: Vec<(String, usize)> = dominated_counts(&tg)
+            .into_iter()
+            .map(|(n, count)| (tg.graph[n].name.clone(), count))
+            .collect();
+        assert_eq!(counts, vec![("Setup".to_owned(), 5), ("A".to_owned(), 1)]);
+    }
+
+    #[test]
+    fn dominated_counts_enter_unrooted_cycles() {
+        let tg = build_one(
+            r#"graph G {
+                node Submit {}
+                node Poll {}
+                Submit -> Poll
+                Poll -> Submit max 5
+            }"#,
+        );
+        let counts = dominated_counts(&tg);
+        assert_eq!(counts, vec![(tg.node_indices[0], 1)]);
+    }
+
+    fn redundant(input: &str) -> Vec<(String, String)> {
+        let tg = build_one(input);
+        redundant_edges(&tg, ForwardMode::default())
+            .into_iter()
+            .map(|e| {
+                let edge = &tg.graph[e];
+                (edge.from.clone(), edge.to.clone())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn redundant_edges_are_implied_by_longer_paths() {
+        let chain = r#"graph G {
+            node A {}
+            node B {}
+            node C {}
+            node D {}
+            A -> B
+            B -> C
+            C -> D
+            A -> C
+            A -> D
+            B -> D
+        }"#;
+        let pair = |from: &str, to: &str| (from.to_owned(), to.to_owned());
+        assert_eq!(
+            redundant(chain),
+            vec![pair("A", "C"), pair("A", "D"), pair("B", "D")]
+        );
+
+        let tg = build_one(chain);
+        let reduced = transitive_reduction(&tg, ForwardMode::default());
+        assert_eq!(reduced.graph.edge_count(), 3);
+        assert_eq!(longest_chain(&reduced), longest_chain(&tg));
+    }
+
+    #[test]
+    fn edges_delivering_data_are_kept() {
+        let shortcut = r#"graph G {
+            node A {}
+            node B {}
+            node C {}
+            A -> B
+            B -> C
+            A -> C { passes { token } }
+        }"#;
+        assert!(redundant(shortcut).is_empty());
+
+        // The token reaches C through B too
+        let forwarded = shortcut.replace("A -> B", "A -> B { passes { token } }");
+        assert_eq!(redundant(&forwarded).len(), 1);
+        let explicit = forwarded.replace("node A {}", "config { dataflow: explicit }\n node A {}");
+        let tg = build_one(&explicit);
+        assert!(redundant_edges(&tg, ForwardMode::Explicit).is_empty());
+
+        // B passes its own token to C, so without the shortcut C would only
+        // get B's
+        let repassed = forwarded.replace("B -> C", "B -> C { passes { token } }");
+        assert!(redundant(&repassed).is_empty());
+    }
+
+    #[test]
+    fn loop_edges_and_duplicates_are_not_redundant() {
+        let input = r#"graph G {
+            node A {}
+            node B {}
+            node C {}
+            A -> B
+            A -> B
+            B -> C
+            C -> A max 3
+        }"#;
+        assert!(redundant(input).is_empty());
+    }
 }
@@ -1,4 +1,4 @@
-use petgraph::graph::{DiGraph, NodeIndex};
+use petgraph::graph::{DiGraph, EdgeIndex, NodeIndex};
 
 use crate::ir::{IrEdge, IrGraph, IrNode};
 
@@ -7,6 +7,9 @@ pub struct TestGraph {
     pub name: String,
     pub graph: DiGraph<IrNode, IrEdge>,
     pub node_indices: Vec<NodeIndex>,
+    /// Edges carrying a loop bound (`repeat N` / `max N`), in declaration order.
+    /// They stay in `graph` but are treated as back edges by traversals.
+    pub loops: Vec<EdgeIndex>,
 }
 
 /// Build a petgraph `DiGraph` from a validated IR graph.
@@ -14,18 +17,39 @@ pub fn build(ir: &IrGraph) -> TestGraph {
     let mut graph = DiGraph::new();
     let node_indices: Vec<NodeIndex> = ir.nodes.iter().map(|n| graph.add_node(n.clone())).collect();
 
+    let mut loops = Vec::new();
     for edge in &ir.edges {
-        graph.add_edge(
+        let idx = graph.add_edge(
             node_indices[edge.from_index],
             node_indices[edge.to_index],
             edge.clone(),
         );
+        if edge.bound.is_some() {
+            loops.push(idx);
+        }
     }
 
     TestGraph {
         name: ir.name.clone(),
         graph,
         node_indices,
+        loops,
+    }
+}
+
+impl TestGraph {
+    /// Copy of the graph without its loop edges, keeping node indices.
+    pub fn without_loops(&self) -> TestGraph {
+        let graph = self.graph.filter_map(
+            |_, node| Some(node.clone()),
+            |idx, edge| (!self.loops.contains(&idx)).then(|| edge.clone()),
+        );
+        TestGraph {
+            name: self.name.clone(),
+            graph,
+            node_indices: self.node_indices.clone(),
+            loops: Vec::new(),
+        }
     }
 }
 
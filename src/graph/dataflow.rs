@@ -0,0 +1,384 @@
+use std::collections::{BTreeSet, HashSet, VecDeque};
+use std::str::FromStr;
+
+use petgraph::Direction;
+use petgraph::graph::NodeIndex;
+use petgraph::visit::EdgeRef;
+
+use crate::graph::builder::TestGraph;
+use crate::ir::IrNode;
+
+/// Graph config key selecting the forwarding mode: `config { dataflow: explicit }`.
+pub const DATAFLOW_CONFIG_KEY: &str = "dataflow";
+
+/// How fields that reach a node flow on to its successors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ForwardMode {
+    /// Every field available at a node is available to its successors.
+    #[default]
+    Transitive,
+    /// A node only passes on the fields listed in its `forwards { ... }` clause.
+    Explicit,
+}
+
+impl FromStr for ForwardMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "transitive" => Ok(Self::Transitive),
+            "explicit" => Ok(Self::Explicit),
+            other => Err(format!(
+                "unknown dataflow mode '{other}' (expected: transitive, explicit)"
+            )),
+        }
+    }
+}
+
+impl ForwardMode {
+    /// Read the mode from graph-level config entries, defaulting to transitive.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the `dataflow` entry names an unknown mode.
+    pub fn from_config(config: &[(String, String)]) -> Result<Self, String> {
+        config
+            .iter()
+            .find(|(k, _)| k == DATAFLOW_CONFIG_KEY)
+            .map_or(Ok(Self::default()), |(_, v)| v.parse())
+    }
+
+    /// Whether `node` passes `field` on to its successors once it has it.
+    pub fn forwards(self, node: &IrNode, field: &str) -> bool {
+        match self {
+            Self::Transitive => true,
+            Self::Explicit => node.forwards.iter().any(|f| f == field),
+        }
+    }
+}
+
+/// The fields that reach a node.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Availability {
+    /// Fields that arrive along every path from a root.
+    pub must: BTreeSet<String>,
+    /// Fields that arrive along at least one path from a root.
+    pub may: BTreeSet<String>,
+}
+
+/// Result of a data-flow analysis over a [`TestGraph`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataFlow {
+    mode: ForwardMode,
+    available: Vec<Availability>,
+}
+
+/// Compute the fields available at every node.
+///
+/// A field arrives at a node through an incoming edge if the edge `passes` it,
+/// or if it was available at the edge's source and the source forwards it.
+/// Root nodes start with nothing. Cycles are handled by iterating to a fixpoint.
+pub fn analyze(tg: &TestGraph, mode: ForwardMode) -> DataFlow {
+    let g = &tg.graph;
+    let count = g.node_count();
+
+    // `None` stands for "every field" — the starting point for must-sets of
+    // nodes that have predecessors, narrowed by intersection.
+    let mut must: Vec<Option<BTreeSet<String>>> = tg
+        .node_indices
+        .iter()
+        .map(|&idx| {
+            let is_root = g.edges_directed(idx, Direction::Incoming).next().is_none();
+            is_root.then(BTreeSet::new)
+        })
+        .collect();
+    let mut may: Vec<BTreeSet<String>> = vec![BTreeSet::new(); count];
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for &idx in &tg.node_indices {
+            let mut incoming = g.edges_directed(idx, Direction::Incoming).peekable();
+            if incoming.peek().is_none() {
+                continue;
+            }
+
+            let mut new_must: Option<BTreeSet<String>> = None;
+            let mut new_may = BTreeSet::new();
+            for edge in incoming {
+                let source = edge.source();
+                let source_node = &g[source];
+                let passes = edge.weight().passes.iter().cloned();
+
+                new_may.extend(passes.clone());
+                new_may.extend(
+                    may[source.index()]
+                        .iter()
+                        .filter(|f| mode.forwards(source_node, f))
+                        .cloned(),
+                );
+
+                if let Some(source_must) = &must[source.index()] {
+                    let arrived: BTreeSet<String> = passes
+                        .chain(
+                            source_must
+                                .iter()
+                                .filter(|f| mode.forwards(source_node, f))
+                                .cloned(),
+                        )
+                        .collect();
+                    new_must = Some(match new_must {
+                        None => arrived,
+                        Some(acc) => acc.intersection(&arrived).cloned().collect(),
+                    });
+                }
+            }
+
+            let i = idx.index();
+            if new_must.is_some() && new_must != must[i] {
+                must[i] = new_must;
+                changed = true;
+            }
+            if new_may != may[i] {
+                may[i] = new_may;
+                changed = true;
+            }
+        }
+    }
+
+    let available = must
+        .into_iter()
+        .zip(may)
+        .map(|(must, may)| Availability {
+            // Nodes reachable only from a rootless cycle never get narrowed.
+            must: must.map_or_else(|| may.clone(), |m| m.intersection(&may).cloned().collect()),
+            may,
+        })
+        .collect();
+
+    DataFlow { mode, available }
+}
+
+impl DataFlow {
+    /// The forwarding mode the analysis ran with.
+    pub fn mode(&self) -> ForwardMode {
+        self.mode
+    }
+
+    /// Fields available at `node`.
+    pub fn at(&self, node: NodeIndex) -> &Availability {
+        &self.available[node.index()]
+    }
+
+    /// Find a path from a root to `node` along which `field` never arrives.
+    ///
+    /// Returns `None` if `field` reaches `node` along every path.
+    pub fn missing_path(
+        &self,
+        tg: &TestGraph,
+        node: NodeIndex,
+        field: &str,
+    ) -> Option<Vec<NodeIndex>> {
+        self.lacking_path(tg, node, field, &mut HashSet::new())
+    }
+
+    fn lacking_path(
+        &self,
+        tg: &TestGraph,
+        node: NodeIndex,
+        field: &str,
+        visited: &mut HashSet<NodeIndex>,
+    ) -> Option<Vec<NodeIndex>> {
+        if !visited.insert(node) {
+            return None;
+        }
+        let g = &tg.graph;
+        let mut incoming = g.edges_directed(node, Direction::Incoming).peekable();
+        if incoming.peek().is_none() {
+            return Some(vec![node]);
+        }
+
+        for edge in incoming {
+            if edge.weight().passes.iter().any(|p| p == field) {
+                continue;
+            }
+            let source = edge.source();
+            let prefix = if self.mode.forwards(&g[source], field) {
+                self.lacking_path(tg, source, field, visited)
+            } else {
+                // The source drops the field, so any way of reaching it will do.
+                path_from_root(tg, source)
+            };
+            if let Some(mut path) = prefix {
+                path.push(node);
+                return Some(path);
+            }
+        }
+        None
+    }
+}
+
+/// Shortest path from any root to `node`, found by walking edges backwards.
+fn path_from_root(tg: &TestGraph, node: NodeIndex) -> Option<Vec<NodeIndex>> {
+    let g = &tg.graph;
+    let mut next_hop = vec![None; g.node_count()];
+    let mut seen = HashSet::from([node]);
+    let mut queue = VecDeque::from([node]);
+
+    while let Some(current) = queue.pop_front() {
+        let mut preds = g
+            .neighbors_directed(current, Direction::Incoming)
+            .peekable();
+        if preds.peek().is_none() {
+            let mut path = vec![current];
+            let mut at = current;
+            while let Some(next) = next_hop[at.index()] {
+                path.push(next);
+                at = next;
+            }
+            return Some(path);
+        }
+        for pred in preds {
+            if seen.insert(pred) {
+                next_hop[pred.index()] = Some(current);
+                queue.push_back(pred);
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::builder::build;
+    use crate::ir::{IrGraph, lower};
+    use crate::parser::parse::parse;
+
+    /// Lower without validation so graphs with unsatisfied requires can be analyzed.
+    fn graph(input: &str) -> (IrGraph, TestGraph) {
+        let mut ast = parse(input).expect("parse failed").remove(0);
+        for node in &mut ast.nodes {
+            node.requires.clear();
+        }
+        let ir = lower(&ast).expect("lower failed");
+        let tg = build(&ir);
+        (ir, tg)
+    }
+
+    fn idx(ir: &IrGraph, tg: &TestGraph, name: &str) -> NodeIndex {
+        tg.node_indices[ir.nodes.iter().position(|n| n.name == name).unwrap()]
+    }
+
+    fn names(tg: &TestGraph, path: &[NodeIndex]) -> Vec<String> {
+        path.iter().map(|&i| tg.graph[i].name.clone()).collect()
+    }
+
+    fn set(fields: &[&str]) -> BTreeSet<String> {
+        fields.iter().map(|f| (*f).to_owned()).collect()
+    }
+
+    const CHAIN: &str = r#"graph G {
+  node Register { }
+  node Login { forwards { user_id } }
+  node Dashboard { }
+  Register -> Login { passes { user_id, email } }
+  Login -> Dashboard { passes { token } }
+}"#;
+
+    #[test]
+    fn transitive_mode_carries_fields_across_hops() {
+        let (ir, tg) = graph(CHAIN);
+        let flow = analyze(&tg, ForwardMode::Transitive);
+        let at = flow.at(idx(&ir, &tg, "Dashboard"));
+        assert_eq!(at.must, set(&["email", "token", "user_id"]));
+        assert_eq!(at.may, at.must);
+        assert!(flow.at(idx(&ir, &tg, "Register")).may.is_empty());
+    }
+
+    #[test]
+    fn explicit_mode_only_forwards_declared_fields() {
+        let (ir, tg) = graph(CHAIN);
+        let flow = analyze(&tg, ForwardMode::Explicit);
+        let at = flow.at(idx(&ir, &tg, "Dashboard"));
+        assert_eq!(at.must, set(&["token", "user_id"]));
+    }
+
+    #[test]
+    fn diamond_distinguishes_must_from_may() {
+        let (ir, tg) = graph(
+            r#"graph G {
+  node Start { }
+  node Left { }
+  node Right { }
+  node End { }
+  Start -> Left { passes { a } }
+  Start -> Right { passes { b } }
+  Left -> End { passes { c } }
+  Right -> End { passes { c } }
+}"#,
+        );
+        let flow = analyze(&tg, ForwardMode::Transitive);
+        let end = idx(&ir, &tg, "End");
+        assert_eq!(flow.at(end).must, set(&["c"]));
+        assert_eq!(flow.at(end).may, set(&["a", "b", "c"]));
+
+        let path = flow
+            .missing_path(&tg, end, "a")
+            .expect("a is missing on some path");
+        assert_eq!(names(&tg, &path), vec!["Start", "Right", "End"]);
+        assert!(flow.missing_path(&tg, end, "c").is_none());
+    }
+
+    #[test]
+    fn explicit_mode_witness_goes_through_dropping_node() {
+        let (ir, tg) = graph(
+            r#"graph G {
+  node A { }
+  node B { }
+  node C { }
+  A -> B { passes { id } }
+  B -> C
+}"#,
+        );
+        let flow = analyze(&tg, ForwardMode::Explicit);
+        let c = idx(&ir, &tg, "C");
+        assert!(flow.at(c).may.is_empty());
+        let path = flow.missing_path(&tg, c, "id").unwrap();
+        assert_eq!(names(&tg, &path), vec!["A", "B", "C"]);
+    }
+
+    #[test]
+    fn cycles_reach_a_fixpoint() {
+        let (ir, tg) = graph(
+            r#"graph G {
+  node A { }
+  node B { }
+  node C { }
+  A -> B { passes { x } }
+  B -> C { passes { y } }
+  C -> B { passes { z } }
+}"#,
+        );
+        let flow = analyze(&tg, ForwardMode::Transitive);
+        let b = idx(&ir, &tg, "B");
+        assert_eq!(flow.at(b).must, set(&["x"]));
+        assert_eq!(flow.at(b).may, set(&["x", "y", "z"]));
+        assert_eq!(flow.at(idx(&ir, &tg, "C")).must, set(&["x", "y"]));
+    }
+
+    #[test]
+    fn mode_from_config() {
+        assert_eq!(
+            ForwardMode::from_config(&[]).unwrap(),
+            ForwardMode::Transitive
+        );
+        let config = vec![("dataflow".to_owned(), "explicit".to_owned())];
+        assert_eq!(
+            ForwardMode::from_config(&config).unwrap(),
+            ForwardMode::Explicit
+        );
+        let config = vec![("dataflow".to_owned(), "magic".to_owned())];
+        assert!(ForwardMode::from_config(&config).is_err());
+    }
+}
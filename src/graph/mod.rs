@@ -1,3 +1,8 @@
 pub mod analysis;
 pub mod builder;
+pub mod dataflow;
+pub mod paths;
+pub mod query;
+pub mod schedule;
+pub mod stats;
 pub mod traversal;
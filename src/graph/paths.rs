@@ -0,0 +1,341 @@
+use std::collections::HashSet;
+
+use petgraph::Direction;
+use petgraph::graph::{DiGraph, NodeIndex};
+use petgraph::visit::EdgeRef;
+
+use crate::graph::analysis::ancestors;
+use crate::graph::builder::TestGraph;
+use crate::graph::traversal::find_node_by_name;
+
+/// Caps on path enumeration, which grows exponentially with branching.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PathLimits {
+    /// Stop after this many paths.
+    pub max_paths: usize,
+    /// Cut off paths once they have this many nodes.
+    pub max_length: usize,
+}
+
+impl Default for PathLimits {
+    fn default() -> Self {
+        Self {
+            max_paths: 100,
+            max_length: 32,
+        }
+    }
+}
+
+/// The simple paths found by [`all_paths`] or [`paths_between`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PathSet {
+    /// Paths in depth-first order, following edges in declaration order.
+    pub paths: Vec<Vec<NodeIndex>>,
+    /// Enumeration stopped at [`PathLimits::max_paths`] with paths left over.
+    pub hit_max_paths: bool,
+    /// Number of branches cut off at [`PathLimits::max_length`] nodes before
+    /// they ended. Each may have led to any number of paths.
+    pub truncated: usize,
+}
+
+/// Enumerate every simple path from a root to a leaf.
+///
+/// Loop edges are not followed. A path also ends where it can only continue
+/// by revisiting one of its own nodes.
+pub fn all_paths(tg: &TestGraph, limits: PathLimits) -> PathSet {
+    let forward = tg.without_loops();
+    let mut search = PathSearch::new(&forward.graph, None, limits);
+    for &root in &forward.node_indices {
+        if forward
+            .graph
+            .neighbors_directed(root, Direction::Incoming)
+            .next()
+            .is_none()
+        {
+            search.visit(root);
+        }
+    }
+    search.set
+}
+
+/// Enumerate every simple path between two named nodes.
+///
+/// # Errors
+///
+/// Returns an error if either node name is unknown.
+pub fn paths_between(
+    tg: &TestGraph,
+    from_name: &str,
+    to_name: &str,
+    limits: PathLimits,
+) -> Result<PathSet, String> {
+    let from =
+        find_node_by_name(tg, from_name).ok_or_else(|| format!("unknown node '{from_name}'"))?;
+    let to = find_node_by_name(tg, to_name).ok_or_else(|| format!("unknown node '{to_name}'"))?;
+    let forward = tg.without_loops();
+    let leads_to = ancestors(&forward, &[to]).into_iter().collect();
+    let mut search = PathSearch::new(&forward.graph, Some((to, leads_to)), limits);
+    search.visit(from);
+    Ok(search.set)
+}
+
+/// The graph of a single path: its nodes in path order, joined by the first
+/// edge declared between each consecutive pair.
+pub fn path_graph(tg: &TestGraph, path: &[NodeIndex]) -> TestGraph {
+    let mut graph = DiGraph::new();
+    let node_indices: Vec<NodeIndex> = path
+        .iter()
+        .map(|&n| graph.add_node(tg.graph[n].clone()))
+        .collect();
+    for (i, pair) in path.windows(2).enumerate() {
+        let edge = tg
+            .graph
+            .edges_connecting(pair[0], pair[1])
+            .filter(|e| !tg.loops.contains(&e.id()))
+            .min_by_key(|e| e.id())
+            .expect("consecutive path nodes are connected");
+        graph.add_edge(node_indices[i], node_indices[i + 1], edge.weight().clone());
+    }
+    TestGraph {
+        name: tg.name.clone(),
+        graph,
+        node_indices,
+        loops: Vec::new(),
+    }
+}
+
+/// Number of leading nodes `a` and `b` have in common.
+pub fn common_prefix(a: &[NodeIndex], b: &[NodeIndex]) -> usize {
+    a.iter().zip(b).take_while(|(x, y)| x == y).count()
+}
+
+struct PathSearch<'a, N, E> {
+    graph: &'a DiGraph<N, E>,
+    /// The node paths end at, with the nodes it can be reached from.
+    target: Option<(NodeIndex, HashSet<NodeIndex>)>,
+    limits: PathLimits,
+    path: Vec<NodeIndex>,
+    on_path: HashSet<NodeIndex>,
+    set: PathSet,
+}
+
+impl<'a, N, E> PathSearch<'a, N, E> {
+    fn new(
+        graph: &'a DiGraph<N, E>,
+        target: Option<(NodeIndex, HashSet<NodeIndex>)>,
+        limits: PathLimits,
+    ) -> Self {
+        Self {
+            graph,
+            target,
+            limits,
+            path: Vec::new(),
+            on_path: HashSet::new(),
+            set: PathSet::default(),
+        }
+    }
+
+    fn visit(&mut self, node: NodeIndex) {
+        if self.set.hit_max_paths {
+            return;
+        }
+        self.path.push(node);
+        self.on_path.insert(node);
+
+        let mut edges: Vec<_> = self.graph.edges(node).collect();
+        edges.sort_by_key(|e| e.id());
+        let mut next: Vec<NodeIndex> = Vec::new();
+        for edge in edges {
+            let succ = edge.target();
+            let leads_to_target = self
+                .target
+                .as_ref()
+                .is_none_or(|(_, leads_to)| leads_to.contains(&succ));
+            if leads_to_target && !self.on_path.contains(&succ) && !next.contains(&succ) {
+                next.push(succ);
+            }
+        }
+
+        let complete = match &self.target {
+            Some((target, _)) => node == *target,
+            None => next.is_empty(),
+        };
+        if complete {
+            if self.set.paths.len() == self.limits.max_paths {
+                self.set.hit_max_paths = true;
+            } else {
+                self.set.paths.push(self.path.clone());
+            }
+        } else if self.path.len() == self.limits.max_length {
+            self.set.truncated += 1;
+        } else {
+            for succ in next {
+                self.visit(succ);
+            }
+        }
+
+        self.on_path.remove(&node);
+        self.path.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::builder::build;
+    use crate::ir::lower;
+    use crate::parser::parse::parse;
+
+    fn build_one(input: &str) -> TestGraph {
+        let graphs = parse(input).expect("parse failed");
+        let ir = lower(&graphs[0]).expect("lower failed");
+        build(&ir)
+    }
+
+    fn names(tg: &TestGraph, set: &PathSet) -> Vec<String> {
+        set.paths
+            .iter()
+            .map(|p| {
+                p.iter()
+                    .map(|&n| tg.graph[n].name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect()
+    }
+
+    const DIAMOND: &str = r#"graph G {
+        node A {}
+        node B {}
+        node C {}
+        node D {}
+        node E {}
+        A -> B
+        A -> C
+        B -> D
+        C -> D
+        D -> E
+    }"#;
+
+    #[test]
+    fn enumerates_root_to_leaf_paths() {
+        let tg = build_one(DIAMOND);
+        let set = all_paths(&tg, PathLimits::default());
+        assert_eq!(names(&tg, &set), vec!["A B D E", "A C D E"]);
+        assert!(!set.hit_max_paths);
+        assert_eq!(set.truncated, 0);
+    }
+
+    #[test]
+    fn caps_path_count() {
+        let tg = build_one(DIAMOND);
+        let limits = PathLimits {
+            max_paths: 1,
+            ..PathLimits::default()
+        };
+        let set = all_paths(&tg, limits);
+        assert_eq!(names(&tg, &set), vec!["A B D E"]);
+        assert!(set.hit_max_paths);
+    }
+
+    #[test]
+    fn truncates_branches_at_length_cap() {
+        let tg = build_one(
+            r#"graph G {
+                node A {}
+                node B {}
+                node C {}
+                node D {}
+                A -> B
+                B -> C
+                A -> D
+            }"#,
+        );
+        let limits = PathLimits {
+            max_length: 2,
+            ..PathLimits::default()
+        };
+        let set = all_paths(&tg, limits);
+        assert_eq!(names(&tg, &set), vec!["A D"]);
+        assert_eq!(set.truncated, 1);
+    }
+
+    #[test]
+    fn counts_only_truncated_branches_that_reach_the_target() {
+        let tg = build_one(
+            r#"graph G {
+                node A {}
+                node B {}
+                node C {}
+                node D {}
+                node E {}
+                node F {}
+                A -> B
+                B -> C
+                B -> D
+                C -> E
+                D -> E
+                A -> F
+                F -> E
+                A -> E
+            }"#,
+        );
+        let limits = PathLimits {
+            max_length: 2,
+            ..PathLimits::default()
+        };
+        // A B counts once, though it leads to two paths
+        let set = all_paths(&tg, limits);
+        assert_eq!(names(&tg, &set), vec!["A E"]);
+        assert_eq!(set.truncated, 2);
+
+        // Only branches that can still reach F count
+        let set = paths_between(&tg, "A", "F", limits).unwrap();
+        assert_eq!(names(&tg, &set), vec!["A F"]);
+        assert_eq!(set.truncated, 0);
+    }
+
+    #[test]
+    fn enumerates_paths_between_nodes() {
+        let tg = build_one(DIAMOND);
+        let set = paths_between(&tg, "B", "E", PathLimits::default()).unwrap();
+        assert_eq!(names(&tg, &set), vec!["B D E"]);
+        let set = paths_between(&tg, "A", "D", PathLimits::default()).unwrap();
+        assert_eq!(names(&tg, &set), vec!["A B D", "A C D"]);
+        assert!(paths_between(&tg, "A", "Nope", PathLimits::default()).is_err());
+    }
+
+    #[test]
+    fn paths_do_not_follow_loop_edges() {
+        let tg = build_one(
+            r#"graph G {
+                node A {}
+                node B {}
+                node C {}
+                A -> B
+                B -> A repeat 2
+                B -> C
+            }"#,
+        );
+        let set = all_paths(&tg, PathLimits::default());
+        assert_eq!(names(&tg, &set), vec!["A B C"]);
+    }
+
+    #[test]
+    fn path_graph_keeps_only_path_edges() {
+        let tg = build_one(DIAMOND);
+        let set = all_paths(&tg, PathLimits::default());
+        let pg = path_graph(&tg, &set.paths[1]);
+        assert_eq!(pg.graph.node_count(), 4);
+        assert_eq!(pg.graph.edge_count(), 3);
+        assert_eq!(pg.graph[pg.node_indices[1]].name, "C");
+    }
+
+    #[test]
+    fn common_prefix_counts_shared_nodes() {
+        let tg = build_one(DIAMOND);
+        let set = all_paths(&tg, PathLimits::default());
+        assert_eq!(common_prefix(&set.paths[0], &set.paths[1]), 1);
+        assert_eq!(common_prefix(&set.paths[0], &set.paths[0]), 4);
+    }
+}
@@ -0,0 +1,690 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use petgraph::graph::NodeIndex;
+
+use crate::graph::analysis::{ancestors, descendants, leaf_nodes, root_nodes};
+use crate::graph::builder::TestGraph;
+use crate::graph::traversal::find_nodes_matching;
+use crate::ir::IrNode;
+use crate::ir::tag::{split_tag, tag_matches};
+use crate::parser::error::ParseError;
+use crate::util::glob::glob_match;
+use crate::util::span::Span;
+
+/// A parsed node selection query.
+///
+/// A query combines selectors with `AND`, `OR` (or `,`) and `NOT`, grouped
+/// with parentheses. `NOT` binds tightest, then `AND`, then `OR`.
+///
+/// | Selector | Selects |
+/// |----------|---------|
+/// | `smoke`, `tag:auth*` | nodes with a tag matching the glob, including namespaced children (`tag:team/auth`) |
+/// | `tag:priority>=2` | nodes with a `key: value` tag that compares true |
+/// | `name:Login*` | nodes whose name matches the glob |
+/// | `priority>=2` | nodes whose `config` entry compares true (`=`, `!=`, `<`, `<=`, `>`, `>=`) |
+/// | `descendants(X)`, `ancestors(X)` | nodes matching `X` and everything below / above them |
+/// | `roots()`, `leaves()` | nodes without incoming / outgoing edges |
+/// | `path(A, B)` | nodes on some path from `A` to `B` |
+#[derive(Debug, Clone, PartialEq)]
+pub enum Query {
+    /// Nodes with a tag matching the glob.
+    Tag(String),
+    /// Nodes with a `key:value` tag whose value compares true against `value`.
+    TagValue {
+        key: String,
+        op: CompareOp,
+        value: String,
+    },
+    /// Nodes whose name matches the glob.
+    Name(String),
+    /// Nodes whose `config` entry `key` compares true against `value`.
+    Config {
+        key: String,
+        op: CompareOp,
+        value: String,
+    },
+    Descendants(String),
+    Ancestors(String),
+    Roots,
+    Leaves,
+    /// Nodes on some path from the first node to the second.
+    Path(String, String),
+    Not(Box<Query>),
+    And(Vec<Query>),
+    Or(Vec<Query>),
+}
+
+/// Comparison operator in a config or tag predicate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl fmt::Display for CompareOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Eq => "=",
+            Self::Ne => "!=",
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+        })
+    }
+}
+
+impl CompareOp {
+    /// Compare numerically when both sides are numbers, otherwise as strings.
+    /// Ordering comparisons between non-numbers are always false.
+    pub(crate) fn holds(self, actual: &str, expected: &str) -> bool {
+        if let (Ok(a), Ok(b)) = (actual.parse::<f64>(), expected.parse::<f64>()) {
+            return match self {
+                Self::Eq => a == b,
+                Self::Ne => a != b,
+                Self::Lt => a < b,
+                Self::Le => a <= b,
+                Self::Gt => a > b,
+                Self::Ge => a >= b,
+            };
+        }
+        match self {
+            Self::Eq => actual == expected,
+            Self::Ne => actual != expected,
+            _ => false,
+        }
+    }
+}
+
+impl Query {
+    /// The nodes of `tg` the query selects, in declaration order.
+    pub fn select(&self, tg: &TestGraph) -> Vec<NodeIndex> {
+        let selected = self.eval(tg);
+        tg.node_indices
+            .iter()
+            .filter(|idx| selected.contains(idx))
+            .copied()
+            .collect()
+    }
+
+    /// The node name patterns the query refers to, in query order: the
+    /// arguments of `name:`, `descendants`, `ancestors` and `path`.
+    pub fn node_patterns(&self) -> Vec<&str> {
+        match self {
+            Self::Name(glob) | Self::Descendants(glob) | Self::Ancestors(glob) => vec![glob],
+            Self::Path(from, to) => vec![from, to],
+            Self::Not(inner) => inner.node_patterns(),
+            Self::And(parts) | Self::Or(parts) => {
+                parts.iter().flat_map(Self::node_patterns).collect()
+            }
+            Self::Tag(_)
+            | Self::TagValue { .. }
+            | Self::Config { .. }
+            | Self::Roots
+            | Self::Leaves => Vec::new(),
+        }
+    }
+
+    fn eval(&self, tg: &TestGraph) -> HashSet<NodeIndex> {
+        let matching = |pred: &dyn Fn(&IrNode) -> bool| -> HashSet<NodeIndex> {
+            tg.node_indices
+                .iter()
+                .filter(|&&idx| pred(&tg.graph[idx]))
+                .copied()
+                .collect()
+        };
+        match self {
+            Self::Tag(glob) => matching(&|n| n.tags.iter().any(|t| tag_matches(t, glob))),
+            Self::TagValue { key, op, value } => matching(&|n| {
+                n.tags.iter().any(|t| match split_tag(t) {
+                    (Some(k), actual) => k == key && op.holds(actual, value),
+                    (None, _) => false,
+                })
+            }),
+            Self::Name(glob) => matching(&|n| glob_match(glob, &n.name)),
+            Self::Config { key, op, value } => {
+                matching(&|n| n.config.iter().any(|(k, v)| k == key && op.holds(v, value)))
+            }
+            Self::Descendants(glob) => descendants(tg, &find_nodes_matching(tg, glob))
+                .into_iter()
+                .collect(),
+            Self::Ancestors(glob) => ancestors(tg, &find_nodes_matching(tg, glob))
+                .into_iter()
+                .collect(),
+            Self::Roots => root_nodes(tg).into_iter().collect(),
+            Self::Leaves => leaf_nodes(tg).into_iter().collect(),
+            Self::Path(from, to) => {
+                let below: HashSet<NodeIndex> = descendants(tg, &find_nodes_matching(tg, from))
+                    .into_iter()
+                    .collect();
+                ancestors(tg, &find_nodes_matching(tg, to))
+                    .into_iter()
+                    .filter(|idx| below.contains(idx))
+                    .collect()
+            }
+            Self::Not(inner) => {
+                let excluded = inner.eval(tg);
+                matching(&|_| true)
+                    .into_iter()
+                    .filter(|idx| !excluded.contains(idx))
+                    .collect()
+            }
+            Self::And(parts) => {
+                let mut sets = parts.iter().map(|p| p.eval(tg));
+                let first = sets.next().unwrap_or_default();
+                sets.fold(first, |acc, set| acc.intersection(&set).copied().collect())
+            }
+            Self::Or(parts) => parts.iter().flat_map(|p| p.eval(tg)).collect(),
+        }
+    }
+}
+
+/// Parse a node selection query.
+///
+/// # Errors
+///
+/// Returns a [`ParseError`] whose span points at the offending part of the
+/// query (line 1, 1-based column).
+pub fn parse_query(input: &str) -> Result<Query, ParseError> {
+    let tokens = tokenize(input)?;
+    let mut parser = QueryParser {
+        tokens,
+        pos: 0,
+        end: input.chars().count(),
+    };
+    let query = parser.parse_or()?;
+    if let Some(token) = parser.peek() {
+        return Err(error(
+            token.col,
+            format!("unexpected {} after end of query", token.kind),
+        ));
+    }
+    Ok(query)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Word(String),
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+    Comma,
+    Colon,
+    Op(CompareOp),
+}
+
+impl fmt::Display for Tok {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Word(w) => write!(f, "'{w}'"),
+            Self::And => f.write_str("AND"),
+            Self::Or => f.write_str("OR"),
+            Self::Not => f.write_str("NOT"),
+            Self::LParen => f.write_str("'('"),
+            Self::RParen => f.write_str("')'"),
+            Self::Comma => f.write_str("','"),
+            Self::Colon => f.write_str("':'"),
+            Self::Op(op) => write!(f, "'{op}'"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct QueryToken {
+    kind: Tok,
+    /// 0-based character column.
+    col: usize,
+}
+
+fn error(col: usize, message: String) -> ParseError {
+    ParseError {
+        message,
+        span: Span::new(col, col + 1, 1, col + 1),
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '/' | '*' | '?')
+}
+
+fn tokenize(input: &str) -> Result<Vec<QueryToken>, ParseError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let col = i;
+        let kind = match c {
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '(' => Tok::LParen,
+            ')' => Tok::RParen,
+            ',' => Tok::Comma,
+            ':' => Tok::Colon,
+            '=' => Tok::Op(CompareOp::Eq),
+            '!' | '<' | '>' => {
+                let with_eq = chars.get(i + 1) == Some(&'=');
+                let op = match (c, with_eq) {
+                    ('!', true) => CompareOp::Ne,
+                    ('<', false) => CompareOp::Lt,
+                    ('<', true) => CompareOp::Le,
+                    ('>', false) => CompareOp::Gt,
+                    ('>', true) => CompareOp::Ge,
+                    _ => return Err(error(col, "expected '=' after '!'".to_owned())),
+                };
+                if with_eq {
+                    i += 1;
+                }
+                Tok::Op(op)
+            }
+            '"' => {
+                let close = chars[i + 1..]
+                    .iter()
+                    .position(|&c| c == '"')
+                    .ok_or_else(|| error(col, "unterminated string".to_owned()))?;
+                let word: String = chars[i + 1..i + 1 + close].iter().collect();
+                i += close + 1;
+                Tok::Word(word)
+            }
+            c if is_word_char(c) => {
+                let len = chars[i..].iter().take_while(|&&c| is_word_char(c)).count();
+                let word: String = chars[i..i + len].iter().collect();
+                i += len - 1;
+                match word.as_str() {
+                    "AND" | "and" => Tok::And,
+                    "OR" | "or" => Tok::Or,
+                    "NOT" | "not" => Tok::Not,
+                    _ => Tok::Word(word),
+                }
+            }
+            other => return Err(error(col, format!("unexpected character '{other}'"))),
+        };
+        tokens.push(QueryToken { kind, col });
+        i += 1;
+    }
+    Ok(tokens)
+}
+
+const FUNCTIONS: &str = "ancestors, descendants, roots, leaves, path";
+
+struct QueryParser {
+    tokens: Vec<QueryToken>,
+    pos: usize,
+    /// Column just past the end of the input, for "unexpected end" errors.
+    end: usize,
+}
+
+impl QueryParser {
+    fn peek(&self) -> Option<&QueryToken> {
+        self.tokens.get(self.pos)
+    }
+
+    fn peek_kind(&self) -> Option<&Tok> {
+        self.peek().map(|t| &t.kind)
+    }
+
+    fn next(&mut self, expected: &str) -> Result<QueryToken, ParseError> {
+        let token = self.tokens.get(self.pos).cloned().ok_or_else(|| {
+            error(
+                self.end,
+                format!("unexpected end of query, expected {expected}"),
+            )
+        })?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn parse_or(&mut self) -> Result<Query, ParseError> {
+        let mut parts = vec![self.parse_and()?];
+        while matches!(self.peek_kind(), Some(Tok::Or | Tok::Comma)) {
+            self.pos += 1;
+            parts.push(self.parse_and()?);
+        }
+        Ok(if parts.len() == 1 {
+            parts.remove(0)
+        } else {
+            Query::Or(parts)
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<Query, ParseError> {
+        let mut parts = vec![self.parse_not()?];
+        while self.peek_kind() == Some(&Tok::And) {
+            self.pos += 1;
+            parts.push(self.parse_not()?);
+        }
+        Ok(if parts.len() == 1 {
+            parts.remove(0)
+        } else {
+            Query::And(parts)
+        })
+    }
+
+    fn parse_not(&mut self) -> Result<Query, ParseError> {
+        if self.peek_kind() == Some(&Tok::Not) {
+            self.pos += 1;
+            return Ok(Query::Not(Box::new(self.parse_not()?)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Query, ParseError> {
+        let token = self.next("a selector")?;
+        let word = match token.kind {
+            Tok::LParen => {
+                let inner = self.parse_or()?;
+                self.expect_close(token.col, "'('")?;
+                return Ok(inner);
+            }
+            Tok::Word(word) => word,
+            other => {
+                return Err(error(
+                    token.col,
+                    format!("expected a selector, found {other}"),
+                ));
+            }
+        };
+
+        match self.peek_kind() {
+            Some(Tok::LParen) => self.parse_function(word, token.col),
+            Some(Tok::Colon) => {
+                self.pos += 1;
+                let glob = self.word("a pattern after ':'")?;
+                match word.as_str() {
+                    "tag" => match self.peek_kind() {
+                        Some(&Tok::Op(op)) => {
+                            self.pos += 1;
+                            let value = self.word(&format!("a value after '{op}'"))?;
+                            Ok(Query::TagValue {
+                                key: glob,
+                                op,
+                                value,
+                            })
+                        }
+                        _ => Ok(Query::Tag(glob)),
+                    },
+                    "name" => Ok(Query::Name(glob)),
+                    _ => Err(error(
+                        token.col,
+                        format!("unknown selector '{word}:' (expected tag: or name:)"),
+                    )),
+                }
+            }
+            Some(&Tok::Op(op)) => {
+                self.pos += 1;
+                let value = self.word(&format!("a value after '{op}'"))?;
+                Ok(Query::Config {
+                    key: word,
+                    op,
+                    value,
+                })
+            }
+            _ => Ok(Query::Tag(word)),
+        }
+    }
+
+    fn parse_function(&mut self, name: String, col: usize) -> Result<Query, ParseError> {
+        self.pos += 1; // '('
+        let mut args = Vec::new();
+        if self.peek_kind() != Some(&Tok::RParen) {
+            args.push(self.word("a node name or pattern")?);
+            while self.peek_kind() == Some(&Tok::Comma) {
+                self.pos += 1;
+                args.push(self.word("a node name or pattern")?);
+            }
+        }
+        self.expect_close(col, &format!("'{name}('"))?;
+
+        let arity = match name.as_str() {
+            "roots" | "leaves" => 0,
+            "ancestors" | "descendants" => 1,
+            "path" => 2,
+            _ => {
+                return Err(error(
+                    col,
+                    format!("unknown function '{name}' (expected: {FUNCTIONS})"),
+                ));
+            }
+        };
+        if args.len() != arity {
+            let plural = if arity == 1 { "" } else { "s" };
+            return Err(error(
+                col,
+                format!(
+                    "'{name}' takes {arity} argument{plural}, found {}",
+                    args.len()
+                ),
+            ));
+        }
+        let mut args = args.into_iter();
+        let mut arg = || args.next().unwrap_or_default();
+        Ok(match name.as_str() {
+            "roots" => Query::Roots,
+            "leaves" => Query::Leaves,
+            "ancestors" => Query::Ancestors(arg()),
+            "descendants" => Query::Descendants(arg()),
+            _ => Query::Path(arg(), arg()),
+        })
+    }
+
+    fn word(&mut self, expected: &str) -> Result<String, ParseError> {
+        let token = self.next(expected)?;
+        match token.kind {
+            Tok::Word(word) => Ok(word),
+            other => Err(error(
+                token.col,
+                format!("expected {expected}, found {other}"),
+            )),
+        }
+    }
+
+    fn expect_close(&mut self, open_col: usize, opened: &str) -> Result<(), ParseError> {
+        let message = format!("expected ')' to close {opened} at column {}", open_col + 1);
+        match self.peek() {
+            Some(token) if token.kind == Tok::RParen => {
+                self.pos += 1;
+                Ok(())
+            }
+            Some(token) => Err(error(token.col, format!("{message}, found {}", token.kind))),
+            None => Err(error(self.end, message)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::builder::build;
+    use crate::ir::lower;
+    use crate::parser::parse::parse;
+
+    fn build_one(input: &str) -> TestGraph {
+        let graphs = parse(input).expect("parse failed");
+        build(&lower(&graphs[0]).expect("lower failed"))
+    }
+
+    const AUTH: &str = r#"graph Auth {
+        node Register {
+            tags [auth, smoke]
+            config { priority: 1 }
+        }
+        node LoginForm {
+            tags [auth_ui]
+            config { priority: 3 }
+        }
+        node LoginApi {
+            tags [auth, slow]
+            config { priority: 2 }
+        }
+        node Dashboard { tags [ui] }
+        node Audit { }
+        Register -> LoginForm
+        Register -> LoginApi
+        LoginForm -> Dashboard
+        LoginApi -> Dashboard
+    }"#;
+
+    fn select(query: &str) -> Vec<String> {
+        let tg = build_one(AUTH);
+        parse_query(query)
+            .unwrap_or_else(|e| panic!("{query}: {e}"))
+            .select(&tg)
+            .iter()
+            .map(|&i| tg.graph[i].name.clone())
+            .collect()
+    }
+
+    fn parse_error(query: &str) -> ParseError {
+        parse_query(query).expect_err(query)
+    }
+
+    #[test]
+    fn bare_words_and_tag_globs_match_tags() {
+        assert_eq!(select("smoke"), vec!["Register"]);
+        assert_eq!(
+            select("tag:auth*"),
+            vec!["Register", "LoginForm", "LoginApi"]
+        );
+        assert_eq!(select("tag:auth"), vec!["Register", "LoginApi"]);
+    }
+
+    #[test]
+    fn name_globs_match_node_names() {
+        assert_eq!(select("name:Login*"), vec!["LoginForm", "LoginApi"]);
+        assert_eq!(select("name:\"Audit\""), vec!["Audit"]);
+    }
+
+    #[test]
+    fn lists_node_name_patterns() {
+        let query = parse_query(
+            "name:Login* OR NOT (smoke AND path(Register, Dashboard)) OR descendants(Audit)",
+        )
+        .unwrap();
+        assert_eq!(
+            query.node_patterns(),
+            vec!["Login*", "Register", "Dashboard", "Audit"]
+        );
+        assert!(
+            parse_query("roots() AND tag:auth")
+                .unwrap()
+                .node_patterns()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn precedence_is_not_then_and_then_or() {
+        assert_eq!(select("auth AND NOT slow"), vec!["Register"]);
+        // AND binds tighter than OR
+        assert_eq!(select("ui OR auth AND slow"), vec!["LoginApi", "Dashboard"]);
+        assert_eq!(
+            select("(ui OR auth) AND NOT slow"),
+            vec!["Register", "Dashboard"]
+        );
+        assert_eq!(select("smoke, ui"), vec!["Register", "Dashboard"]);
+        assert_eq!(select("not not smoke"), vec!["Register"]);
+    }
+
+    #[test]
+    fn structural_functions() {
+        assert_eq!(
+            select("descendants(LoginForm)"),
+            vec!["LoginForm", "Dashboard"]
+        );
+        assert_eq!(
+            select("ancestors(Login*)"),
+            vec!["Register", "LoginForm", "LoginApi"]
+        );
+        assert_eq!(select("roots()"), vec!["Register", "Audit"]);
+        assert_eq!(select("leaves()"), vec!["Dashboard", "Audit"]);
+        assert_eq!(
+            select("path(Register, Dashboard) AND NOT slow"),
+            vec!["Register", "LoginForm", "Dashboard"]
+        );
+        assert!(select("path(Dashboard, Register)").is_empty());
+    }
+
+    #[test]
+    fn config_predicates_compare_numbers_and_strings() {
+        assert_eq!(select("priority>=2"), vec!["LoginForm", "LoginApi"]);
+        assert_eq!(select("priority < 2"), vec!["Register"]);
+        assert_eq!(select("priority != 1"), vec!["LoginForm", "LoginApi"]);
+        assert_eq!(select("priority = 3.0"), vec!["LoginForm"]);
+        assert!(select("owner = auth").is_empty());
+    }
+
+    #[test]
+    fn tag_selectors_match_namespaces_and_key_values() {
+        let tg = build_one(
+            r#"graph Shop {
+                tags [team/shop]
+                node Cart { tags [team/shop/cart, area: checkout, risk: 3] }
+                node Search { tags [area: search, risk: 1] }
+            }"#,
+        );
+        let select = |query: &str| -> Vec<String> {
+            parse_query(query)
+                .unwrap()
+                .select(&tg)
+                .iter()
+                .map(|&i| tg.graph[i].name.clone())
+                .collect()
+        };
+        assert_eq!(select("tag:team/shop"), vec!["Cart", "Search"]);
+        assert_eq!(select("tag:team/shop/cart"), vec!["Cart"]);
+        assert_eq!(select("tag:area=checkout"), vec!["Cart"]);
+        assert_eq!(select("tag:risk > 1"), vec!["Cart"]);
+        assert_eq!(select("area"), vec!["Cart", "Search"]);
+    }
+
+    #[test]
+    fn errors_point_at_the_offending_column() {
+        let err = parse_error("auth AND (ui OR smoke");
+        assert_eq!(err.span.col, 22);
+        assert!(
+            err.message
+                .contains("expected ')' to close '(' at column 10"),
+            "{}",
+            err.message
+        );
+
+        let err = parse_error("auth AND");
+        assert_eq!(err.span.col, 9);
+        assert!(
+            err.message
+                .contains("unexpected end of query, expected a selector")
+        );
+
+        let err = parse_error("smoke & ui");
+        assert_eq!(
+            (err.span.col, err.message.as_str()),
+            (7, "unexpected character '&'")
+        );
+
+        let err = parse_error("auth OR kids(A)");
+        assert_eq!(err.span.col, 9);
+        assert!(err.message.starts_with("unknown function 'kids'"));
+
+        let err = parse_error("path(A)");
+        assert_eq!(err.message, "'path' takes 2 arguments, found 1");
+
+        let err = parse_error("owner:auth");
+        assert!(err.message.contains("unknown selector 'owner:'"));
+
+        let err = parse_error("smoke ui");
+        assert_eq!(err.span.col, 7);
+        assert_eq!(err.message, "unexpected 'ui' after end of query");
+
+        let err = parse_error("name:\"Login");
+        assert_eq!(err.message, "unterminated string");
+    }
+}
@@ -0,0 +1,852 @@
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use petgraph::Direction;
+use petgraph::graph::NodeIndex;
+use petgraph::visit::EdgeRef;
+
+use crate::graph::analysis::{ancestors, loop_bounds};
+use crate::graph::builder::TestGraph;
+use crate::graph::traversal::Condensation;
+use crate::ir::IrNode;
+use crate::util::duration::parse_duration;
+
+/// Node config key for how much a node matters; higher runs earlier.
+pub const PRIORITY_CONFIG_KEY: &str = "priority";
+
+/// Node config key for how long a node is expected to take, e.g. `"30s"`.
+pub const DURATION_CONFIG_KEY: &str = "estimated_duration";
+
+/// Scheduling hints a node declares in its `config` block.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct NodeWeight {
+    /// Defaults to 0.
+    pub priority: f64,
+    pub duration: Option<Duration>,
+}
+
+impl NodeWeight {
+    /// Read the hints from a node's config entries.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `priority` is not a finite number or
+    /// `estimated_duration` is not a valid duration.
+    pub fn of(node: &IrNode) -> Result<Self, String> {
+        let mut weight = Self::default();
+        for (key, value) in &node.config {
+            match key.as_str() {
+                PRIORITY_CONFIG_KEY => {
+                    weight.priority = value
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|p| p.is_finite())
+                        .ok_or_else(|| format!("{key}: expected a number, got '{value}'"))?;
+                }
+                DURATION_CONFIG_KEY => {
+                    weight.duration =
+                        Some(parse_duration(value).map_err(|e| format!("{key}: {e}"))?);
+                }
+                _ => {}
+            }
+        }
+        Ok(weight)
+    }
+}
+
+/// Hints for every node, indexed like the graph. Invalid hints count as
+/// undeclared; validation reports them.
+pub fn node_weights(tg: &TestGraph) -> Vec<NodeWeight> {
+    tg.graph
+        .node_weights()
+        .map(|node| NodeWeight::of(node).unwrap_or_default())
+        .collect()
+}
+
+/// Estimated time each node takes, indexed like the graph: its
+/// `estimated_duration`, or zero without one, once per pass of a bounded loop.
+pub fn node_costs(tg: &TestGraph) -> Vec<Duration> {
+    let bounds = loop_bounds(tg);
+    node_weights(tg)
+        .into_iter()
+        .zip(tg.graph.node_indices())
+        .map(|(weight, n)| {
+            let passes = bounds.get(&n).map_or(1, |b| b.count());
+            weight.duration.unwrap_or_default() * passes
+        })
+        .collect()
+}
+
+/// A dependency-valid order that runs important and quick nodes first.
+///
+/// Each node is as urgent as the highest `priority` among itself and its
+/// descendants, so the setup an important node needs is pulled forward with
+/// it. Of the nodes whose dependencies are done, the most urgent runs next;
+/// ties go to the shortest `estimated_duration` (none counts as zero), so a
+/// failure shows up as soon as possible, and then to the plain topological
+/// order. Loops are unrolled as in [`topological`](crate::graph::traversal::topological): a loop runs as one block,
+/// as urgent as its most urgent node and as long as one pass over its nodes.
+///
+/// # Errors
+///
+/// Returns the same error as [`topological`](crate::graph::traversal::topological) if a cycle has no loop edge.
+pub fn weighted(tg: &TestGraph) -> Result<Vec<NodeIndex>, String> {
+    let cond = Condensation::of(tg)?;
+    let forward = tg.without_loops();
+    let weights = node_weights(tg);
+
+    let mut urgency: Vec<f64> = weights.iter().map(|w| w.priority).collect();
+    for &node in cond.order.iter().rev() {
+        for next in forward.graph.neighbors_directed(node, Direction::Outgoing) {
+            urgency[node.index()] = urgency[node.index()].max(urgency[next.index()]);
+        }
+    }
+    let block_urgency: Vec<f64> = cond
+        .components
+        .iter()
+        .map(|members| {
+            members
+                .iter()
+                .map(|n| urgency[n.index()])
+                .fold(f64::NEG_INFINITY, f64::max)
+        })
+        .collect();
+    let block_cost: Vec<Duration> = cond
+        .components
+        .iter()
+        .map(|members| {
+            members
+                .iter()
+                .map(|n| weights[n.index()].duration.unwrap_or_default())
+                .sum()
+        })
+        .collect();
+    // Greater is better: more urgent, then shorter, then earlier
+    let rank = |a: usize, b: usize| -> Ordering {
+        block_urgency[a]
+            .total_cmp(&block_urgency[b])
+            .then_with(|| block_cost[b].cmp(&block_cost[a]))
+            .then_with(|| b.cmp(&a))
+    };
+
+    let mut in_degree = cond.in_degrees();
+    let mut ready: Vec<usize> = (0..in_degree.len())
+        .filter(|&c| in_degree[c] == 0)
+        .collect();
+    let mut order = Vec::with_capacity(in_degree.len());
+    while let Some(best) = (0..ready.len()).max_by(|&i, &j| rank(ready[i], ready[j])) {
+        let c = ready.swap_remove(best);
+        order.push(c);
+        for &d in &cond.successors[c] {
+            in_degree[d] -= 1;
+            if in_degree[d] == 0 {
+                ready.push(d);
+            }
+        }
+    }
+    Ok(cond.unroll(tg, &order))
+}
+
+/// What budgeted selection tries to keep as much of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CoverageMetric {
+    /// Edges between selected nodes.
+    #[default]
+    Edges,
+    /// Distinct tags on selected nodes.
+    Tags,
+    /// Sum of the selected nodes' `priority`.
+    Priority,
+}
+
+impl fmt::Display for CoverageMetric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Edges => write!(f, "edges"),
+            Self::Tags => write!(f, "tags"),
+            Self::Priority => write!(f, "priority"),
+        }
+    }
+}
+
+impl FromStr for CoverageMetric {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "edges" => Ok(Self::Edges),
+            "tags" => Ok(Self::Tags),
+            "priority" => Ok(Self::Priority),
+            other => Err(format!(
+                "unknown coverage metric '{other}' (expected: edges, tags, priority)"
+            )),
+        }
+    }
+}
+
+/// The nodes [`select_within_budget`] keeps and drops.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BudgetSelection {
+    /// In declaration order; every ancestor of a selected node is selected.
+    pub selected: Vec<NodeIndex>,
+    pub dropped: Vec<NodeIndex>,
+    /// Estimated time the selected nodes take.
+    pub cost: Duration,
+    /// Share of the whole graph's coverage the selection keeps, in percent.
+    pub coverage: f64,
+    /// Selected nodes without an `estimated_duration`, counted as free.
+    pub unestimated: Vec<NodeIndex>,
+}
+
+/// How many extra starting points [`select_within_budget`] grows a selection
+/// from, besides the empty one.
+const BUDGET_RESTARTS: usize = 32;
+
+/// Choose nodes that fit in `budget` and keep as much coverage as possible.
+///
+/// A node can only be chosen together with all of its ancestors, which it
+/// needs for setup. The selection is greedy: each round adds the node, with
+/// whatever ancestors are still missing, that gains the most coverage per
+/// second of `estimated_duration` and still fits, until nothing fits.
+/// Because a cheap node can crowd out an expensive one that is worth more,
+/// the greedy rounds are also run starting from each of the
+/// [`BUDGET_RESTARTS`] nodes that are worth the most together with their
+/// ancestors and fit on their own, and the selection with the most coverage
+/// wins, the cheaper one on ties.
+/// Nodes without an estimate cost nothing, so the selection lists the ones
+/// it keeps, and nodes on a bounded loop cost their duration once per pass.
+///
+/// # Errors
+///
+/// Returns an error if the graph has nodes but none of them declares an
+/// `estimated_duration`, since there is then nothing to budget.
+pub fn select_within_budget(
+    tg: &TestGraph,
+    budget: Duration,
+    metric: CoverageMetric,
+) -> Result<BudgetSelection, String> {
+    let weights = node_weights(tg);
+    if tg.graph.node_count() > 0 && weights.iter().all(|w| w.duration.is_none()) {
+        return Err(
+            "cannot select within a budget: no node declares an estimated_duration".to_owned(),
+        );
+    }
+    let ctx = Budgeting::new(tg, metric);
+    let mut empty = Selection::new(&ctx);
+
+    // Most valuable first, then cheapest, then in declaration order
+    let mut starts: Vec<(f64, Duration, NodeIndex)> = tg
+        .node_indices
+        .iter()
+        .filter_map(|&node| {
+            let closure = &ctx.closures[node.index()];
+            let cost = ctx.cost(closure);
+            (cost <= budget).then(|| (empty.gain(&ctx, closure), cost, node))
+        })
+        .collect();
+    starts.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+
+    let mut best = empty.clone();
+    best.grow(&ctx, budget);
+    for &(_, _, node) in starts.iter().take(BUDGET_RESTARTS) {
+        let mut candidate = empty.clone();
+        candidate.add(&ctx, &ctx.closures[node.index()]);
+        candidate.grow(&ctx, budget);
+        let better = candidate
+            .covered
+            .total_cmp(&best.covered)
+            .then_with(|| best.spent.cmp(&candidate.spent))
+            == Ordering::Greater;
+        if better {
+            best = candidate;
+        }
+    }
+
+    let total = empty.gain(&ctx, &tg.node_indices);
+    let (selected, dropped): (Vec<NodeIndex>, _) =
+        tg.node_indices.iter().partition(|n| best.member[n.index()]);
+    let unestimated = selected
+        .iter()
+        .filter(|n| weights[n.index()].duration.is_none())
+        .copied()
+        .collect();
+    Ok(BudgetSelection {
+        selected,
+        dropped,
+        cost: best.spent,
+        coverage: if total == 0.0 {
+            100.0
+        } else {
+            (best.covered * 1000.0 / total).round() / 10.0
+        },
+        unestimated,
+    })
+}
+
+/// What [`select_within_budget`] computes once per graph.
+struct Budgeting<'a> {
+    tg: &'a TestGraph,
+    metric: CoverageMetric,
+    costs: Vec<Duration>,
+    priorities: Vec<f64>,
+    /// Each node's tags, as indices into the graph's distinct tags.
+    tags: Vec<Vec<usize>>,
+    tag_count: usize,
+    /// Each node with its ancestors.
+    closures: Vec<Vec<NodeIndex>>,
+}
+
+impl<'a> Budgeting<'a> {
+    fn new(tg: &'a TestGraph, metric: CoverageMetric) -> Self {
+        let mut ids: HashMap<&str, usize> = HashMap::new();
+        let tags = tg
+            .graph
+            .node_weights()
+            .map(|node| {
+                node.tags
+                    .iter()
+                    .map(|tag| {
+                        let next = ids.len();
+                        *ids.entry(tag.as_str()).or_insert(next)
+                    })
+                    .collect()
+            })
+            .collect();
+        Self {
+            tg,
+            metric,
+            costs: node_costs(tg),
+            priorities: node_weights(tg).iter().map(|w| w.priority).collect(),
+            tags,
+            tag_count: ids.len(),
+            closures: tg
+                .graph
+                .node_indices()
+                .map(|n| ancestors(tg, &[n]))
+                .collect(),
+        }
+    }
+
+    fn cost(&self, nodes: &[NodeIndex]) -> Duration {
+        nodes.iter().map(|&n| self.costs[n.index()]).sum()
+    }
+}
+
+/// A selection being built by [`select_within_budget`], with its coverage
+/// kept up to date as nodes are added.
+#[derive(Clone)]
+struct Selection {
+    member: Vec<bool>,
+    spent: Duration,
+    covered: f64,
+    /// Tags some selected node carries.
+    tagged: Vec<bool>,
+    /// Scratch marks for [`Selection::gain`], always cleared again.
+    pending: Vec<bool>,
+    pending_tags: Vec<bool>,
+}
+
+impl Selection {
+    fn new(ctx: &Budgeting) -> Self {
+        let nodes = ctx.tg.graph.node_count();
+        Self {
+            member: vec![false; nodes],
+            spent: Duration::ZERO,
+            covered: 0.0,
+            tagged: vec![false; ctx.tag_count],
+            pending: vec![false; nodes],
+            pending_tags: vec![false; ctx.tag_count],
+        }
+    }
+
+    /// Coverage gained by adding `nodes`, none of which are selected yet.
+    fn gain(&mut self, ctx: &Budgeting, nodes: &[NodeIndex]) -> f64 {
+        match ctx.metric {
+            CoverageMetric::Edges => {
+                for &n in nodes {
+                    self.pending[n.index()] = true;
+                }
+                // Count edges out of the new nodes to any node in the result,
+                // and edges into them from nodes already selected
+                let mut edges = 0;
+                for &n in nodes {
+                    for e in ctx.tg.graph.edges_directed(n, Direction::Outgoing) {
+                        let t = e.target().index();
+                        if self.member[t] || self.pending[t] {
+                            edges += 1;
+                        }
+                    }
+                    for e in ctx.tg.graph.edges_directed(n, Direction::Incoming) {
+                        if self.member[e.source().index()] {
+                            edges += 1;
+                        }
+                    }
+                }
+                for &n in nodes {
+                    self.pending[n.index()] = false;
+                }
+                f64::from(edges)
+            }
+            CoverageMetric::Tags => {
+                let mut new = Vec::new();
+                for &n in nodes {
+                    for &tag in &ctx.tags[n.index()] {
+                        if !self.tagged[tag] && !self.pending_tags[tag] {
+                            self.pending_tags[tag] = true;
+                            new.push(tag);
+                        }
+                    }
+                }
+                for &tag in &new {
+                    self.pending_tags[tag] = false;
+                }
+                new.len() as f64
+            }
+            CoverageMetric::Priority => nodes.iter().map(|&n| ctx.priorities[n.index()]).sum(),
+        }
+    }
+
+    /// Add `nodes`, skipping any already selected.
+    fn add(&mut self, ctx: &Budgeting, nodes: &[NodeIndex]) {
+        let new: Vec<NodeIndex> = nodes
+            .iter()
+            .copied()
+            .filter(|n| !self.member[n.index()])
+            .collect();
+        self.covered += self.gain(ctx, &new);
+        for &n in &new {
+            self.member[n.index()] = true;
+            self.spent += ctx.costs[n.index()];
+            for &tag in &ctx.tags[n.index()] {
+                self.tagged[tag] = true;
+            }
+        }
+    }
+
+    /// Add the best node per second, with its missing ancestors, until
+    /// nothing more fits in `budget`.
+    fn grow(&mut self, ctx: &Budgeting, budget: Duration) {
+        let mut missing = Vec::new();
+        loop {
+            // (coverage per second, gain, cost, node)
+            let mut best: Option<(f64, f64, Duration, NodeIndex)> = None;
+            for &node in &ctx.tg.node_indices {
+                if self.member[node.index()] {
+                    continue;
+                }
+                missing.clear();
+                missing.extend(
+                    ctx.closures[node.index()]
+                        .iter()
+                        .filter(|n| !self.member[n.index()]),
+                );
+                let extra = ctx.cost(&missing);
+                if self.spent + extra > budget {
+                    continue;
+                }
+                let gain = self.gain(ctx, &missing);
+                let rate = if extra.is_zero() {
+                    f64::INFINITY
+                } else {
+                    gain / extra.as_secs_f64()
+                };
+                let better = best
+                    .as_ref()
+                    .is_none_or(|(best_rate, best_gain, best_cost, _)| {
+                        rate.total_cmp(best_rate)
+                            .then_with(|| gain.total_cmp(best_gain))
+                            .then_with(|| best_cost.cmp(&extra))
+                            == Ordering::Greater
+                    });
+                if better {
+                    best = Some((rate, gain, extra, node));
+                }
+            }
+            let Some((_, _, _, node)) = best else {
+                break;
+            };
+            self.add(ctx, &ctx.closures[node.index()]);
+        }
+    }
+}
+
+/// One part of a sharded graph.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Shard {
+    /// Nodes this shard is responsible for, in declaration order.
+    pub owned: Vec<NodeIndex>,
+    /// Ancestors of owned nodes that another shard owns, repeated here as
+    /// setup, in declaration order.
+    pub setup: Vec<NodeIndex>,
+    /// Work the shard runs, owned and setup nodes together.
+    pub load: f64,
+}
+
+/// The result of [`shard`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sharding {
+    pub shards: Vec<Shard>,
+    /// Whether work is measured by `estimated_duration` rather than by
+    /// node count.
+    pub by_duration: bool,
+    /// Work repeated as setup across all shards, as a percentage of the
+    /// graph's work.
+    pub overhead: f64,
+}
+
+/// Split a graph into `count` shards that can each run on their own.
+///
+/// Every node is owned by exactly one shard, and each shard also runs the
+/// ancestors its nodes need. Work is measured in seconds of
+/// `estimated_duration` when any node declares one, else in nodes, counting
+/// each pass of a bounded loop. Nodes are placed heaviest first, weighing a
+/// node together with its ancestors, each on the shard whose work grows
+/// least, counting any ancestors it would have to repeat there; ties go to
+/// the lower shard. `count` must not be zero.
+pub fn shard(tg: &TestGraph, count: usize) -> Sharding {
+    let durations = node_costs(tg);
+    let by_duration = durations.iter().any(|d| !d.is_zero());
+    let bounds = loop_bounds(tg);
+    let cost = |n: NodeIndex| {
+        if by_duration {
+            durations[n.index()].as_secs_f64()
+        } else {
+            f64::from(bounds.get(&n).map_or(1, |b| b.count()))
+        }
+    };
+    let closures: Vec<Vec<NodeIndex>> = tg
+        .graph
+        .node_indices()
+        .map(|n| ancestors(tg, &[n]))
+        .collect();
+
+    let mut order = tg.node_indices.clone();
+    let weight = |n: NodeIndex| -> f64 { closures[n.index()].iter().map(|&a| cost(a)).sum() };
+    order.sort_by(|&a, &b| weight(b).total_cmp(&weight(a)));
+
+    let mut members: Vec<HashSet<NodeIndex>> = vec![HashSet::new(); count];
+    let mut owner = vec![0; tg.graph.node_count()];
+    let mut loads = vec![0.0; count];
+    for node in order {
+        let grown = |s: usize| -> f64 {
+            loads[s]
+                + closures[node.index()]
+                    .iter()
+                    .filter(|a| !members[s].contains(a))
+                    .map(|&a| cost(a))
+                    .sum::<f64>()
+        };
+        let target = (0..count)
+            .min_by(|&a, &b| grown(a).total_cmp(&grown(b)))
+            .unwrap_or_default();
+        loads[target] = grown(target);
+        members[target].extend(&closures[node.index()]);
+        owner[node.index()] = target;
+    }
+
+    let total: f64 = tg.node_indices.iter().map(|&n| cost(n)).sum();
+    let run: f64 = loads.iter().sum();
+    let shards = (0..count)
+        .map(|s| {
+            let (owned, setup) = tg
+                .node_indices
+                .iter()
+                .filter(|n| members[s].contains(n))
+                .partition(|n| owner[n.index()] == s);
+            Shard {
+                owned,
+                setup,
+                load: loads[s],
+            }
+        })
+        .collect();
+    Sharding {
+        shards,
+        by_duration,
+        overhead: if total == 0.0 {
+            0.0
+        } else {
+            ((run - total) * 1000.0 / total).round() / 10.0
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::builder::build;
+    use crate::ir::lower;
+    use crate::parser::parse::parse;
+
+    fn order(input: &str) -> Vec<String> {
+        let graphs = parse(input).expect("parse failed");
+        let tg = build(&lower(&graphs[0]).expect("lower failed"));
+        weighted(&tg)
+            .unwrap()
+            .into_iter()
+            .map(|n| tg.graph[n].name.clone())
+            .collect()
+    }
+
+    #[test]
+    fn reads_priority_and_duration_from_config() {
+        let graphs = parse(
+            r#"graph G {
+                node A { config { priority: 2.5, estimated_duration: "1m30s" } }
+                node B { config { estimated_duration: 45 } }
+            }"#,
+        )
+        .unwrap();
+        let ir = lower(&graphs[0]).unwrap();
+        assert_eq!(
+            NodeWeight::of(&ir.nodes[0]),
+            Ok(NodeWeight {
+                priority: 2.5,
+                duration: Some(Duration::from_secs(90)),
+            })
+        );
+        assert_eq!(
+            NodeWeight::of(&ir.nodes[1]).unwrap().duration,
+            Some(Duration::from_secs(45))
+        );
+    }
+
+    #[test]
+    fn important_nodes_pull_their_setup_forward() {
+        let input = r#"graph G {
+            node Login {}
+            node Profile {}
+            node Settings {}
+            node Checkout { config { priority: 3 } }
+            node Cart {}
+            Login -> Profile
+            Profile -> Settings
+            Login -> Cart
+            Cart -> Checkout
+        }"#;
+        assert_eq!(
+            order(input),
+            vec!["Login", "Cart", "Checkout", "Profile", "Settings"]
+        );
+    }
+
+    #[test]
+    fn shorter_nodes_break_ties() {
+        let input = r#"graph G {
+            node Slow { config { estimated_duration: "5m" } }
+            node Fast { config { estimated_duration: "10s" } }
+            node Unknown {}
+        }"#;
+        assert_eq!(order(input), vec!["Unknown", "Fast", "Slow"]);
+    }
+
+    const SHOP: &str = r#"graph Shop {
+        node Login { tags [auth] config { estimated_duration: "20s" } }
+        node Cart { tags [cart] config { estimated_duration: "30s" } }
+        node Pay { tags [cart, payments] config { priority: 5, estimated_duration: "1m" } }
+        node Search { tags [search] config { estimated_duration: "2m" } }
+        node Profile { tags [auth] config { estimated_duration: "10s" } }
+        Login -> Cart
+        Cart -> Pay
+        Login -> Search
+        Login -> Profile
+    }"#;
+
+    fn budgeted(budget: &str, metric: CoverageMetric) -> (Vec<String>, Vec<String>, f64) {
+        let graphs = parse(SHOP).expect("parse failed");
+        let tg = build(&lower(&graphs[0]).expect("lower failed"));
+        let selection = select_within_budget(&tg, parse_duration(budget).unwrap(), metric).unwrap();
+        let names = |nodes: &[NodeIndex]| -> Vec<String> {
+            nodes.iter().map(|&n| tg.graph[n].name.clone()).collect()
+        };
+        (
+            names(&selection.selected),
+            names(&selection.dropped),
+            selection.coverage,
+        )
+    }
+
+    #[test]
+    fn budget_keeps_the_cheapest_coverage_with_ancestors() {
+        let (selected, dropped, coverage) = budgeted("1m", CoverageMetric::Edges);
+        assert_eq!(selected, vec!["Login", "Cart", "Profile"]);
+        assert_eq!(dropped, vec!["Pay", "Search"]);
+        assert!((coverage - 50.0).abs() < f64::EPSILON);
+
+        // Pay is worth more than everything else, so its chain comes first
+        let (selected, _, _) = budgeted("2m", CoverageMetric::Priority);
+        assert_eq!(selected, vec!["Login", "Cart", "Pay", "Profile"]);
+
+        // Profile only repeats Login's tag
+        let (selected, _, coverage) = budgeted("50s", CoverageMetric::Tags);
+        assert_eq!(selected, vec!["Login", "Cart"]);
+        assert!((coverage - 50.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn budget_too_small_for_any_root_selects_nothing() {
+        let (selected, dropped, coverage) = budgeted("5s", CoverageMetric::Edges);
+        assert!(selected.is_empty());
+        assert_eq!(dropped.len(), 5);
+        assert!(coverage.abs() < f64::EPSILON);
+        assert_eq!("tags".parse(), Ok(CoverageMetric::Tags));
+        assert!("speed".parse::<CoverageMetric>().is_err());
+    }
+
+    #[test]
+    fn budget_prefers_one_valuable_node_over_a_cheap_one() {
+        let graphs = parse(
+            r#"graph G {
+                node X { tags [a, b, c, d] config { estimated_duration: "10m" } }
+                node Y { tags [e] config { estimated_duration: "1s" } }
+            }"#,
+        )
+        .unwrap();
+        let tg = build(&lower(&graphs[0]).unwrap());
+        let selection =
+            select_within_budget(&tg, parse_duration("10m").unwrap(), CoverageMetric::Tags)
+                .unwrap();
+        // Y has the better rate, but taking it first leaves no room for X
+        assert_eq!(names(&tg, &selection.selected), vec!["X"]);
+        assert_eq!(selection.cost, Duration::from_secs(600));
+        assert!((selection.coverage - 80.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn budget_reports_nodes_without_estimates() {
+        let graphs = parse(
+            r#"graph G {
+                node Login { config { estimated_duration: "20s" } }
+                node Profile {}
+                Login -> Profile
+            }"#,
+        )
+        .unwrap();
+        let tg = build(&lower(&graphs[0]).unwrap());
+        let selection =
+            select_within_budget(&tg, parse_duration("30s").unwrap(), CoverageMetric::Edges)
+                .unwrap();
+        assert_eq!(names(&tg, &selection.selected), vec!["Login", "Profile"]);
+        assert_eq!(names(&tg, &selection.unestimated), vec!["Profile"]);
+
+        let graphs = parse("graph G {\n node A {}\n node B {}\n A -> B\n}").unwrap();
+        let tg = build(&lower(&graphs[0]).unwrap());
+        let err = select_within_budget(&tg, parse_duration("1s").unwrap(), CoverageMetric::Edges)
+            .unwrap_err();
+        assert_eq!(
+            err,
+            "cannot select within a budget: no node declares an estimated_duration"
+        );
+    }
+
+    #[test]
+    fn budget_selection_scales_to_large_graphs() {
+        use std::fmt::Write;
+        use std::time::Instant;
+
+        use crate::util::rng::SplitMix64;
+
+        // 400 nodes, each with up to two parents declared before it
+        let mut rng = SplitMix64::new(1);
+        let mut input = String::from("graph Large {\n");
+        for i in 0..400 {
+            let secs = 1 + rng.below(59);
+            let tag = rng.below(50);
+            writeln!(
+                input,
+                "node N{i} {{ tags [t{tag}] config {{ estimated_duration: \"{secs}s\" }} }}"
+            )
+            .unwrap();
+        }
+        for i in 1..400 {
+            let (a, b) = (rng.below(i), rng.below(i));
+            writeln!(input, "N{a} -> N{i}").unwrap();
+            if b != a {
+                writeln!(input, "N{b} -> N{i}").unwrap();
+            }
+        }
+        input.push('}');
+        let graphs = parse(&input).expect("parse failed");
+        let tg = build(&lower(&graphs[0]).expect("lower failed"));
+
+        let budget = Duration::from_secs(600);
+        let start = Instant::now();
+        let selection = select_within_budget(&tg, budget, CoverageMetric::Edges).unwrap();
+        // A generous bound: this takes well under a second even unoptimized
+        assert!(start.elapsed().as_secs() < 10, "took {:?}", start.elapsed());
+
+        assert!(selection.cost <= budget);
+        assert!(selection.coverage > 0.0);
+        let selected: HashSet<NodeIndex> = selection.selected.iter().copied().collect();
+        assert_eq!(ancestors(&tg, &selection.selected).len(), selected.len());
+        let costs = node_costs(&tg);
+        let spent: Duration = selected.iter().map(|n| costs[n.index()]).sum();
+        assert_eq!(spent, selection.cost);
+    }
+
+    fn names(tg: &TestGraph, nodes: &[NodeIndex]) -> Vec<String> {
+        nodes.iter().map(|&n| tg.graph[n].name.clone()).collect()
+    }
+
+    #[test]
+    fn shards_balance_durations_and_repeat_ancestors() {
+        let graphs = parse(SHOP).expect("parse failed");
+        let tg = build(&lower(&graphs[0]).expect("lower failed"));
+        let sharding = shard(&tg, 2);
+        assert!(sharding.by_duration);
+        let [first, second] = &sharding.shards[..] else {
+            panic!("expected two shards");
+        };
+        assert_eq!(names(&tg, &first.owned), vec!["Search"]);
+        assert_eq!(names(&tg, &first.setup), vec!["Login"]);
+        assert_eq!(
+            names(&tg, &second.owned),
+            vec!["Login", "Cart", "Pay", "Profile"]
+        );
+        assert!(second.setup.is_empty());
+        assert_eq!((first.load, second.load), (140.0, 120.0));
+        // Login's 20s runs twice in 240s of work
+        assert!((sharding.overhead - 8.3).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn shards_count_nodes_without_durations() {
+        let graphs = parse(
+            r#"graph G {
+                node A {}
+                node B {}
+                node C {}
+                node D {}
+                A -> B
+                A -> C
+                A -> D
+            }"#,
+        )
+        .unwrap();
+        let tg = build(&lower(&graphs[0]).unwrap());
+        let sharding = shard(&tg, 2);
+        assert!(!sharding.by_duration);
+        let owned: Vec<Vec<String>> = sharding
+            .shards
+            .iter()
+            .map(|s| names(&tg, &s.owned))
+            .collect();
+        assert_eq!(owned, vec![vec!["B", "D"], vec!["A", "C"]]);
+        assert_eq!(names(&tg, &sharding.shards[0].setup), vec!["A"]);
+        assert!((sharding.overhead - 25.0).abs() < f64::EPSILON);
+
+        // More shards than nodes leaves some empty
+        let sharding = shard(&tg, 6);
+        assert_eq!(
+            sharding
+                .shards
+                .iter()
+                .filter(|s| s.owned.is_empty())
+                .count(),
+            2
+        );
+    }
+}
@@ -0,0 +1,306 @@
+use std::fmt::Write as _;
+
+use petgraph::Direction;
+use petgraph::graph::NodeIndex;
+use serde::{Deserialize, Serialize};
+
+use crate::graph::analysis::{
+    cyclic_components, dominated_counts, leaf_nodes, longest_chain, root_nodes, weak_components,
+};
+use crate::graph::builder::TestGraph;
+use crate::ir::params::BindingSource;
+
+/// How many nodes the fan-in, fan-out and dominator lists show.
+const HOT_SPOTS: usize = 3;
+
+/// Structural metrics of one graph.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphStats {
+    pub name: String,
+    pub nodes: usize,
+    pub edges: usize,
+    pub roots: Vec<String>,
+    pub leaves: Vec<String>,
+    /// Nodes on a longest chain, ignoring loop edges. Its length is the depth.
+    pub longest_chain: Vec<String>,
+    /// Nodes with the most incoming edges, most first.
+    pub fan_in: Vec<(String, usize)>,
+    /// Nodes with the most outgoing edges, most first.
+    pub fan_out: Vec<(String, usize)>,
+    pub weak_components: usize,
+    /// Strongly connected components that form cycles.
+    pub cycles: Vec<Vec<String>>,
+    /// Nodes whose failure blocks others from running, with the number of
+    /// nodes each blocks, most first.
+    pub blockers: Vec<(String, usize)>,
+    pub steps: usize,
+    /// Steps with at least one parameter still unresolved.
+    pub unresolved_steps: usize,
+}
+
+/// Limits that make `tast stats` fail, from `[stats]` or the command line.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Thresholds {
+    /// Most nodes allowed on a chain.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_depth: Option<usize>,
+    /// Most incoming edges allowed on one node.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_fan_in: Option<usize>,
+    /// Most outgoing edges allowed on one node.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_fan_out: Option<usize>,
+    /// Largest share of steps with unresolved parameters, in percent.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_unresolved: Option<f64>,
+}
+
+impl GraphStats {
+    /// Metrics of `tg`, after upstream parameters have been bound.
+    pub fn of(tg: &TestGraph) -> Self {
+        let names = |nodes: &[NodeIndex]| -> Vec<String> {
+            nodes.iter().map(|&n| tg.graph[n].name.clone()).collect()
+        };
+        let degrees = |direction: Direction| -> Vec<(String, usize)> {
+            let mut degrees: Vec<(String, usize)> = tg
+                .node_indices
+                .iter()
+                .map(|&idx| {
+                    let degree = tg.graph.edges_directed(idx, direction).count();
+                    (tg.graph[idx].name.clone(), degree)
+                })
+                .filter(|&(_, degree)| degree > 0)
+                .collect();
+            degrees.sort_by_key(|&(_, degree)| std::cmp::Reverse(degree));
+            degrees.truncate(HOT_SPOTS);
+            degrees
+        };
+        let steps = tg.node_indices.iter().flat_map(|&idx| &tg.graph[idx].steps);
+
+        Self {
+            name: tg.name.clone(),
+            nodes: tg.graph.node_count(),
+            edges: tg.graph.edge_count(),
+            roots: names(&root_nodes(tg)),
+            leaves: names(&leaf_nodes(tg)),
+            longest_chain: names(&longest_chain(tg)),
+            fan_in: degrees(Direction::Incoming),
+            fan_out: degrees(Direction::Outgoing),
+            weak_components: weak_components(tg).len(),
+            cycles: cyclic_components(tg).iter().map(|c| names(c)).collect(),
+            blockers: dominated_counts(tg)
+                .into_iter()
+                .take(HOT_SPOTS)
+                .map(|(idx, count)| (tg.graph[idx].name.clone(), count))
+                .collect(),
+            steps: steps.clone().count(),
+            unresolved_steps: steps
+                .filter(|step| {
+                    step.parameters
+                        .iter()
+                        .any(|p| p.source == BindingSource::Unresolved)
+                })
+                .count(),
+        }
+    }
+
+    /// Number of nodes on the longest chain.
+    pub fn depth(&self) -> usize {
+        self.longest_chain.len()
+    }
+
+    pub fn steps_per_node(&self) -> f64 {
+        if self.nodes == 0 {
+            0.0
+        } else {
+            self.steps as f64 / self.nodes as f64
+        }
+    }
+
+    /// Share of steps with unresolved parameters, in percent.
+    pub fn unresolved_percent(&self) -> f64 {
+        if self.steps == 0 {
+            0.0
+        } else {
+            self.unresolved_steps as f64 * 100.0 / self.steps as f64
+        }
+    }
+
+    /// One message per threshold the graph exceeds.
+    pub fn violations(&self, thresholds: &Thresholds) -> Vec<String> {
+        let mut violations = Vec::new();
+        if let Some(max) = thresholds.max_depth
+            && self.depth() > max
+        {
+            violations.push(format!("depth {} exceeds max-depth {max}", self.depth()));
+        }
+        let degree_limits = [
+            ("fan-in", &self.fan_in, thresholds.max_fan_in),
+            ("fan-out", &self.fan_out, thresholds.max_fan_out),
+        ];
+        for (label, degrees, limit) in degree_limits {
+            if let (Some(max), Some((node, degree))) = (limit, degrees.first())
+                && *degree > max
+            {
+                violations.push(format!(
+                    "{label} {degree} of '{node}' exceeds max-{label} {max}"
+                ));
+            }
+        }
+        if let Some(max) = thresholds.max_unresolved
+            && self.unresolved_percent() > max
+        {
+            violations.push(format!(
+                "unresolved parameters in {:.1}% of steps exceed max-unresolved {max}%",
+                self.unresolved_percent()
+            ));
+        }
+        violations
+    }
+
+    /// Human-readable report, indented under a `graph Name` line.
+    pub fn report(&self) -> String {
+        let list = |items: &[String]| {
+            if items.is_empty() {
+                "none".to_owned()
+            } else {
+                items.join(", ")
+            }
+        };
+        let counted = |items: &[(String, usize)]| {
+            let items: Vec<String> = items.iter().map(|(n, c)| format!("{n} ({c})")).collect();
+            list(&items)
+        };
+        let cycles: String = self
+            .cycles
+            .iter()
+            .map(|c| format!(" [{}]", c.join(", ")))
+            .collect();
+
+        let mut out = String::new();
+        let _ = writeln!(out, "graph {}", self.name);
+        let _ = writeln!(out, "  nodes: {}, edges: {}", self.nodes, self.edges);
+        let _ = writeln!(out, "  roots: {}", list(&self.roots));
+        let _ = writeln!(out, "  leaves: {}", list(&self.leaves));
+        let _ = writeln!(
+            out,
+            "  max depth: {} ({})",
+            self.depth(),
+            self.longest_chain.join(" -> ")
+        );
+        let _ = writeln!(out, "  fan-in: {}", counted(&self.fan_in));
+        let _ = writeln!(out, "  fan-out: {}", counted(&self.fan_out));
+        let _ = writeln!(
+            out,
+            "  weakly connected components: {}",
+            self.weak_components
+        );
+        let _ = writeln!(
+            out,
+            "  strongly connected components (cycles): {}{cycles}",
+            self.cycles.len()
+        );
+        let _ = writeln!(out, "  blocking nodes: {}", counted(&self.blockers));
+        let _ = writeln!(out, "  steps per node: {:.1}", self.steps_per_node());
+        let _ = writeln!(
+            out,
+            "  unresolved parameters: {} of {} steps ({:.1}%)",
+            self.unresolved_steps,
+            self.steps,
+            self.unresolved_percent()
+        );
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::builder::build;
+    use crate::graph::dataflow::ForwardMode;
+    use crate::ir::lower;
+    use crate::ir::params::bind_upstream_parameters;
+    use crate::parser::parse::parse;
+
+    fn stats_of(input: &str) -> GraphStats {
+        let graphs = parse(input).expect("parse failed");
+        let mut tg = build(&lower(&graphs[0]).expect("lower failed"));
+        bind_upstream_parameters(&mut tg, ForwardMode::default());
+        GraphStats::of(&tg)
+    }
+
+    const SHOP: &str = r#"graph Shop {
+        node Login {
+            given a user with email "a@b.com"
+        }
+        node Browse {
+            given a catalog
+        }
+        node Cart {
+            when they add <sku> to the cart
+        }
+        node Pay {
+            then they pay
+        }
+        node Audit {
+            then the log is written
+        }
+        Login -> Cart
+        Browse -> Cart
+        Cart -> Pay
+        Cart -> Audit
+    }"#;
+
+    #[test]
+    fn collects_structure_and_step_metrics() {
+        let stats = stats_of(SHOP);
+        assert_eq!((stats.nodes, stats.edges), (5, 4));
+        assert_eq!(stats.roots, vec!["Login", "Browse"]);
+        assert_eq!(stats.leaves, vec!["Pay", "Audit"]);
+        assert_eq!(stats.depth(), 3);
+        assert_eq!(stats.longest_chain, vec!["Login", "Cart", "Pay"]);
+        assert_eq!(stats.fan_in[0], ("Cart".to_owned(), 2));
+        assert_eq!(stats.fan_out[0], ("Cart".to_owned(), 2));
+        assert_eq!(stats.weak_components, 1);
+        assert!(stats.cycles.is_empty());
+        // Cart is reachable from either root, so nothing blocks it
+        assert_eq!(stats.blockers, vec![("Cart".to_owned(), 2)]);
+        assert_eq!(stats.steps, 5);
+        assert!((stats.steps_per_node() - 1.0).abs() < f64::EPSILON);
+        assert_eq!(stats.unresolved_steps, 1);
+        assert!((stats.unresolved_percent() - 20.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn reports_threshold_violations() {
+        let stats = stats_of(SHOP);
+        assert!(stats.violations(&Thresholds::default()).is_empty());
+        let thresholds = Thresholds {
+            max_depth: Some(2),
+            max_fan_in: Some(2),
+            max_fan_out: Some(1),
+            max_unresolved: Some(10.0),
+        };
+        assert_eq!(
+            stats.violations(&thresholds),
+            vec![
+                "depth 3 exceeds max-depth 2",
+                "fan-out 2 of 'Cart' exceeds max-fan-out 1",
+                "unresolved parameters in 20.0% of steps exceed max-unresolved 10%",
+            ]
+        );
+    }
+
+    #[test]
+    fn report_lists_every_metric() {
+        let report = stats_of(SHOP).report();
+        assert!(
+            report.starts_with("graph Shop\n  nodes: 5, edges: 4\n"),
+            "{report}"
+        );
+        assert!(report.contains("  max depth: 3 (Login -> Cart -> Pay)\n"));
+        assert!(report.contains("  blocking nodes: Cart (2)\n"));
+        assert!(report.contains("  unresolved parameters: 1 of 5 steps (20.0%)\n"));
+    }
+}
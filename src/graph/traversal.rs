@@ -1,9 +1,18 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, BinaryHeap, HashMap, HashSet};
 use std::fmt;
+use std::str::FromStr;
 
-use petgraph::algo::toposort;
+use petgraph::Direction;
+use petgraph::algo::{kosaraju_scc, toposort};
 use petgraph::graph::NodeIndex;
+use petgraph::visit::EdgeRef;
 
+use crate::graph::analysis::{find_cycle, loop_bounds};
 use crate::graph::builder::TestGraph;
+use crate::graph::schedule::weighted;
+use crate::util::glob::glob_match;
+use crate::util::rng::SplitMix64;
 
 /// Strategy for traversing a test graph.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -11,6 +20,32 @@ pub enum TraversalStrategy {
     Topological,
     DepthFirst,
     BreadthFirst,
+    /// One plan per simple root-to-leaf path; see [`crate::graph::paths`].
+    Paths,
+    /// One plan per path in a small set of root-to-leaf paths covering every
+    /// edge; see [`edge_cover`].
+    EdgeCover,
+    /// A shuffled topological order, the same for the same seed; see
+    /// [`random_topological`].
+    Random {
+        seed: u64,
+    },
+    /// Important and quick nodes first; see [`crate::graph::schedule::weighted`].
+    Weighted,
+}
+
+impl TraversalStrategy {
+    /// Names accepted by [`TraversalStrategy::from_str`], in the order they
+    /// are listed in help text and error messages.
+    pub const NAMES: &[&str] = &[
+        "topological",
+        "dfs",
+        "bfs",
+        "paths",
+        "edge-cover",
+        "random",
+        "weighted",
+    ];
 }
 
 impl fmt::Display for TraversalStrategy {
@@ -19,6 +54,30 @@ impl fmt::Display for TraversalStrategy {
             Self::Topological => write!(f, "topological"),
             Self::DepthFirst => write!(f, "dfs"),
             Self::BreadthFirst => write!(f, "bfs"),
+            Self::Paths => write!(f, "paths"),
+            Self::EdgeCover => write!(f, "edge-cover"),
+            Self::Random { .. } => write!(f, "random"),
+            Self::Weighted => write!(f, "weighted"),
+        }
+    }
+}
+
+impl FromStr for TraversalStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "topological" => Ok(Self::Topological),
+            "dfs" => Ok(Self::DepthFirst),
+            "bfs" => Ok(Self::BreadthFirst),
+            "paths" => Ok(Self::Paths),
+            "edge-cover" => Ok(Self::EdgeCover),
+            "random" => Ok(Self::Random { seed: 0 }),
+            "weighted" => Ok(Self::Weighted),
+            other => Err(format!(
+                "unknown strategy '{other}' (expected: {})",
+                Self::NAMES.join(", ")
+            )),
         }
     }
 }
@@ -27,23 +86,310 @@ impl fmt::Display for TraversalStrategy {
 ///
 /// # Errors
 ///
-/// Returns an error if the graph contains a cycle (topological only) or is otherwise invalid.
+/// Returns an error if the graph contains an unbounded cycle (topological only) or is
+/// otherwise invalid.
 pub fn traverse(tg: &TestGraph, strategy: TraversalStrategy) -> Result<Vec<NodeIndex>, String> {
     match strategy {
         TraversalStrategy::Topological => topological(tg),
         TraversalStrategy::DepthFirst => Ok(depth_first(tg)),
         TraversalStrategy::BreadthFirst => Ok(breadth_first(tg)),
+        TraversalStrategy::Random { seed } => random_topological(tg, seed).map(|r| r.nodes),
+        TraversalStrategy::Weighted => weighted(tg),
+        TraversalStrategy::Paths | TraversalStrategy::EdgeCover => Err(format!(
+            "the {strategy} strategy yields one plan per path, not a single order"
+        )),
+    }
+}
+
+/// The strongly connected components of a graph, the blocks every
+/// loop-aware order is built from.
+///
+/// Components are numbered by the position of their first member in a plain
+/// topological order that ignores loop edges, and each lists its members in
+/// that order. An order of components becomes a node order with
+/// [`Condensation::unroll`].
+pub(crate) struct Condensation {
+    pub components: Vec<Vec<NodeIndex>>,
+    /// Components each component has edges into, with repeats.
+    pub successors: Vec<Vec<usize>>,
+    /// Plain topological order of the nodes, ignoring loop edges.
+    pub order: Vec<NodeIndex>,
+}
+
+impl Condensation {
+    /// # Errors
+    ///
+    /// Returns an error with the full cycle path if a cycle has no loop edge.
+    pub fn of(tg: &TestGraph) -> Result<Self, String> {
+        let forward = tg.without_loops();
+        let order = toposort(&forward.graph, None).map_err(|e| match find_cycle(&forward) {
+            Some(mut cycle) => {
+                cycle.push(cycle[0].clone());
+                format!(
+                    "unbounded cycle {} (mark a loop edge with `repeat N` or `max N`)",
+                    cycle.join(" -> ")
+                )
+            }
+            None => format!(
+                "cycle detected involving node '{}'",
+                tg.graph[e.node_id()].name
+            ),
+        })?;
+
+        let mut position = vec![0; tg.graph.node_count()];
+        for (i, n) in order.iter().enumerate() {
+            position[n.index()] = i;
+        }
+        let mut components = kosaraju_scc(&tg.graph);
+        for members in &mut components {
+            members.sort_by_key(|n| position[n.index()]);
+        }
+        components.sort_by_key(|members| position[members[0].index()]);
+        let mut component_of = vec![0; tg.graph.node_count()];
+        for (c, members) in components.iter().enumerate() {
+            for n in members {
+                component_of[n.index()] = c;
+            }
+        }
+        let mut successors = vec![Vec::new(); components.len()];
+        for edge in tg.graph.edge_references() {
+            let (from, to) = (
+                component_of[edge.source().index()],
+                component_of[edge.target().index()],
+            );
+            if from != to {
+                successors[from].push(to);
+            }
+        }
+        Ok(Self {
+            components,
+            successors,
+            order,
+        })
+    }
+
+    /// Number of edges into each component from other components.
+    pub fn in_degrees(&self) -> Vec<usize> {
+        let mut in_degree = vec![0; self.components.len()];
+        for &to in self.successors.iter().flatten() {
+            in_degree[to] += 1;
+        }
+        in_degree
+    }
+
+    /// The nodes of the components in `order`, each component closed by a
+    /// loop edge repeated once per iteration of its bound (see
+    /// [`loop_bounds`]).
+    pub fn unroll(&self, tg: &TestGraph, order: &[usize]) -> Vec<NodeIndex> {
+        let bounds = loop_bounds(tg);
+        let mut nodes = Vec::with_capacity(self.order.len());
+        for &c in order {
+            let members = &self.components[c];
+            let iterations = bounds.get(&members[0]).map_or(1, |b| b.count());
+            for _ in 0..iterations {
+                nodes.extend(members);
+            }
+        }
+        nodes
     }
 }
 
-/// Topological sort — respects dependency order. Fails on cycles.
+/// Topological sort over strongly connected components — respects dependency
+/// order and unrolls bounded loops.
+///
+/// Loop edges are ignored for ordering. Each component closed by a loop edge is
+/// emitted as a block, its nodes in dependency order, once per iteration of its
+/// bound (see [`loop_bounds`]): `A -> B`, `B -> A repeat 2` yields `A B A B`.
+/// Ties go to the node that comes first in a plain topological sort, so acyclic
+/// graphs keep that order.
+///
+/// # Errors
+///
+/// Returns an error with the full cycle path if a cycle has no loop edge.
 pub fn topological(tg: &TestGraph) -> Result<Vec<NodeIndex>, String> {
-    toposort(&tg.graph, None).map_err(|e| {
-        let node_name = &tg.graph[e.node_id()].name;
-        format!("cycle detected involving node '{node_name}'")
+    let cond = Condensation::of(tg)?;
+    if tg.loops.is_empty() {
+        return Ok(cond.order);
+    }
+    // Kahn's algorithm over the condensation, earliest component first.
+    let mut in_degree = cond.in_degrees();
+    let mut ready: BinaryHeap<_> = (0..in_degree.len())
+        .filter(|&c| in_degree[c] == 0)
+        .map(Reverse)
+        .collect();
+    let mut order = Vec::with_capacity(in_degree.len());
+    while let Some(Reverse(c)) = ready.pop() {
+        order.push(c);
+        for &d in &cond.successors[c] {
+            in_degree[d] -= 1;
+            if in_degree[d] == 0 {
+                ready.push(Reverse(d));
+            }
+        }
+    }
+    Ok(cond.unroll(tg, &order))
+}
+
+/// Most states [`uniform_extension`] counts before it gives up on exact
+/// sampling.
+const EXACT_STATES: usize = 1 << 16;
+
+/// An order drawn by [`random_topological`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RandomOrder {
+    pub nodes: Vec<NodeIndex>,
+    /// Whether every valid order was equally likely. False when the graph had
+    /// too many partial orders to count (see [`uniform_extension`]).
+    pub uniform: bool,
+}
+
+/// A topological order drawn at random from `seed`.
+///
+/// Every valid order is equally likely, unless the graph is too large to
+/// count its orders; [`RandomOrder::uniform`] tells which. Loops are unrolled as in
+/// [`topological`]: each loop runs as a block, its nodes in an order drawn
+/// once and repeated on every iteration.
+///
+/// # Errors
+///
+/// Returns the same error as [`topological`] if a cycle has no loop edge.
+pub fn random_topological(tg: &TestGraph, seed: u64) -> Result<RandomOrder, String> {
+    let mut cond = Condensation::of(tg)?;
+    let mut rng = SplitMix64::new(seed);
+    let mut predecessors = vec![Vec::new(); cond.components.len()];
+    for (c, successors) in cond.successors.iter().enumerate() {
+        for &d in successors {
+            predecessors[d].push(c);
+        }
+    }
+    let (order, mut uniform) = uniform_extension(&predecessors, &mut rng);
+
+    let forward = tg.without_loops();
+    for members in cond.components.iter_mut().filter(|m| m.len() > 1) {
+        let predecessors: Vec<Vec<usize>> = members
+            .iter()
+            .map(|&n| {
+                forward
+                    .graph
+                    .neighbors_directed(n, Direction::Incoming)
+                    .filter_map(|pred| members.iter().position(|&m| m == pred))
+                    .collect()
+            })
+            .collect();
+        let (shuffled, exact) = uniform_extension(&predecessors, &mut rng);
+        uniform &= exact;
+        *members = shuffled.into_iter().map(|i| members[i]).collect();
+    }
+    Ok(RandomOrder {
+        nodes: cond.unroll(tg, &order),
+        uniform,
     })
 }
 
+/// A linear extension of the acyclic graph on `0..predecessors.len()`,
+/// drawn uniformly at random.
+///
+/// Each next unit is picked with probability proportional to the number of
+/// ways the remaining units can still be ordered after it, counted over the
+/// sets of placed units. Graphs with more than [`EXACT_STATES`] such sets
+/// fall back to picking uniformly among the ready units, which favours
+/// orders that keep many units ready; the returned flag is false then.
+fn uniform_extension(predecessors: &[Vec<usize>], rng: &mut SplitMix64) -> (Vec<usize>, bool) {
+    let total = predecessors.len();
+    let mut counter = ExtensionCounter {
+        predecessors,
+        counts: HashMap::new(),
+    };
+    let mut placed = vec![0u64; total.div_ceil(64)];
+    let exact = counter.count(&mut placed, total).is_some();
+
+    let mut order = Vec::with_capacity(total);
+    while order.len() < total {
+        let ready = counter.ready(&placed);
+        let pick = if exact {
+            let left = total - order.len() - 1;
+            let weights: Vec<f64> = ready
+                .iter()
+                .map(|&unit| {
+                    set_bit(&mut placed, unit, true);
+                    let weight = counter.count(&mut placed, left).unwrap_or_default();
+                    set_bit(&mut placed, unit, false);
+                    weight
+                })
+                .collect();
+            let mut target = rng.next_f64() * weights.iter().sum::<f64>();
+            let mut pick = ready.len() - 1;
+            for (i, weight) in weights.iter().enumerate() {
+                if target < *weight {
+                    pick = i;
+                    break;
+                }
+                target -= weight;
+            }
+            ready[pick]
+        } else {
+            ready[rng.below(ready.len())]
+        };
+        set_bit(&mut placed, pick, true);
+        order.push(pick);
+    }
+    (order, exact)
+}
+
+/// Counts the ways to finish a partial order, memoized by the set of units
+/// already placed.
+struct ExtensionCounter<'a> {
+    predecessors: &'a [Vec<usize>],
+    counts: HashMap<Vec<u64>, f64>,
+}
+
+impl ExtensionCounter<'_> {
+    /// Units not yet placed whose predecessors all are.
+    fn ready(&self, placed: &[u64]) -> Vec<usize> {
+        (0..self.predecessors.len())
+            .filter(|&unit| {
+                !has_bit(placed, unit)
+                    && self.predecessors[unit].iter().all(|&p| has_bit(placed, p))
+            })
+            .collect()
+    }
+
+    /// Number of orders of the `left` units not in `placed`, or `None` once
+    /// more than [`EXACT_STATES`] sets would be needed.
+    fn count(&mut self, placed: &mut Vec<u64>, left: usize) -> Option<f64> {
+        if left == 0 {
+            return Some(1.0);
+        }
+        if let Some(&count) = self.counts.get(placed.as_slice()) {
+            return Some(count);
+        }
+        if self.counts.len() >= EXACT_STATES {
+            return None;
+        }
+        let mut total = 0.0;
+        for unit in self.ready(placed) {
+            set_bit(placed, unit, true);
+            let count = self.count(placed, left - 1);
+            set_bit(placed, unit, false);
+            total += count?;
+        }
+        self.counts.insert(placed.clone(), total);
+        Some(total)
+    }
+}
+
+fn has_bit(bits: &[u64], i: usize) -> bool {
+    bits[i / 64] & (1 << (i % 64)) != 0
+}
+
+fn set_bit(bits: &mut [u64], i: usize, value: bool) {
+    if value {
+        bits[i / 64] |= 1 << (i % 64);
+    } else {
+        bits[i / 64] &= !(1 << (i % 64));
+    }
+}
+
 /// Depth-first traversal starting from root nodes.
 pub fn depth_first(tg: &TestGraph) -> Vec<NodeIndex> {
     use petgraph::visit::Dfs;
@@ -84,6 +430,127 @@ pub fn breadth_first(tg: &TestGraph) -> Vec<NodeIndex> {
     visited
 }
 
+/// Greedy edge cover: a small set of root-to-leaf paths that together take
+/// every edge at least once.
+///
+/// Parallel edges count once, and loop edges are left out since they never
+/// lie on a simple path. Each round picks the root-to-leaf path taking the
+/// most edges not yet covered, preferring earlier roots and edges on ties.
+/// Nodes without any edge get a single-node path.
+///
+/// # Errors
+///
+/// Returns an error if the graph contains an unbounded cycle.
+pub fn edge_cover(tg: &TestGraph) -> Result<Vec<Vec<NodeIndex>>, String> {
+    let forward = tg.without_loops();
+    // Reuse the unbounded-cycle report; `forward` has no loops to unroll.
+    let order = topological(&forward)?;
+    let g = &forward.graph;
+
+    let mut uncovered: BTreeSet<(NodeIndex, NodeIndex)> = distinct_edges(tg);
+    let mut paths = Vec::new();
+    while !uncovered.is_empty() {
+        // best[v]: most uncovered edges on a path from v to a leaf,
+        // with the first hop that achieves it.
+        let mut best: Vec<(usize, Option<NodeIndex>)> = vec![(0, None); g.node_count()];
+        for &node in order.iter().rev() {
+            let mut edges: Vec<_> = g.edges(node).collect();
+            edges.sort_by_key(|e| e.id());
+            for edge in edges {
+                let next = edge.target();
+                let gain = usize::from(uncovered.contains(&(node, next))) + best[next.index()].0;
+                if best[node.index()].1.is_none() || gain > best[node.index()].0 {
+                    best[node.index()] = (gain, Some(next));
+                }
+            }
+        }
+        let root = forward
+            .node_indices
+            .iter()
+            .copied()
+            .filter(|&n| {
+                g.neighbors_directed(n, Direction::Incoming)
+                    .next()
+                    .is_none()
+            })
+            .fold(None, |acc: Option<NodeIndex>, n| match acc {
+                Some(a) if best[a.index()].0 >= best[n.index()].0 => Some(a),
+                _ => Some(n),
+            });
+        let Some(root) = root.filter(|r| best[r.index()].0 > 0) else {
+            // Remaining edges are unreachable from any root
+            break;
+        };
+
+        let mut path = vec![root];
+        let mut at = root;
+        while let (_, Some(next)) = best[at.index()] {
+            uncovered.remove(&(at, next));
+            path.push(next);
+            at = next;
+        }
+        paths.push(path);
+    }
+
+    let touched: HashSet<NodeIndex> = paths.iter().flatten().copied().collect();
+    paths.extend(
+        tg.node_indices
+            .iter()
+            .filter(|n| !touched.contains(n))
+            .map(|&n| vec![n]),
+    );
+    Ok(paths)
+}
+
+/// How much a path adds to the coverage of the paths before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PathCoverage {
+    /// Distinct edges this path takes that no earlier path did.
+    pub new_edges: usize,
+    /// Nodes this path visits that no earlier path did.
+    pub new_nodes: usize,
+    /// Edges covered by this path and all earlier ones.
+    pub covered_edges: usize,
+    pub covered_nodes: usize,
+    /// Distinct non-loop edges and nodes in the whole graph.
+    pub total_edges: usize,
+    pub total_nodes: usize,
+}
+
+/// Incremental coverage of each path in `paths`, in order.
+pub fn path_coverage(tg: &TestGraph, paths: &[Vec<NodeIndex>]) -> Vec<PathCoverage> {
+    let all = distinct_edges(tg);
+    let mut seen_edges = HashSet::new();
+    let mut seen_nodes = HashSet::new();
+    paths
+        .iter()
+        .map(|path| {
+            let new_edges = path
+                .windows(2)
+                .filter(|w| all.contains(&(w[0], w[1])) && seen_edges.insert((w[0], w[1])))
+                .count();
+            let new_nodes = path.iter().filter(|&&n| seen_nodes.insert(n)).count();
+            PathCoverage {
+                new_edges,
+                new_nodes,
+                covered_edges: seen_edges.len(),
+                covered_nodes: seen_nodes.len(),
+                total_edges: all.len(),
+                total_nodes: tg.graph.node_count(),
+            }
+        })
+        .collect()
+}
+
+/// Distinct `(from, to)` pairs joined by a non-loop edge.
+fn distinct_edges(tg: &TestGraph) -> BTreeSet<(NodeIndex, NodeIndex)> {
+    tg.graph
+        .edge_references()
+        .filter(|e| !tg.loops.contains(&e.id()))
+        .map(|e| (e.source(), e.target()))
+        .collect()
+}
+
 /// Find the shortest path between two named nodes using BFS.
 ///
 /// # Errors
@@ -153,14 +620,18 @@ pub fn extract_subgraph(tg: &TestGraph, nodes: &[NodeIndex]) -> TestGraph {
     }
 
     // Add edges between included nodes
+    let mut loops = Vec::new();
     for edge_idx in tg.graph.edge_indices() {
         let (src, dst) = tg.graph.edge_endpoints(edge_idx).unwrap();
         if node_set.contains(&src) && node_set.contains(&dst) {
-            new_graph.add_edge(
+            let new_edge = new_graph.add_edge(
                 old_to_new[&src],
                 old_to_new[&dst],
                 tg.graph[edge_idx].clone(),
             );
+            if tg.loops.contains(&edge_idx) {
+                loops.push(new_edge);
+            }
         }
     }
 
@@ -168,10 +639,21 @@ pub fn extract_subgraph(tg: &TestGraph, nodes: &[NodeIndex]) -> TestGraph {
         name: tg.name.clone(),
         graph: new_graph,
         node_indices: new_node_indices,
+        loops,
     }
 }
 
-fn find_node_by_name(tg: &TestGraph, name: &str) -> Option<NodeIndex> {
+/// Nodes whose names match a glob pattern (see [`glob_match`]), in
+/// declaration order. A pattern without wildcards matches one node at most.
+pub fn find_nodes_matching(tg: &TestGraph, pattern: &str) -> Vec<NodeIndex> {
+    tg.node_indices
+        .iter()
+        .filter(|&&idx| glob_match(pattern, &tg.graph[idx].name))
+        .copied()
+        .collect()
+}
+
+pub(crate) fn find_node_by_name(tg: &TestGraph, name: &str) -> Option<NodeIndex> {
     tg.node_indices
         .iter()
         .find(|&&idx| tg.graph[idx].name == name)
@@ -254,6 +736,9 @@ mod tests {
                     steps: vec![],
                     tags: vec![],
                     requires: vec![],
+                    produces: vec![],
+                    forwards: vec![],
+                    config: vec![],
                     span: Span::default(),
                 },
                 IrNode {
@@ -262,6 +747,9 @@ mod tests {
                     steps: vec![],
                     tags: vec![],
                     requires: vec![],
+                    produces: vec![],
+                    forwards: vec![],
+                    config: vec![],
                     span: Span::default(),
                 },
             ],
@@ -273,6 +761,7 @@ mod tests {
                     to_index: 1,
                     passes: vec![],
                     description: None,
+                    bound: None,
                     span: Span::default(),
                 },
                 IrEdge {
@@ -282,10 +771,12 @@ mod tests {
                     to_index: 0,
                     passes: vec![],
                     description: None,
+                    bound: None,
                     span: Span::default(),
                 },
             ],
             fixtures: vec![],
+            config: vec![],
             span: Span::default(),
         };
         let tg = build(&ir);
@@ -294,11 +785,178 @@ mod tests {
         assert!(result.unwrap_err().contains("cycle"));
     }
 
+    fn names(tg: &TestGraph, order: &[NodeIndex]) -> Vec<String> {
+        order.iter().map(|&i| tg.graph[i].name.clone()).collect()
+    }
+
+    #[test]
+    fn topological_unrolls_bounded_loop() {
+        let tg = build_one(
+            r#"graph G {
+                node Start {}
+                node Submit {}
+                node Poll {}
+                node Done {}
+                Start -> Submit
+                Submit -> Poll
+                Poll -> Submit repeat 3
+                Poll -> Done
+            }"#,
+        );
+        let order = topological(&tg).unwrap();
+        assert_eq!(
+            names(&tg, &order),
+            vec![
+                "Start", "Submit", "Poll", "Submit", "Poll", "Submit", "Poll", "Done"
+            ]
+        );
+    }
+
+    #[test]
+    fn topological_unrolls_bounded_self_loop() {
+        let tg = build_one(
+            r#"graph G {
+                node Retry {}
+                node After {}
+                Retry -> Retry max 2
+                Retry -> After
+            }"#,
+        );
+        let order = topological(&tg).unwrap();
+        assert_eq!(names(&tg, &order), vec!["Retry", "Retry", "After"]);
+    }
+
+    #[test]
+    fn topological_keeps_loop_after_outside_dependencies() {
+        let tg = build_one(
+            r#"graph G {
+                node A {}
+                node Y {}
+                node C {}
+                A -> C
+                C -> A repeat 2
+                Y -> C
+            }"#,
+        );
+        let order = topological(&tg).unwrap();
+        assert_eq!(names(&tg, &order), vec!["Y", "A", "C", "A", "C"]);
+    }
+
+    #[test]
+    fn topological_reports_unbounded_cycle_path() {
+        let tg = build_one(
+            r#"graph G {
+                node A {}
+                node B {}
+                node C {}
+                A -> B
+                B -> C
+                C -> A
+            }"#,
+        );
+        let err = topological(&tg).unwrap_err();
+        assert!(
+            err.contains("unbounded cycle A -> B -> C -> A"),
+            "got: {err}"
+        );
+    }
+
+    // ── Edge Cover ─────────────────────────────────────────
+
+    #[test]
+    fn edge_cover_needs_fewer_paths_than_enumeration() {
+        // Two diamonds in a row: four root-to-leaf paths, but two cover every edge
+        let tg = build_one(
+            r#"graph G {
+                node A {}
+                node B {}
+                node C {}
+                node D {}
+                node E {}
+                node F {}
+                A -> B
+                A -> C
+                B -> D
+                C -> D
+                D -> E
+                D -> F
+            }"#,
+        );
+        let paths = edge_cover(&tg).unwrap();
+        let named: Vec<Vec<String>> = paths.iter().map(|p| names(&tg, p)).collect();
+        assert_eq!(
+            named,
+            vec![vec!["A", "B", "D", "E"], vec!["A", "C", "D", "F"]]
+        );
+
+        let coverage = path_coverage(&tg, &paths);
+        assert_eq!(coverage[0].new_edges, 3);
+        assert_eq!(coverage[1].new_edges, 3);
+        assert_eq!(coverage[1].covered_edges, coverage[1].total_edges);
+        assert_eq!(coverage[1].new_nodes, 2);
+    }
+
+    #[test]
+    fn edge_cover_adds_isolated_nodes_and_skips_loop_edges() {
+        let tg = build_one(
+            r#"graph G {
+                node A {}
+                node B {}
+                node Lonely {}
+                A -> B
+                B -> B repeat 2
+            }"#,
+        );
+        let paths = edge_cover(&tg).unwrap();
+        let named: Vec<Vec<String>> = paths.iter().map(|p| names(&tg, p)).collect();
+        assert_eq!(named, vec![vec!["A", "B"], vec!["Lonely"]]);
+        let coverage = path_coverage(&tg, &paths);
+        assert_eq!(coverage[0].total_edges, 1);
+        assert_eq!(coverage[1].covered_nodes, 3);
+    }
+
+    #[test]
+    fn edge_cover_rejects_unbounded_cycles() {
+        let tg = build_one(
+            r#"graph G {
+                node A {}
+                node B {}
+                A -> B
+                B -> A
+            }"#,
+        );
+        assert!(edge_cover(&tg).unwrap_err().contains("unbounded cycle"));
+    }
+
     #[test]
     fn display_strategy_names() {
         assert_eq!(TraversalStrategy::Topological.to_string(), "topological");
         assert_eq!(TraversalStrategy::DepthFirst.to_string(), "dfs");
         assert_eq!(TraversalStrategy::BreadthFirst.to_string(), "bfs");
+        assert_eq!(TraversalStrategy::Paths.to_string(), "paths");
+        assert_eq!(TraversalStrategy::EdgeCover.to_string(), "edge-cover");
+        assert_eq!(TraversalStrategy::Random { seed: 7 }.to_string(), "random");
+        assert_eq!(TraversalStrategy::Weighted.to_string(), "weighted");
+    }
+
+    #[test]
+    fn parses_strategy_names() {
+        assert_eq!(
+            "topological".parse::<TraversalStrategy>(),
+            Ok(TraversalStrategy::Topological)
+        );
+        assert_eq!("dfs".parse(), Ok(TraversalStrategy::DepthFirst));
+        assert_eq!("bfs".parse(), Ok(TraversalStrategy::BreadthFirst));
+        assert_eq!("paths".parse(), Ok(TraversalStrategy::Paths));
+        assert_eq!("edge-cover".parse(), Ok(TraversalStrategy::EdgeCover));
+        assert_eq!("random".parse(), Ok(TraversalStrategy::Random { seed: 0 }));
+        assert_eq!("weighted".parse(), Ok(TraversalStrategy::Weighted));
+        let err = "sideways".parse::<TraversalStrategy>().unwrap_err();
+        assert!(err.contains("unknown strategy 'sideways'"));
+        for name in TraversalStrategy::NAMES {
+            let strategy: TraversalStrategy = name.parse().unwrap();
+            assert_eq!(strategy.to_string(), *name);
+        }
     }
 
     #[test]
@@ -315,6 +973,91 @@ mod tests {
         assert_eq!(topo_result, traverse_result);
     }
 
+    // ── Random ─────────────────────────────────────────────
+
+    const FAN: &str = r#"graph G {
+        node Setup {}
+        node A {}
+        node B {}
+        node C {}
+        node D {}
+        node Done {}
+        Setup -> A
+        Setup -> B
+        Setup -> C
+        Setup -> D
+        A -> Done
+        B -> Done
+        C -> Done
+        D -> Done
+        Done -> Setup max 2
+    }"#;
+
+    #[test]
+    fn random_orders_are_valid_and_reproducible() {
+        let tg = build_one(FAN);
+        let mut orders = HashSet::new();
+        for seed in 0..20 {
+            let order = names(&tg, &random_topological(&tg, seed).unwrap().nodes);
+            // The loop runs twice, in the same order each time
+            assert_eq!(order.len(), 12);
+            assert_eq!(order[0], "Setup");
+            assert_eq!(order[5], "Done");
+            assert_eq!(order[..6], order[6..]);
+            assert_eq!(
+                names(&tg, &random_topological(&tg, seed).unwrap().nodes),
+                order
+            );
+            orders.insert(order);
+        }
+        // 24 ways to order the middle four; twenty seeds should hit several
+        assert!(orders.len() > 5, "only {} distinct orders", orders.len());
+    }
+
+    #[test]
+    fn random_orders_are_uniform() {
+        let tg = build_one(
+            r#"graph G {
+                node A {}
+                node B {}
+                node C {}
+                A -> B
+            }"#,
+        );
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for seed in 0..600 {
+            let order = names(&tg, &random_topological(&tg, seed).unwrap().nodes).concat();
+            *counts.entry(order).or_default() += 1;
+        }
+        // ABC, ACB and CAB are equally likely: about 200 each
+        assert_eq!(counts.len(), 3);
+        assert!(
+            counts.values().all(|&n| (150..250).contains(&n)),
+            "{counts:?}"
+        );
+    }
+
+    #[test]
+    fn uniform_extension_falls_back_on_large_graphs() {
+        // 20 independent units have 2^20 placed sets, more than are counted
+        let predecessors = vec![Vec::new(); 20];
+        let (order, exact) = uniform_extension(&predecessors, &mut SplitMix64::new(3));
+        assert!(!exact);
+        let mut sorted = order.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, (0..20).collect::<Vec<_>>());
+
+        let tg = build_one(FAN);
+        assert!(random_topological(&tg, 1).unwrap().uniform);
+    }
+
+    #[test]
+    fn random_rejects_unbounded_cycles() {
+        let tg = build_one(&FAN.replace("max 2", ""));
+        let err = traverse(&tg, TraversalStrategy::Random { seed: 1 }).unwrap_err();
+        assert!(err.contains("unbounded cycle"), "{err}");
+    }
+
     // ── DFS ────────────────────────────────────────────────
 
     #[test]
@@ -572,6 +1315,26 @@ mod tests {
         assert_eq!(path.len(), 1);
     }
 
+    #[test]
+    fn find_nodes_matching_globs_in_declaration_order() {
+        let tg = build_one(
+            r#"graph G {
+                node LoginForm {}
+                node Register {}
+                node LoginApi {}
+            }"#,
+        );
+        let names = |pattern: &str| -> Vec<String> {
+            find_nodes_matching(&tg, pattern)
+                .iter()
+                .map(|&i| tg.graph[i].name.clone())
+                .collect()
+        };
+        assert_eq!(names("Login*"), vec!["LoginForm", "LoginApi"]);
+        assert_eq!(names("Register"), vec!["Register"]);
+        assert!(names("Logout*").is_empty());
+    }
+
     // ── Subgraph Extraction ────────────────────────────────
 
     #[test]
@@ -0,0 +1,718 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use serde::Serialize;
+
+use crate::ir::{IrEdge, IrGraph, IrNode, IrStep};
+use crate::util::span::Span;
+
+/// Semantic differences between two versions of a set of graphs, matched by
+/// graph name.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Diff {
+    pub added_graphs: Vec<String>,
+    pub removed_graphs: Vec<String>,
+    /// Graphs present in both versions that changed.
+    pub graphs: Vec<GraphDiff>,
+}
+
+/// What changed in one graph.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct GraphDiff {
+    pub name: String,
+    pub added_nodes: Vec<String>,
+    pub removed_nodes: Vec<String>,
+    pub renamed_nodes: Vec<Rename>,
+    pub changed_nodes: Vec<NodeChange>,
+    /// `From -> To`, with renamed endpoints under their new names.
+    pub added_edges: Vec<String>,
+    pub removed_edges: Vec<String>,
+    pub changed_passes: Vec<PassesChange>,
+    pub changed_bounds: Vec<BoundChange>,
+    /// Nodes whose place in the compiled plan moved relative to the others.
+    pub plan_order: Vec<OrderChange>,
+    /// Why the plan order could not be compared, when a version does not
+    /// compile.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plan_order_error: Option<String>,
+}
+
+/// A node whose name changed while its content stayed the same.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Rename {
+    pub from: String,
+    pub to: String,
+}
+
+/// Changes to the steps, tags, data declarations and config of a node in
+/// both versions.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct NodeChange {
+    pub name: String,
+    /// Steps as `keyword text`, e.g. `given a registered user`.
+    pub removed_steps: Vec<String>,
+    pub added_steps: Vec<String>,
+    pub removed_tags: Vec<String>,
+    pub added_tags: Vec<String>,
+    pub removed_requires: Vec<String>,
+    pub added_requires: Vec<String>,
+    pub removed_produces: Vec<String>,
+    pub added_produces: Vec<String>,
+    pub removed_forwards: Vec<String>,
+    pub added_forwards: Vec<String>,
+    /// Config entries as `key: value`; a changed value is removed and added.
+    pub removed_config: Vec<String>,
+    pub added_config: Vec<String>,
+}
+
+/// An edge whose `passes` fields changed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PassesChange {
+    pub edge: String,
+    pub old: Vec<String>,
+    pub new: Vec<String>,
+}
+
+/// An edge whose loop bound changed, was added, or was removed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BoundChange {
+    pub edge: String,
+    /// `repeat N` or `max N`, or `None` for an edge without a bound.
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+/// A node that moved in the compiled plan, with 1-based positions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OrderChange {
+    pub node: String,
+    pub old_position: usize,
+    pub new_position: usize,
+}
+
+impl Diff {
+    /// Compare two versions of a set of graphs.
+    pub fn between(old: &[IrGraph], new: &[IrGraph]) -> Self {
+        let mut diff = Self::default();
+        for graph in new {
+            match old.iter().find(|g| g.name == graph.name) {
+                Some(before) => {
+                    let changes = GraphDiff::between(before, graph);
+                    if !changes.is_empty() {
+                        diff.graphs.push(changes);
+                    }
+                }
+                None => diff.added_graphs.push(graph.name.clone()),
+            }
+        }
+        diff.removed_graphs = old
+            .iter()
+            .filter(|g| !new.iter().any(|n| n.name == g.name))
+            .map(|g| g.name.clone())
+            .collect();
+        diff
+    }
+
+    /// Compare the compiled plan order of every graph in both versions.
+    ///
+    /// `plan_order` gives the node order of a graph's plan. Graphs whose
+    /// order moved are reported even without other changes. When either
+    /// version fails to compile, the error is kept with the graph's other
+    /// changes instead of failing the whole diff.
+    pub fn compare_plan_order(
+        &mut self,
+        old: &[IrGraph],
+        new: &[IrGraph],
+        plan_order: impl Fn(&IrGraph) -> Result<Vec<String>, String>,
+    ) {
+        let mut changed = std::mem::take(&mut self.graphs);
+        for after in new {
+            let Some(before) = old.iter().find(|g| g.name == after.name) else {
+                continue;
+            };
+            let mut graph = match changed.iter().position(|g| g.name == after.name) {
+                Some(i) => changed.remove(i),
+                None => GraphDiff {
+                    name: after.name.clone(),
+                    ..GraphDiff::default()
+                },
+            };
+            match (plan_order(before), plan_order(after)) {
+                (Ok(old_order), Ok(new_order)) => graph.set_plan_order(&old_order, &new_order),
+                (Err(e), _) => graph.plan_order_error = Some(format!("old version: {e}")),
+                (_, Err(e)) => graph.plan_order_error = Some(format!("new version: {e}")),
+            }
+            if !graph.is_empty() {
+                self.graphs.push(graph);
+            }
+        }
+    }
+
+    /// Whether nothing changed.
+    pub fn is_empty(&self) -> bool {
+        self.added_graphs.is_empty() && self.removed_graphs.is_empty() && self.graphs.is_empty()
+    }
+
+    /// Human-readable report, one line per change.
+    pub fn report(&self) -> String {
+        if self.is_empty() {
+            return "no changes\n".to_owned();
+        }
+        let mut out = String::new();
+        for name in &self.added_graphs {
+            let _ = writeln!(out, "+ graph {name}");
+        }
+        for name in &self.removed_graphs {
+            let _ = writeln!(out, "- graph {name}");
+        }
+        for graph in &self.graphs {
+            graph.write_report(&mut out);
+        }
+        out
+    }
+}
+
+impl GraphDiff {
+    /// Compare two versions of a graph.
+    ///
+    /// A removed node and an added node with identical, non-empty steps and
+    /// otherwise identical content count as a rename. Edges are compared
+    /// after renaming, so an edge that only follows a renamed node is not
+    /// reported.
+    pub fn between(old: &IrGraph, new: &IrGraph) -> Self {
+        let find = |graph: &IrGraph, name: &str| graph.nodes.iter().any(|n| n.name == name);
+        let mut removed: Vec<&IrNode> = old.nodes.iter().filter(|n| !find(new, &n.name)).collect();
+        let mut added: Vec<&IrNode> = new.nodes.iter().filter(|n| !find(old, &n.name)).collect();
+
+        let mut renamed_nodes = Vec::new();
+        removed.retain(|before| {
+            let Some(pos) = added
+                .iter()
+                .position(|after| !before.steps.is_empty() && same_content(before, after))
+            else {
+                return true;
+            };
+            renamed_nodes.push(Rename {
+                from: before.name.clone(),
+                to: added.remove(pos).name.clone(),
+            });
+            false
+        });
+
+        let changed_nodes = old
+            .nodes
+            .iter()
+            .filter_map(|before| {
+                let after = new.nodes.iter().find(|n| n.name == before.name)?;
+                let change = NodeChange::between(before, after);
+                (!change.is_empty()).then_some(change)
+            })
+            .collect();
+
+        let mut diff = Self {
+            name: new.name.clone(),
+            added_nodes: added.iter().map(|n| n.name.clone()).collect(),
+            removed_nodes: removed.iter().map(|n| n.name.clone()).collect(),
+            renamed_nodes,
+            changed_nodes,
+            ..Self::default()
+        };
+
+        let old_edges = edge_shapes(&old.edges, |name| diff.renamed(name));
+        let new_edges = edge_shapes(&new.edges, |name| name.to_owned());
+        for (key, after) in &new_edges {
+            let Some(before) = old_edges.get(key) else {
+                diff.added_edges.push(key.clone());
+                continue;
+            };
+            if before.passes != after.passes {
+                diff.changed_passes.push(PassesChange {
+                    edge: key.clone(),
+                    old: before.passes.clone(),
+                    new: after.passes.clone(),
+                });
+            }
+            if before.bound != after.bound {
+                diff.changed_bounds.push(BoundChange {
+                    edge: key.clone(),
+                    old: before.bound.clone(),
+                    new: after.bound.clone(),
+                });
+            }
+        }
+        diff.removed_edges = old_edges
+            .keys()
+            .filter(|key| !new_edges.contains_key(*key))
+            .cloned()
+            .collect();
+        diff
+    }
+
+    /// Record how the compiled plan order changed, given the node order of
+    /// the old and new plans. Nodes in only one plan are ignored; of the
+    /// rest, those outside the longest common subsequence count as moved.
+    pub fn set_plan_order(&mut self, old: &[String], new: &[String]) {
+        let old: Vec<String> = first_occurrences(old)
+            .iter()
+            .map(|name| self.renamed(name))
+            .collect();
+        let new = first_occurrences(new);
+        let old_common: Vec<&String> = old.iter().filter(|n| new.contains(n)).collect();
+        let new_common: Vec<&String> = new.iter().filter(|n| old.contains(n)).collect();
+        let kept = lcs(&old_common, &new_common);
+        let position = |order: &[String], name: &str| order.iter().position(|n| n == name);
+
+        self.plan_order = new_common
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !kept.iter().any(|&(_, j)| j == *i))
+            .filter_map(|(_, name)| {
+                Some(OrderChange {
+                    node: (*name).clone(),
+                    old_position: position(&old, name)? + 1,
+                    new_position: position(&new, name)? + 1,
+                })
+            })
+            .collect();
+    }
+
+    /// Whether nothing changed.
+    pub fn is_empty(&self) -> bool {
+        self.added_nodes.is_empty()
+            && self.removed_nodes.is_empty()
+            && self.renamed_nodes.is_empty()
+            && self.changed_nodes.is_empty()
+            && self.added_edges.is_empty()
+            && self.removed_edges.is_empty()
+            && self.changed_passes.is_empty()
+            && self.changed_bounds.is_empty()
+            && self.plan_order.is_empty()
+    }
+
+    /// The new name of an old node.
+    fn renamed(&self, name: &str) -> String {
+        self.renamed_nodes
+            .iter()
+            .find(|r| r.from == name)
+            .map_or_else(|| name.to_owned(), |r| r.to.clone())
+    }
+
+    fn write_report(&self, out: &mut String) {
+        let _ = writeln!(out, "graph {}", self.name);
+        for name in &self.added_nodes {
+            let _ = writeln!(out, "  + node {name}");
+        }
+        for name in &self.removed_nodes {
+            let _ = writeln!(out, "  - node {name}");
+        }
+        for rename in &self.renamed_nodes {
+            let _ = writeln!(out, "  ~ node {} renamed to {}", rename.from, rename.to);
+        }
+        for node in &self.changed_nodes {
+            let _ = writeln!(out, "  ~ node {}", node.name);
+            let lines = [
+                ("step", "-", &node.removed_steps),
+                ("step", "+", &node.added_steps),
+                ("tag", "-", &node.removed_tags),
+                ("tag", "+", &node.added_tags),
+                ("requires", "-", &node.removed_requires),
+                ("requires", "+", &node.added_requires),
+                ("produces", "-", &node.removed_produces),
+                ("produces", "+", &node.added_produces),
+                ("forwards", "-", &node.removed_forwards),
+                ("forwards", "+", &node.added_forwards),
+                ("config", "-", &node.removed_config),
+                ("config", "+", &node.added_config),
+            ];
+            for (what, sign, items) in lines {
+                for item in items {
+                    let _ = writeln!(out, "      {sign} {what} {item}");
+                }
+            }
+        }
+        for edge in &self.added_edges {
+            let _ = writeln!(out, "  + edge {edge}");
+        }
+        for edge in &self.removed_edges {
+            let _ = writeln!(out, "  - edge {edge}");
+        }
+        for change in &self.changed_passes {
+            let _ = writeln!(
+                out,
+                "  ~ edge {} passes {{ {} }} -> {{ {} }}",
+                change.edge,
+                change.old.join(", "),
+                change.new.join(", ")
+            );
+        }
+        for change in &self.changed_bounds {
+            let bound = |b: &Option<String>| b.clone().unwrap_or_else(|| "unbounded".to_owned());
+            let _ = writeln!(
+                out,
+                "  ~ edge {} {} -> {}",
+                change.edge,
+                bound(&change.old),
+                bound(&change.new)
+            );
+        }
+        if let Some(error) = &self.plan_order_error {
+            let _ = writeln!(out, "  ! plan order not compared: {error}");
+        }
+        if !self.plan_order.is_empty() {
+            let _ = writeln!(out, "  ~ plan order");
+            for moved in &self.plan_order {
+                let _ = writeln!(
+                    out,
+                    "      {}: step {} -> {}",
+                    moved.node, moved.old_position, moved.new_position
+                );
+            }
+        }
+    }
+}
+
+impl NodeChange {
+    fn between(old: &IrNode, new: &IrNode) -> Self {
+        let old_steps: Vec<String> = old.steps.iter().map(step_line).collect();
+        let new_steps: Vec<String> = new.steps.iter().map(step_line).collect();
+        let kept = lcs(&old_steps, &new_steps);
+        let (removed_tags, added_tags) = set_changes(&old.tags, &new.tags);
+        let (removed_requires, added_requires) = set_changes(&old.requires, &new.requires);
+        let (removed_produces, added_produces) = set_changes(&old.produces, &new.produces);
+        let (removed_forwards, added_forwards) = set_changes(&old.forwards, &new.forwards);
+        let config = |n: &IrNode| -> Vec<String> {
+            n.config.iter().map(|(k, v)| format!("{k}: {v}")).collect()
+        };
+        let (removed_config, added_config) = set_changes(&config(old), &config(new));
+        Self {
+            name: new.name.clone(),
+            removed_steps: unmatched(&old_steps, kept.iter().map(|&(i, _)| i)),
+            added_steps: unmatched(&new_steps, kept.iter().map(|&(_, j)| j)),
+            removed_tags,
+            added_tags,
+            removed_requires,
+            added_requires,
+            removed_produces,
+            added_produces,
+            removed_forwards,
+            added_forwards,
+            removed_config,
+            added_config,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.removed_steps.is_empty()
+            && self.added_steps.is_empty()
+            && self.removed_tags.is_empty()
+            && self.added_tags.is_empty()
+            && self.removed_requires.is_empty()
+            && self.added_requires.is_empty()
+            && self.removed_produces.is_empty()
+            && self.added_produces.is_empty()
+            && self.removed_forwards.is_empty()
+            && self.added_forwards.is_empty()
+            && self.removed_config.is_empty()
+            && self.added_config.is_empty()
+    }
+}
+
+fn step_line(step: &IrStep) -> String {
+    format!("{} {}", step.step_type, step.text)
+}
+
+/// Whether two nodes differ only in name and position.
+fn same_content(a: &IrNode, b: &IrNode) -> bool {
+    let strip = |n: &IrNode| IrNode {
+        name: String::new(),
+        span: Span::default(),
+        ..n.clone()
+    };
+    strip(a) == strip(b)
+}
+
+/// What the edges between two nodes carry, compared across versions.
+#[derive(Default)]
+struct EdgeShape {
+    passes: Vec<String>,
+    bound: Option<String>,
+}
+
+/// `From -> To` to the `passes` and loop bound of every edge between the two
+/// nodes, with endpoint names mapped through `name`.
+fn edge_shapes(edges: &[IrEdge], name: impl Fn(&str) -> String) -> BTreeMap<String, EdgeShape> {
+    let mut shapes: BTreeMap<String, EdgeShape> = BTreeMap::new();
+    for edge in edges {
+        let key = format!("{} -> {}", name(&edge.from), name(&edge.to));
+        let shape = shapes.entry(key).or_default();
+        shape.passes.extend(edge.passes.clone());
+        if let Some(bound) = edge.bound {
+            shape.bound = Some(bound.to_string());
+        }
+    }
+    shapes
+}
+
+/// Items only in `old`, and items only in `new`, each in their own order.
+fn set_changes(old: &[String], new: &[String]) -> (Vec<String>, Vec<String>) {
+    let only = |a: &[String], b: &[String]| a.iter().filter(|x| !b.contains(x)).cloned().collect();
+    (only(old, new), only(new, old))
+}
+
+fn unmatched(items: &[String], matched: impl Iterator<Item = usize>) -> Vec<String> {
+    let matched: Vec<usize> = matched.collect();
+    items
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !matched.contains(i))
+        .map(|(_, item)| item.clone())
+        .collect()
+}
+
+fn first_occurrences(names: &[String]) -> Vec<String> {
+    let mut seen = Vec::new();
+    for name in names {
+        if !seen.contains(name) {
+            seen.push(name.clone());
+        }
+    }
+    seen
+}
+
+/// Index pairs of a longest common subsequence of `a` and `b`.
+fn lcs<T: PartialEq>(a: &[T], b: &[T]) -> Vec<(usize, usize)> {
+    let mut lengths = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lengths[i][j] = if a[i] == b[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    let mut pairs = Vec::new();
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            pairs.push((i, j));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::lower;
+    use crate::parser::parse::parse;
+
+    fn lower_one(input: &str) -> IrGraph {
+        lower(&parse(input).expect("parse failed")[0]).expect("lower failed")
+    }
+
+    const BASE: &str = r#"graph Shop {
+  node Browse {
+    tags [smoke]
+    given a visitor
+  }
+  node Cart {
+    given a cart
+    then it is empty
+  }
+  node Pay {
+    then the order is paid
+  }
+  Browse -> Cart
+  Cart -> Pay { passes { cart_id } }
+}"#;
+
+    #[test]
+    fn identical_graphs_have_no_diff() {
+        let ir = lower_one(BASE);
+        let diff = Diff::between(std::slice::from_ref(&ir), std::slice::from_ref(&ir));
+        assert!(diff.is_empty());
+        assert_eq!(diff.report(), "no changes\n");
+    }
+
+    #[test]
+    fn reports_node_content_changes() {
+        let old = lower_one(BASE);
+        let new = lower_one(
+            &BASE
+                .replace("tags [smoke]", "tags [smoke, ui]")
+                .replace("then it is empty", "then it has one item")
+                .replace("node Pay {", "node Pay {\n    requires { cart_id }"),
+        );
+        let diff = GraphDiff::between(&old, &new);
+        assert_eq!(diff.changed_nodes.len(), 3);
+        assert_eq!(diff.changed_nodes[0].added_tags, vec!["ui"]);
+        assert_eq!(
+            diff.changed_nodes[1].removed_steps,
+            vec!["then it is empty"]
+        );
+        assert_eq!(
+            diff.changed_nodes[1].added_steps,
+            vec!["then it has one item"]
+        );
+        assert_eq!(diff.changed_nodes[2].added_requires, vec!["cart_id"]);
+        assert!(diff.added_nodes.is_empty() && diff.added_edges.is_empty());
+    }
+
+    #[test]
+    fn detects_renames_and_follows_their_edges() {
+        let old = lower_one(BASE);
+        let new = lower_one(&BASE.replace("Cart", "Basket"));
+        let diff = GraphDiff::between(&old, &new);
+        assert_eq!(
+            diff.renamed_nodes,
+            vec![Rename {
+                from: "Cart".into(),
+                to: "Basket".into()
+            }]
+        );
+        assert!(diff.added_nodes.is_empty() && diff.removed_nodes.is_empty());
+        assert!(diff.added_edges.is_empty() && diff.removed_edges.is_empty());
+    }
+
+    #[test]
+    fn reports_edges_and_passes() {
+        let old = lower_one(BASE);
+        let new = lower_one(
+            &BASE
+                .replace("  Browse -> Cart\n", "  Browse -> Pay\n")
+                .replace("passes { cart_id }", "passes { cart_id, total }"),
+        );
+        let diff = GraphDiff::between(&old, &new);
+        assert_eq!(diff.added_edges, vec!["Browse -> Pay"]);
+        assert_eq!(diff.removed_edges, vec!["Browse -> Cart"]);
+        assert_eq!(
+            diff.changed_passes,
+            vec![PassesChange {
+                edge: "Cart -> Pay".into(),
+                old: vec!["cart_id".into()],
+                new: vec!["cart_id".into(), "total".into()],
+            }]
+        );
+        let report = Diff {
+            graphs: vec![diff],
+            ..Diff::default()
+        }
+        .report();
+        assert!(report.contains("  + edge Browse -> Pay\n"), "{report}");
+        assert!(
+            report.contains("  ~ edge Cart -> Pay passes { cart_id } -> { cart_id, total }\n"),
+            "{report}"
+        );
+    }
+
+    #[test]
+    fn reports_loop_bounds_and_data_declarations() {
+        let old = lower_one(BASE);
+        let new = lower_one(
+            &BASE
+                .replace(
+                    "  Cart -> Pay { passes { cart_id } }\n",
+                    "  Cart -> Pay { passes { cart_id } }\n  Pay -> Cart repeat 3\n",
+                )
+                .replace(
+                    "node Cart {",
+                    "node Cart {\n    produces { cart_id }\n    forwards { user_id }\n    config { priority: 2 }",
+                ),
+        );
+        let diff = GraphDiff::between(&old, &new);
+        assert_eq!(diff.added_edges, vec!["Pay -> Cart"]);
+        let cart = &diff.changed_nodes[0];
+        assert_eq!(cart.added_produces, vec!["cart_id"]);
+        assert_eq!(cart.added_forwards, vec!["user_id"]);
+        assert_eq!(cart.added_config, vec!["priority: 2"]);
+
+        let newer = lower_one(
+            &BASE
+                .replace(
+                    "  Cart -> Pay { passes { cart_id } }\n",
+                    "  Cart -> Pay { passes { cart_id } }\n  Pay -> Cart max 5\n",
+                )
+                .replace(
+                    "node Cart {",
+                    "node Cart {\n    produces { cart_id }\n    forwards { user_id }\n    config { priority: 4 }",
+                ),
+        );
+        let diff = GraphDiff::between(&new, &newer);
+        assert_eq!(
+            diff.changed_bounds,
+            vec![BoundChange {
+                edge: "Pay -> Cart".into(),
+                old: Some("repeat 3".into()),
+                new: Some("max 5".into()),
+            }]
+        );
+        assert_eq!(diff.changed_nodes[0].removed_config, vec!["priority: 2"]);
+        assert_eq!(diff.changed_nodes[0].added_config, vec!["priority: 4"]);
+        let report = Diff {
+            graphs: vec![diff],
+            ..Diff::default()
+        }
+        .report();
+        assert!(
+            report.contains("  ~ edge Pay -> Cart repeat 3 -> max 5\n"),
+            "{report}"
+        );
+        assert!(report.contains("      + config priority: 4\n"), "{report}");
+    }
+
+    #[test]
+    fn plan_order_reports_moved_nodes_only() {
+        let names = |s: &str| -> Vec<String> { s.split(' ').map(str::to_owned).collect() };
+        let mut diff = GraphDiff::default();
+        // Inserting New shifts positions but moves nothing; B and C swapping does
+        diff.set_plan_order(&names("A B C D"), &names("A New C B D"));
+        assert_eq!(
+            diff.plan_order,
+            vec![OrderChange {
+                node: "B".into(),
+                old_position: 2,
+                new_position: 4,
+            }]
+        );
+    }
+
+    #[test]
+    fn plan_order_is_compared_for_every_graph() {
+        let shop = lower_one(BASE);
+        let old = [shop.clone()];
+        let new = [shop];
+        let mut diff = Diff::between(&old, &new);
+        diff.compare_plan_order(&old, &new, |g| {
+            let first = if std::ptr::eq(g, &old[0]) {
+                "Browse"
+            } else {
+                "Cart"
+            };
+            let second = if first == "Browse" { "Cart" } else { "Browse" };
+            Ok(vec![first.to_owned(), second.to_owned()])
+        });
+        assert_eq!(diff.graphs.len(), 1);
+        assert_eq!(diff.graphs[0].plan_order.len(), 1);
+
+        let mut diff = Diff::between(&old, &new);
+        diff.compare_plan_order(&old, &new, |_| Err("unbounded cycle".to_owned()));
+        assert!(diff.is_empty(), "an unchanged graph needs no note");
+    }
+
+    #[test]
+    fn graphs_are_matched_by_name() {
+        let shop = lower_one(BASE);
+        let other = lower_one("graph Other {}");
+        let diff = Diff::between(std::slice::from_ref(&shop), &[shop.clone(), other]);
+        assert_eq!(diff.added_graphs, vec!["Other"]);
+        assert!(diff.graphs.is_empty());
+        assert!(diff.report().starts_with("+ graph Other\n"));
+    }
+}
@@ -7,6 +7,7 @@ use crate::util::span::Span;
 pub struct IrFixture {
     pub name: String,
     pub fields: Vec<(String, String)>,
+    pub span: Span,
 }
 
 /// Lower AST fixtures into IR fixtures.
@@ -21,6 +22,7 @@ pub fn lower_fixtures(ast_fixtures: &[ast::Fixture]) -> Vec<IrFixture> {
                 .iter()
                 .map(|(k, v)| (k.clone(), format_fixture_value(v)))
                 .collect(),
+            span: f.span,
         })
         .collect()
 }
@@ -67,7 +69,7 @@ pub fn validate_fixtures(fixtures: &[IrFixture]) -> Result<(), ParseError> {
         if !seen.insert(&f.name) {
             return Err(ParseError {
                 message: format!("duplicate fixture name '{}'", f.name),
-                span: Span::default(),
+                span: f.span,
             });
         }
     }
@@ -185,6 +187,7 @@ mod tests {
                 ("role".into(), "admin".into()),
                 ("email".into(), "admin@example.com".into()),
             ],
+            span: Span::default(),
         };
         let mut data = Vec::new();
         apply_fixture(&mut data, &fixture);
@@ -201,6 +204,7 @@ mod tests {
                 ("role".into(), "admin".into()),
                 ("email".into(), "fixture@example.com".into()),
             ],
+            span: Span::default(),
         };
         let mut data = vec![("email".into(), "explicit@example.com".into())];
         apply_fixture(&mut data, &fixture);
@@ -218,10 +222,12 @@ mod tests {
             IrFixture {
                 name: "Admin".into(),
                 fields: vec![],
+                span: Span::default(),
             },
             IrFixture {
                 name: "Admin".into(),
                 fields: vec![],
+                span: Span::default(),
             },
         ];
         let result = validate_fixtures(&fixtures);
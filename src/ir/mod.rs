@@ -1,6 +1,9 @@
+pub mod diff;
 pub mod fixture;
 pub mod params;
 pub mod resolve;
+pub mod snapshot;
+pub mod tag;
 mod validate;
 
 use crate::parser::ast;
@@ -18,6 +21,8 @@ pub struct IrGraph {
     pub nodes: Vec<IrNode>,
     pub edges: Vec<IrEdge>,
     pub fixtures: Vec<fixture::IrFixture>,
+    /// Graph-level `config { ... }` entries, values formatted as strings.
+    pub config: Vec<(String, String)>,
     pub span: Span,
 }
 
@@ -29,6 +34,12 @@ pub struct IrNode {
     pub steps: Vec<IrStep>,
     pub tags: Vec<String>,
     pub requires: Vec<String>,
+    /// Fields this node declares it creates. Empty means undeclared.
+    pub produces: Vec<String>,
+    /// Fields this node passes on to its successors under explicit forwarding.
+    pub forwards: Vec<String>,
+    /// Node-level `config { ... }` entries, values formatted as strings.
+    pub config: Vec<(String, String)>,
     pub span: Span,
 }
 
@@ -41,6 +52,8 @@ pub struct IrEdge {
     pub to_index: usize,
     pub passes: Vec<String>,
     pub description: Option<String>,
+    /// Set on loop edges that close a bounded cycle.
+    pub bound: Option<ast::LoopBound>,
     pub span: Span,
 }
 
@@ -67,6 +80,18 @@ pub enum IrStepType {
     But,
 }
 
+impl std::fmt::Display for IrStepType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Given => "given",
+            Self::When => "when",
+            Self::Then => "then",
+            Self::And => "and",
+            Self::But => "but",
+        })
+    }
+}
+
 /// Lower an AST graph into a validated IR graph.
 ///
 /// # Errors
@@ -78,68 +103,98 @@ pub fn lower(ast_graph: &ast::Graph) -> Result<IrGraph, ParseError> {
     let fixtures = fixture::lower_fixtures(&ast_graph.fixtures);
     fixture::validate_fixtures(&fixtures)?;
 
+    let graph_tags: Vec<String> = ast_graph.tags.iter().map(|t| t.0.clone()).collect();
     let nodes: Vec<IrNode> = ast_graph
         .nodes
         .iter()
-        .map(|n| IrNode {
-            name: n.name.clone(),
-            description: n.description.clone(),
-            steps: n
-                .steps
-                .iter()
-                .map(|s| {
-                    let normalized = normalize(&s.text);
-
-                    // Start with explicit data block fields
-                    let mut data: Vec<(String, String)> = s
-                        .data
-                        .as_ref()
-                        .map(|d| {
-                            d.fields
-                                .iter()
-                                .map(|(k, v)| (k.clone(), format_value(v)))
-                                .collect()
-                        })
-                        .unwrap_or_default();
-
-                    // Merge in extracted data from prose (explicit fields take precedence)
-                    let extracted = extract_data(&s.text);
-                    for (key, val) in extracted.fields {
-                        if !data.iter().any(|(k, _)| *k == key) {
-                            data.push((key, val));
+        .map(|n| {
+            let node_config = lower_data_block(n.config.as_ref());
+            IrNode {
+                name: n.name.clone(),
+                description: n.description.clone(),
+                steps: n
+                    .steps
+                    .iter()
+                    .map(|s| {
+                        let normalized = normalize(&s.text);
+
+                        // Start with explicit data block fields
+                        let mut data: Vec<(String, String)> = s
+                            .data
+                            .as_ref()
+                            .map(|d| {
+                                d.fields
+                                    .iter()
+                                    .map(|(k, v)| (k.clone(), format_value(v)))
+                                    .collect()
+                            })
+                            .unwrap_or_default();
+
+                        // Merge in extracted data from prose (explicit fields take precedence)
+                        let extracted = extract_data(&s.text);
+                        for (key, val) in extracted.fields {
+                            if !data.iter().any(|(k, _)| *k == key) {
+                                data.push((key, val));
+                            }
                         }
-                    }
 
-                    // Apply fixture data if step references a fixture
-                    if let Some(fixture_name) = fixture::extract_fixture_ref(&s.text)
-                        && let Some(f) = fixture::resolve_fixture(&fixtures, &fixture_name)
-                    {
-                        fixture::apply_fixture(&mut data, f);
-                    }
+                        // Resolve parameters from the step's own data, then the
+                        // referenced fixture, then node-level config. Upstream edge
+                        // data is bound later by `params::bind_upstream_parameters`.
+                        let fixture = fixture::extract_fixture_ref(&s.text)
+                            .and_then(|name| fixture::resolve_fixture(&fixtures, &name));
+                        let mut sources = vec![params::DataSource {
+                            label: "step",
+                            source: params::BindingSource::StepData,
+                            data: &data,
+                        }];
+                        if let Some(f) = fixture {
+                            sources.push(params::DataSource {
+                                label: &f.name,
+                                source: params::BindingSource::Fixture(f.name.clone()),
+                                data: &f.fields,
+                            });
+                        }
+                        sources.push(params::DataSource {
+                            label: &n.name,
+                            source: params::BindingSource::NodeData(n.name.clone()),
+                            data: &node_config,
+                        });
+                        let parameters =
+                            params::resolve_parameters_with_sources(&s.fragments, &sources);
 
-                    // Resolve parameters from step fragments against available data
-                    let parameters = params::resolve_parameters(&s.fragments, &data);
-
-                    IrStep {
-                        step_type: match s.step_type {
-                            ast::StepType::Given => IrStepType::Given,
-                            ast::StepType::When => IrStepType::When,
-                            ast::StepType::Then => IrStepType::Then,
-                            ast::StepType::And => IrStepType::And,
-                            ast::StepType::But => IrStepType::But,
-                            #[allow(unreachable_patterns)]
-                            _ => IrStepType::Given,
-                        },
-                        text: s.text.clone(),
-                        normalized_text: normalized.normalized,
-                        data,
-                        parameters,
-                    }
-                })
-                .collect(),
-            tags: n.tags.iter().map(|t| t.0.clone()).collect(),
-            requires: n.requires.clone(),
-            span: n.span,
+                        // Apply fixture data if step references a fixture
+                        if let Some(f) = fixture {
+                            fixture::apply_fixture(&mut data, f);
+                        }
+
+                        IrStep {
+                            step_type: match s.step_type {
+                                ast::StepType::Given => IrStepType::Given,
+                                ast::StepType::When => IrStepType::When,
+                                ast::StepType::Then => IrStepType::Then,
+                                ast::StepType::And => IrStepType::And,
+                                ast::StepType::But => IrStepType::But,
+                                #[allow(unreachable_patterns)]
+                                _ => IrStepType::Given,
+                            },
+                            text: s.text.clone(),
+                            normalized_text: normalized.normalized,
+                            data,
+                            parameters,
+                        }
+                    })
+                    .collect(),
+                tags: tag::inherit_tags(
+                    &n.tags.iter().map(|t| t.0.clone()).collect::<Vec<_>>(),
+                    &graph_tags,
+                ),
+                requires: n.requires.clone(),
+                produces: n.produces.clone(),
+                forwards: n.forwards.clone(),
+                config: node_config,
+                span: n.span,
+            }
         })
         .collect();
 
@@ -176,6 +231,7 @@ pub fn lower(ast_graph: &ast::Graph) -> Result<IrGraph, ParseError> {
             to_index: to_idx,
             passes: e.passes.clone(),
             description: e.description.clone(),
+            bound: e.bound,
             span: e.span,
         });
     }
@@ -185,6 +241,7 @@ pub fn lower(ast_graph: &ast::Graph) -> Result<IrGraph, ParseError> {
         nodes,
         edges,
         fixtures,
+        config: lower_data_block(ast_graph.config.as_ref()),
         span: ast_graph.span,
     };
 
@@ -193,6 +250,17 @@ pub fn lower(ast_graph: &ast::Graph) -> Result<IrGraph, ParseError> {
     Ok(ir)
 }
 
+fn lower_data_block(block: Option<&ast::DataBlock>) -> Vec<(String, String)> {
+    block
+        .map(|b| {
+            b.fields
+                .iter()
+                .map(|(k, v)| (k.clone(), format_value(v)))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 fn format_value(v: &ast::Value) -> String {
     match v {
         ast::Value::String(s) => s.clone(),
@@ -235,6 +303,22 @@ mod tests {
         assert_eq!(ir.nodes[0].description.as_deref(), Some("Node A"));
     }
 
+    #[test]
+    fn nodes_inherit_graph_tags() {
+        let ir = lower_one(
+            r#"graph G {
+                tags [team/checkout, area: cart]
+                node A { tags [smoke] }
+                node B { tags [area: payments] }
+            }"#,
+        );
+        assert_eq!(
+            ir.nodes[0].tags,
+            vec!["smoke", "team/checkout", "area:cart"]
+        );
+        assert_eq!(ir.nodes[1].tags, vec!["area:payments", "team/checkout"]);
+    }
+
     #[test]
     fn ir_resolves_edge_node_references() {
         let ir = lower_one(
@@ -272,6 +356,8 @@ mod tests {
                 steps: vec![],
                 tags: vec![],
                 requires: vec![],
+                produces: vec![],
+                forwards: vec![],
                 config: None,
                 span: Span::default(),
             }],
@@ -280,8 +366,10 @@ mod tests {
                 to: "Unknown".into(),
                 passes: vec![],
                 description: None,
+                bound: None,
                 span: Span::default(),
             }],
+            tags: vec![],
             config: None,
             imports: vec![],
             fixtures: vec![],
@@ -333,6 +421,169 @@ mod tests {
         assert!(err.message.contains("unsatisfied"), "got: {}", err.message);
     }
 
+    #[test]
+    fn ir_accepts_requires_forwarded_across_hops() {
+        let ir = lower_one(
+            r#"graph G {
+                node Register {}
+                node Login {}
+                node Dashboard { requires { user_id } }
+                Register -> Login { passes { user_id } }
+                Login -> Dashboard { passes { token } }
+            }"#,
+        );
+        assert_eq!(ir.nodes[2].requires, vec!["user_id"]);
+    }
+
+    #[test]
+    fn ir_accepts_requires_missing_on_some_paths() {
+        let graphs = parse(
+            r#"graph G {
+                node Register {}
+                node Guest {}
+                node Dashboard { requires { user_id } }
+                Register -> Dashboard { passes { user_id } }
+                Guest -> Dashboard
+            }"#,
+        )
+        .expect("parse failed");
+        // Reported by the partial-requires lint rule instead
+        assert!(lower(&graphs[0]).is_ok());
+    }
+
+    #[test]
+    fn ir_explicit_dataflow_requires_forwards() {
+        let source = |forwards: &str| {
+            format!(
+                r#"graph G {{
+                    config {{ dataflow: explicit }}
+                    node Register {{}}
+                    node Login {{ {forwards} }}
+                    node Dashboard {{ requires {{ user_id }} }}
+                    Register -> Login {{ passes {{ user_id }} }}
+                    Login -> Dashboard
+                }}"#
+            )
+        };
+        let graphs = parse(&source("")).expect("parse failed");
+        let err = lower(&graphs[0]).unwrap_err();
+        assert!(err.message.contains("unsatisfied"), "got: {}", err.message);
+
+        let graphs = parse(&source("forwards { user_id }")).expect("parse failed");
+        assert!(lower(&graphs[0]).is_ok());
+    }
+
+    #[test]
+    fn ir_rejects_unknown_dataflow_mode() {
+        let graphs = parse(
+            r#"graph G {
+                config { dataflow: sideways }
+                node A {}
+                node B { requires { id } }
+                A -> B { passes { id } }
+            }"#,
+        )
+        .expect("parse failed");
+        let err = lower(&graphs[0]).unwrap_err();
+        assert!(
+            err.message.contains("unknown dataflow mode 'sideways'"),
+            "got: {}",
+            err.message
+        );
+    }
+
+    #[test]
+    fn ir_rejects_passes_not_produced_by_source() {
+        let graphs = parse(
+            r#"graph G {
+                node Register { produces { user_id } }
+                node Login {}
+                Register -> Login { passes { user_id, auth_token } }
+            }"#,
+        )
+        .expect("parse failed");
+        let err = lower(&graphs[0]).unwrap_err();
+        assert_eq!(
+            err.message,
+            "edge 'Register -> Login' passes 'auth_token', but 'Register' does not produce it (produces: user_id)"
+        );
+    }
+
+    #[test]
+    fn ir_allows_passing_produced_or_received_fields() {
+        let ir = lower_one(
+            r#"graph G {
+                node Register { produces { user_id } }
+                node Login { produces { auth_token } }
+                node Dashboard { requires { user_id, auth_token } }
+                Register -> Login { passes { user_id } }
+                Login -> Dashboard { passes { user_id, auth_token } }
+            }"#,
+        );
+        assert_eq!(ir.nodes[1].produces, vec!["auth_token"]);
+    }
+
+    #[test]
+    fn ir_does_not_check_nodes_without_produces() {
+        let ir = lower_one(
+            r#"graph G {
+                node A {}
+                node B {}
+                A -> B { passes { anything } }
+            }"#,
+        );
+        assert!(ir.nodes[0].produces.is_empty());
+    }
+
+    #[test]
+    fn ir_binds_parameters_step_then_fixture_then_node_config() {
+        let ir = lower_one(
+            r#"graph G {
+                fixture Admin {
+                    role: "admin"
+                    region: "us"
+                }
+                node A {
+                    config { region: "eu", plan: "gold" }
+                    given a user from fixture Admin with <role> in <region> on <plan> {
+                        role: "owner"
+                    }
+                }
+            }"#,
+        );
+        assert_eq!(
+            ir.nodes[0].config[1],
+            ("plan".to_owned(), "gold".to_owned())
+        );
+        let params = &ir.nodes[0].steps[0].parameters;
+        assert_eq!(params[0].value.as_deref(), Some("owner"));
+        assert_eq!(params[0].source, params::BindingSource::StepData);
+        assert_eq!(params[1].value.as_deref(), Some("us"));
+        assert_eq!(
+            params[1].source,
+            params::BindingSource::Fixture("Admin".into())
+        );
+        assert_eq!(params[2].value.as_deref(), Some("gold"));
+        assert_eq!(
+            params[2].source,
+            params::BindingSource::NodeData("A".into())
+        );
+    }
+
+    #[test]
+    fn ir_lowers_graph_config() {
+        let ir = lower_one(
+            r#"graph G {
+                config { dataflow: explicit }
+                node A {}
+            }"#,
+        );
+        assert_eq!(
+            ir.config,
+            vec![("dataflow".to_owned(), "explicit".to_owned())]
+        );
+    }
+
     #[test]
     fn ir_detects_duplicate_node_names() {
         // Parser already catches this, but test that IR layer also validates.
@@ -345,6 +596,8 @@ mod tests {
                     steps: vec![],
                     tags: vec![],
                     requires: vec![],
+                    produces: vec![],
+                    forwards: vec![],
                     config: None,
                     span: Span::default(),
                 },
@@ -354,11 +607,14 @@ mod tests {
                     steps: vec![],
                     tags: vec![],
                     requires: vec![],
+                    produces: vec![],
+                    forwards: vec![],
                     config: None,
                     span: Span::new(10, 20, 2, 1),
                 },
             ],
             edges: vec![],
+            tags: vec![],
             config: None,
             imports: vec![],
             fixtures: vec![],
@@ -369,6 +625,91 @@ mod tests {
         assert!(result.unwrap_err().message.contains("duplicate"));
     }
 
+    #[test]
+    fn ir_lowers_loop_bounds() {
+        let ir = lower_one(
+            r#"graph G {
+                node A {}
+                node B {}
+                A -> B
+                B -> A max 4 { describe "retry" }
+            }"#,
+        );
+        assert_eq!(ir.edges[0].bound, None);
+        assert_eq!(ir.edges[1].bound, Some(ast::LoopBound::Max(4)));
+    }
+
+    #[test]
+    fn ir_rejects_loop_bound_that_closes_no_cycle() {
+        let graphs = parse(
+            r#"graph G {
+                node A {}
+                node B {}
+                A -> B repeat 3
+            }"#,
+        )
+        .unwrap();
+        let err = lower(&graphs[0]).unwrap_err();
+        assert_eq!(
+            err.message,
+            "edge 'A -> B' has loop bound 'repeat 3' but closes no cycle"
+        );
+    }
+
+    #[test]
+    fn ir_rejects_nested_loops() {
+        let graphs = parse(
+            r#"graph G {
+                node Submit {}
+                node Poll {}
+                node Check {}
+                Submit -> Poll
+                Poll -> Check
+                Check -> Poll repeat 3
+                Check -> Submit max 2
+            }"#,
+        )
+        .unwrap();
+        let err = lower(&graphs[0]).unwrap_err();
+        assert_eq!(
+            err.message,
+            "edge 'Check -> Submit' has loop bound 'max 2' inside the loop closed by 'Check -> Poll'; nested loops are not supported"
+        );
+
+        // Separate cycles may each have their own bound
+        lower_one(
+            r#"graph G {
+                node A {}
+                node B {}
+                A -> A repeat 2
+                A -> B
+                B -> B max 3
+            }"#,
+        );
+    }
+
+    #[test]
+    fn ir_rejects_invalid_scheduling_config() {
+        let graphs = parse(
+            r#"graph G {
+                node A { config { estimated_duration: "soon" } }
+            }"#,
+        )
+        .unwrap();
+        let err = lower(&graphs[0]).unwrap_err();
+        assert_eq!(
+            err.message,
+            "node 'A' config: estimated_duration: invalid duration 'soon'"
+        );
+
+        let graphs = parse("graph G { node A { config { priority: \"high\" } } }").unwrap();
+        let err = lower(&graphs[0]).unwrap_err();
+        assert_eq!(
+            err.message,
+            "node 'A' config: priority: expected a number, got 'high'"
+        );
+    }
+
     #[test]
     fn ir_preserves_step_order() {
         let ir = lower_one(
@@ -1,21 +1,50 @@
+use std::collections::{HashSet, VecDeque};
+use std::fmt::Write;
+
+use petgraph::Direction;
+use petgraph::graph::NodeIndex;
+use petgraph::visit::EdgeRef;
+
+use crate::graph::builder::TestGraph;
+use crate::graph::dataflow::ForwardMode;
+use crate::ir::IrNode;
 use crate::parser::ast::StepFragment;
 
 /// The source from which a parameter binding was resolved.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum BindingSource {
+    /// Bound from the step's own data (inline block or extracted from prose).
+    StepData,
     /// Bound from a fixture's data.
     Fixture(String),
-    /// Bound from edge data (passes from a source node).
+    /// Bound from the node-level `config { ... }` block of the named node.
+    NodeData(String),
+    /// Bound from a field passed along an incoming edge, labelled `Origin.field`.
     EdgeData(String),
     /// No binding found — left for runtime resolution.
     Unresolved,
 }
 
+impl std::fmt::Display for BindingSource {
+    /// Plan-facing label: `step`, `fixture:Admin`, `node:Login`,
+    /// `edge:Register.email`, or `unresolved`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::StepData => f.write_str("step"),
+            Self::Fixture(name) => write!(f, "fixture:{name}"),
+            Self::NodeData(name) => write!(f, "node:{name}"),
+            Self::EdgeData(label) => write!(f, "edge:{label}"),
+            Self::Unresolved => f.write_str("unresolved"),
+        }
+    }
+}
+
 /// A resolved (or unresolved) parameter binding.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ParameterBinding {
     pub name: String,
-    /// `None` = unresolved at plan-time, `Some` = bound value.
+    /// `Some` = bound value; `None` = not known at plan time (unresolved, or
+    /// supplied by an upstream node at run time).
     pub value: Option<String>,
     pub source: BindingSource,
 }
@@ -38,7 +67,7 @@ pub fn resolve_parameters(
                     .map(|(_, v)| ParameterBinding {
                         name: name.clone(),
                         value: Some(v.clone()),
-                        source: BindingSource::EdgeData(String::new()),
+                        source: BindingSource::StepData,
                     })
                     .unwrap_or_else(|| ParameterBinding {
                         name: name.clone(),
@@ -91,9 +120,124 @@ pub fn resolve_parameters_with_sources(
         .collect()
 }
 
+/// Bind parameters left unresolved by lowering to fields passed along
+/// incoming edges.
+///
+/// For each unresolved `<name>`, walks backwards to the nearest edge that
+/// passes `name`, following nodes that forward it under `mode`. The binding is
+/// labelled `Origin.name`, where `Origin` is that edge's source. Its value is
+/// taken from the origin's config or step data when known at plan time, and
+/// is otherwise left for run time.
+pub fn bind_upstream_parameters(tg: &mut TestGraph, mode: ForwardMode) {
+    let mut updates = Vec::new();
+    for &idx in &tg.node_indices {
+        for (si, step) in tg.graph[idx].steps.iter().enumerate() {
+            for (pi, param) in step.parameters.iter().enumerate() {
+                if param.source != BindingSource::Unresolved {
+                    continue;
+                }
+                if let Some(origin) = find_origin(tg, idx, &param.name, mode) {
+                    let origin = &tg.graph[origin];
+                    let binding = ParameterBinding {
+                        name: param.name.clone(),
+                        value: plan_time_value(origin, &param.name),
+                        source: BindingSource::EdgeData(format!("{}.{}", origin.name, param.name)),
+                    };
+                    updates.push((idx, si, pi, binding));
+                }
+            }
+        }
+    }
+    for (idx, si, pi, binding) in updates {
+        tg.graph[idx].steps[si].parameters[pi] = binding;
+    }
+}
+
+/// The source of the nearest edge (breadth-first, in declaration order)
+/// that passes `field` into `node`.
+fn find_origin(
+    tg: &TestGraph,
+    node: NodeIndex,
+    field: &str,
+    mode: ForwardMode,
+) -> Option<NodeIndex> {
+    let g = &tg.graph;
+    let mut seen = HashSet::from([node]);
+    let mut queue = VecDeque::from([node]);
+    while let Some(current) = queue.pop_front() {
+        let mut incoming: Vec<_> = g.edges_directed(current, Direction::Incoming).collect();
+        incoming.sort_by_key(|e| e.id());
+        for edge in incoming {
+            let source = edge.source();
+            if edge.weight().passes.iter().any(|p| p == field) {
+                return Some(source);
+            }
+            if mode.forwards(&g[source], field) && seen.insert(source) {
+                queue.push_back(source);
+            }
+        }
+    }
+    None
+}
+
+/// A value for `field` declared on `node` itself, if any.
+fn plan_time_value(node: &IrNode, field: &str) -> Option<String> {
+    node.config
+        .iter()
+        .chain(node.steps.iter().flat_map(|s| &s.data))
+        .find(|(k, _)| k == field)
+        .map(|(_, v)| v.clone())
+}
+
+/// Substitute bound parameters into a step's text.
+///
+/// Each `<name>` with a plan-time value is replaced by that value. A parameter
+/// supplied by an upstream node at run time becomes `<name from Origin.field>`,
+/// and one with no binding becomes `<name: UNRESOLVED>`. Placeholders without a
+/// matching binding are left as written.
+pub fn render_step_text(text: &str, bindings: &[ParameterBinding]) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let binding = after.find('>').and_then(|end| {
+            let name = &after[..end];
+            bindings.iter().find(|b| b.name == name).map(|b| (b, end))
+        });
+        match binding {
+            Some((binding, end)) => {
+                match (&binding.value, &binding.source) {
+                    (Some(value), _) => out.push_str(value),
+                    (None, BindingSource::Unresolved) => {
+                        write!(out, "<{}: UNRESOLVED>", binding.name).unwrap();
+                    }
+                    (None, source) => {
+                        let origin = match source {
+                            BindingSource::EdgeData(label) => label.clone(),
+                            other => other.to_string(),
+                        };
+                        write!(out, "<{} from {origin}>", binding.name).unwrap();
+                    }
+                }
+                rest = &after[end + 1..];
+            }
+            None => {
+                out.push('<');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::graph::builder::build;
+    use crate::ir::lower;
+    use crate::parser::parse::parse;
 
     #[test]
     fn resolves_parameter_from_available_data() {
@@ -229,4 +373,128 @@ mod tests {
         assert_eq!(bindings[1].value, None);
         assert_eq!(bindings[1].source, BindingSource::Unresolved);
     }
+
+    fn bound(input: &str, mode: ForwardMode) -> TestGraph {
+        let ir = lower(&parse(input).expect("parse failed")[0]).expect("lower failed");
+        let mut tg = build(&ir);
+        bind_upstream_parameters(&mut tg, mode);
+        tg
+    }
+
+    fn param(tg: &TestGraph, node: &str) -> ParameterBinding {
+        let idx = tg
+            .node_indices
+            .iter()
+            .find(|&&i| tg.graph[i].name == node)
+            .unwrap();
+        tg.graph[*idx].steps[0].parameters[0].clone()
+    }
+
+    const CHAIN: &str = r#"graph G {
+        node Register {
+            given a user with email "a@b.com"
+        }
+        node Login {}
+        node Dashboard {
+            when the dashboard loads for <email>
+        }
+        Register -> Login { passes { email } }
+        Login -> Dashboard
+    }"#;
+
+    #[test]
+    fn binds_from_origin_across_hops_with_value() {
+        let tg = bound(CHAIN, ForwardMode::Transitive);
+        let binding = param(&tg, "Dashboard");
+        assert_eq!(
+            binding.source,
+            BindingSource::EdgeData("Register.email".into())
+        );
+        assert_eq!(binding.value, Some("a@b.com".into()));
+    }
+
+    #[test]
+    fn explicit_mode_stops_at_non_forwarding_node() {
+        let tg = bound(CHAIN, ForwardMode::Explicit);
+        assert_eq!(param(&tg, "Dashboard").source, BindingSource::Unresolved);
+    }
+
+    #[test]
+    fn runtime_value_has_no_plan_time_value() {
+        let tg = bound(
+            r#"graph G {
+                node Login {}
+                node Dashboard {
+                    then it shows <token>
+                }
+                Login -> Dashboard { passes { token } }
+            }"#,
+            ForwardMode::Transitive,
+        );
+        let binding = param(&tg, "Dashboard");
+        assert_eq!(
+            binding.source,
+            BindingSource::EdgeData("Login.token".into())
+        );
+        assert_eq!(binding.value, None);
+    }
+
+    #[test]
+    fn local_data_wins_over_upstream() {
+        let tg = bound(
+            r#"graph G {
+                node Login {}
+                node Dashboard {
+                    config { token: "local" }
+                    then it shows <token>
+                }
+                Login -> Dashboard { passes { token } }
+            }"#,
+            ForwardMode::Transitive,
+        );
+        let binding = param(&tg, "Dashboard");
+        assert_eq!(binding.source, BindingSource::NodeData("Dashboard".into()));
+        assert_eq!(binding.value, Some("local".into()));
+    }
+
+    #[test]
+    fn source_labels() {
+        assert_eq!(BindingSource::StepData.to_string(), "step");
+        assert_eq!(BindingSource::Fixture("A".into()).to_string(), "fixture:A");
+        assert_eq!(BindingSource::NodeData("N".into()).to_string(), "node:N");
+        assert_eq!(
+            BindingSource::EdgeData("N.f".into()).to_string(),
+            "edge:N.f"
+        );
+    }
+
+    fn binding(name: &str, value: Option<&str>, source: BindingSource) -> ParameterBinding {
+        ParameterBinding {
+            name: name.into(),
+            value: value.map(Into::into),
+            source,
+        }
+    }
+
+    #[test]
+    fn renders_bound_and_marks_missing_parameters() {
+        let bindings = vec![
+            binding("email", Some("a@b.com"), BindingSource::StepData),
+            binding("token", None, BindingSource::EdgeData("Login.token".into())),
+            binding("password", None, BindingSource::Unresolved),
+        ];
+        assert_eq!(
+            render_step_text("<email> signs in with <password> and <token>", &bindings),
+            "a@b.com signs in with <password: UNRESOLVED> and <token from Login.token>"
+        );
+    }
+
+    #[test]
+    fn render_leaves_unknown_placeholders_and_stray_brackets() {
+        let bindings = vec![binding("n", Some("<n>"), BindingSource::StepData)];
+        assert_eq!(
+            render_step_text("a < b and <n> <other> <n", &bindings),
+            "a < b and <n> <other> <n"
+        );
+    }
 }
@@ -282,6 +282,9 @@ mod tests {
                 steps: vec![],
                 tags: vec![],
                 requires: vec![],
+                produces: vec![],
+                forwards: vec![],
+                config: vec![],
                 span: Span::default(),
             }],
             edges: vec![crate::ir::IrEdge {
@@ -291,9 +294,11 @@ mod tests {
                 to_index: 0,
                 passes: vec!["auth_token".into()],
                 description: None,
+                bound: None,
                 span: Span::default(),
             }],
             fixtures: vec![],
+            config: vec![],
             span: Span::default(),
         };
 
@@ -320,9 +325,11 @@ mod tests {
                 to_index: 0,
                 passes: vec![],
                 description: None,
+                bound: None,
                 span: Span::default(),
             }],
             fixtures: vec![],
+            config: vec![],
             span: Span::default(),
         };
         let result = resolve_cross_graph_edges(&mut graph, &resolved);
@@ -350,9 +357,11 @@ mod tests {
                 to_index: 0,
                 passes: vec![],
                 description: None,
+                bound: None,
                 span: Span::default(),
             }],
             fixtures: vec![],
+            config: vec![],
             span: Span::default(),
         };
         let result = resolve_cross_graph_edges(&mut graph, &resolved);
@@ -378,6 +387,9 @@ mod tests {
                 steps: vec![],
                 tags: vec![],
                 requires: vec![],
+                produces: vec![],
+                forwards: vec![],
+                config: vec![],
                 span: Span::default(),
             }],
             edges: vec![crate::ir::IrEdge {
@@ -387,9 +399,11 @@ mod tests {
                 to_index: 0,
                 passes: vec!["token".into()],
                 description: None,
+                bound: None,
                 span: Span::default(),
             }],
             fixtures: vec![],
+            config: vec![],
             span: Span::default(),
         };
 
@@ -416,6 +430,9 @@ mod tests {
                     steps: vec![],
                     tags: vec![],
                     requires: vec![],
+                    produces: vec![],
+                    forwards: vec![],
+                    config: vec![],
                     span: Span::default(),
                 },
                 crate::ir::IrNode {
@@ -424,6 +441,9 @@ mod tests {
                     steps: vec![],
                     tags: vec![],
                     requires: vec![],
+                    produces: vec![],
+                    forwards: vec![],
+                    config: vec![],
                     span: Span::default(),
                 },
             ],
@@ -435,6 +455,7 @@ mod tests {
                     to_index: 1,
                     passes: vec![],
                     description: None,
+                    bound: None,
                     span: Span::default(),
                 },
                 crate::ir::IrEdge {
@@ -444,10 +465,12 @@ mod tests {
                     to_index: 0,
                     passes: vec![],
                     description: None,
+                    bound: None,
                     span: Span::default(),
                 },
             ],
             fixtures: vec![],
+            config: vec![],
             span: Span::default(),
         };
 
@@ -0,0 +1,425 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ir::fixture::extract_fixture_ref;
+use crate::ir::params::ParameterBinding;
+use crate::ir::{IrEdge, IrGraph, IrNode, IrStep};
+
+/// Snapshot format version, bumped when fingerprints are computed differently.
+pub const SNAPSHOT_VERSION: u32 = 2;
+
+/// Content fingerprints of a set of graphs, saved to detect later changes.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub version: u32,
+    pub graphs: Vec<GraphSnapshot>,
+}
+
+/// Fingerprints of the nodes, edges and fixtures of one graph.
+///
+/// Fingerprints ignore source positions, so moving a node around in its file
+/// does not count as a change.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GraphSnapshot {
+    pub name: String,
+    /// Node name to fingerprint.
+    pub nodes: BTreeMap<String, String>,
+    /// `From -> To` to a fingerprint of every edge between the two nodes.
+    pub edges: BTreeMap<String, String>,
+    /// Fixture name to fingerprint.
+    pub fixtures: BTreeMap<String, String>,
+}
+
+impl Snapshot {
+    /// Snapshot of the given graphs.
+    pub fn of(graphs: &[IrGraph]) -> Self {
+        Self {
+            version: SNAPSHOT_VERSION,
+            graphs: graphs.iter().map(GraphSnapshot::of).collect(),
+        }
+    }
+
+    /// Parse a snapshot saved as YAML.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input is not a snapshot, or was written by a
+    /// different snapshot format version.
+    pub fn from_yaml(input: &str) -> Result<Self, String> {
+        let snapshot: Self =
+            serde_yaml::from_str(input).map_err(|e| format!("invalid snapshot: {e}"))?;
+        if snapshot.version != SNAPSHOT_VERSION {
+            return Err(format!(
+                "snapshot has version {}, expected {SNAPSHOT_VERSION} (take a new snapshot)",
+                snapshot.version
+            ));
+        }
+        Ok(snapshot)
+    }
+
+    /// Serialize the snapshot as YAML.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if YAML serialization fails.
+    pub fn to_yaml(&self) -> Result<String, String> {
+        serde_yaml::to_string(self).map_err(|e| format!("yaml serialization failed: {e}"))
+    }
+
+    /// The snapshot of the graph called `name`, if it was recorded.
+    pub fn graph(&self, name: &str) -> Option<&GraphSnapshot> {
+        self.graphs.iter().find(|g| g.name == name)
+    }
+}
+
+impl GraphSnapshot {
+    /// Snapshot of a single graph.
+    pub fn of(ir: &IrGraph) -> Self {
+        let mut edges: BTreeMap<String, Vec<&IrEdge>> = BTreeMap::new();
+        for edge in &ir.edges {
+            edges.entry(edge_key(edge)).or_default().push(edge);
+        }
+        Self {
+            name: ir.name.clone(),
+            nodes: ir
+                .nodes
+                .iter()
+                .map(|n| (n.name.clone(), node_fingerprint(n)))
+                .collect(),
+            edges: edges
+                .into_iter()
+                .map(|(key, parallel)| (key, edges_fingerprint(&parallel)))
+                .collect(),
+            fixtures: ir
+                .fixtures
+                .iter()
+                .map(|f| {
+                    (
+                        f.name.clone(),
+                        Content::default().pairs(&f.fields).fingerprint(),
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+/// What changed in a graph since a snapshot was taken.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChangeSet {
+    /// Nodes that are new or whose content changed.
+    pub nodes: Vec<String>,
+    pub removed_nodes: Vec<String>,
+    /// `From -> To` pairs whose edges are new or changed.
+    pub edges: Vec<String>,
+    pub removed_edges: Vec<String>,
+    /// Fixtures that are new, changed or removed.
+    pub fixtures: Vec<String>,
+}
+
+impl ChangeSet {
+    /// Compare the current state of a graph against its snapshot.
+    pub fn between(old: &GraphSnapshot, ir: &IrGraph) -> Self {
+        let new = GraphSnapshot::of(ir);
+        let (nodes, removed_nodes) = changed_keys(&old.nodes, &new.nodes);
+        let (edges, removed_edges) = changed_keys(&old.edges, &new.edges);
+        let (mut fixtures, removed_fixtures) = changed_keys(&old.fixtures, &new.fixtures);
+        fixtures.extend(removed_fixtures);
+        Self {
+            nodes,
+            removed_nodes,
+            edges,
+            removed_edges,
+            fixtures,
+        }
+    }
+
+    /// Whether nothing changed.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+            && self.removed_nodes.is_empty()
+            && self.edges.is_empty()
+            && self.removed_edges.is_empty()
+            && self.fixtures.is_empty()
+    }
+
+    /// Names of the current nodes directly touched by the changes: changed
+    /// nodes, targets of changed or removed edges, and nodes whose steps use a
+    /// changed fixture. In declaration order.
+    pub fn touched_nodes(&self, ir: &IrGraph) -> Vec<String> {
+        let edge_targets: Vec<&str> = self
+            .edges
+            .iter()
+            .chain(&self.removed_edges)
+            .filter_map(|key| key.split_once(" -> ").map(|(_, to)| to))
+            .collect();
+        ir.nodes
+            .iter()
+            .filter(|node| {
+                self.nodes.contains(&node.name)
+                    || edge_targets.contains(&node.name.as_str())
+                    || node.steps.iter().any(|step| {
+                        extract_fixture_ref(&step.text).is_some_and(|f| self.fixtures.contains(&f))
+                    })
+            })
+            .map(|node| node.name.clone())
+            .collect()
+    }
+}
+
+/// Keys that are new or have a different value in `new`, and keys missing from it.
+fn changed_keys(
+    old: &BTreeMap<String, String>,
+    new: &BTreeMap<String, String>,
+) -> (Vec<String>, Vec<String>) {
+    let changed = new
+        .iter()
+        .filter(|(key, value)| old.get(*key) != Some(value))
+        .map(|(key, _)| key.clone())
+        .collect();
+    let removed = old
+        .keys()
+        .filter(|key| !new.contains_key(*key))
+        .cloned()
+        .collect();
+    (changed, removed)
+}
+
+fn edge_key(edge: &IrEdge) -> String {
+    format!("{} -> {}", edge.from, edge.to)
+}
+
+/// Fingerprint of everything a node declares except its source position.
+/// Normalized step text is left out, since it follows from the text. The
+/// structs are destructured so that a new field has to be placed here.
+fn node_fingerprint(node: &IrNode) -> String {
+    let IrNode {
+        name,
+        description,
+        steps,
+        tags,
+        requires,
+        produces,
+        forwards,
+        config,
+        span: _,
+    } = node;
+    let mut content = Content::default();
+    content
+        .field(name)
+        .optional(description.as_deref())
+        .count(steps.len());
+    for step in steps {
+        let IrStep {
+            step_type,
+            text,
+            normalized_text: _,
+            data,
+            parameters,
+        } = step;
+        content
+            .field(&step_type.to_string())
+            .field(text)
+            .pairs(data)
+            .count(parameters.len());
+        for ParameterBinding {
+            name,
+            value,
+            source,
+        } in parameters
+        {
+            content
+                .field(name)
+                .optional(value.as_deref())
+                .field(&source.to_string());
+        }
+    }
+    content
+        .list(tags)
+        .list(requires)
+        .list(produces)
+        .list(forwards)
+        .pairs(config)
+        .fingerprint()
+}
+
+/// Fingerprint of the parallel edges between two nodes, in declaration order.
+/// Endpoint names are already in the key, and endpoint indices are left out,
+/// since they shift when nodes are added.
+fn edges_fingerprint(edges: &[&IrEdge]) -> String {
+    let mut content = Content::default();
+    content.count(edges.len());
+    for IrEdge {
+        from: _,
+        to: _,
+        from_index: _,
+        to_index: _,
+        passes,
+        description,
+        bound,
+        span: _,
+    } in edges
+    {
+        content
+            .list(passes)
+            .optional(description.as_deref())
+            .optional(bound.map(|b| b.to_string()).as_deref());
+    }
+    content.fingerprint()
+}
+
+/// The fields a fingerprint covers, written in a fixed order. Every value
+/// is prefixed with its length and every list with its size, so different
+/// fields never write the same content.
+#[derive(Default)]
+struct Content(String);
+
+impl Content {
+    fn field(&mut self, value: &str) -> &mut Self {
+        write!(self.0, "{}:{value}", value.len()).unwrap();
+        self
+    }
+
+    fn count(&mut self, len: usize) -> &mut Self {
+        write!(self.0, "#{len}").unwrap();
+        self
+    }
+
+    fn optional(&mut self, value: Option<&str>) -> &mut Self {
+        match value {
+            Some(value) => self.field(value),
+            None => {
+                self.0.push('-');
+                self
+            }
+        }
+    }
+
+    fn list(&mut self, values: &[String]) -> &mut Self {
+        self.count(values.len());
+        for value in values {
+            self.field(value);
+        }
+        self
+    }
+
+    fn pairs(&mut self, pairs: &[(String, String)]) -> &mut Self {
+        self.count(pairs.len());
+        for (key, value) in pairs {
+            self.field(key).field(value);
+        }
+        self
+    }
+
+    /// 64-bit FNV-1a hash of the content, as hex.
+    fn fingerprint(&self) -> String {
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        for byte in self.0.bytes() {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+        format!("{hash:016x}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::lower;
+    use crate::parser::parse::parse;
+
+    fn lower_one(input: &str) -> IrGraph {
+        lower(&parse(input).expect("parse failed")[0]).expect("lower failed")
+    }
+
+    const BASE: &str = r#"graph Shop {
+  fixture Buyer { email: "a@b.com" }
+  node Browse {
+    given a visitor
+  }
+  node Cart {
+    given a buyer from fixture Buyer
+  }
+  node Pay {
+    then the order is paid
+  }
+  Browse -> Cart
+  Cart -> Pay { passes { cart_id } }
+}"#;
+
+    #[test]
+    fn unchanged_graph_has_no_changes() {
+        let ir = lower_one(BASE);
+        let snapshot = Snapshot::of(std::slice::from_ref(&ir));
+        let yaml = snapshot.to_yaml().unwrap();
+        let loaded = Snapshot::from_yaml(&yaml).unwrap();
+        assert_eq!(loaded, snapshot);
+        let changes = ChangeSet::between(loaded.graph("Shop").unwrap(), &ir);
+        assert!(changes.is_empty(), "{changes:?}");
+    }
+
+    #[test]
+    fn moving_a_node_is_not_a_change() {
+        let old = GraphSnapshot::of(&lower_one(BASE));
+        let moved = BASE.replace("graph Shop {\n", "graph Shop {\n\n\n");
+        assert!(ChangeSet::between(&old, &lower_one(&moved)).is_empty());
+    }
+
+    #[test]
+    fn detects_changed_nodes_edges_and_fixtures() {
+        let old = GraphSnapshot::of(&lower_one(BASE));
+        let edited = BASE
+            .replace("the order is paid", "the order is settled")
+            .replace("passes { cart_id }", "passes { cart_id, total }")
+            .replace("\"a@b.com\"", "\"c@d.com\"");
+        let ir = lower_one(&edited);
+        let changes = ChangeSet::between(&old, &ir);
+        // Fixture data is copied into Cart's steps, so Cart changes too
+        assert_eq!(changes.nodes, vec!["Cart", "Pay"]);
+        assert_eq!(changes.edges, vec!["Cart -> Pay"]);
+        assert_eq!(changes.fixtures, vec!["Buyer"]);
+        assert_eq!(changes.touched_nodes(&ir), vec!["Cart", "Pay"]);
+    }
+
+    #[test]
+    fn removed_edges_touch_their_target() {
+        let old = GraphSnapshot::of(&lower_one(BASE));
+        let ir = lower_one(&BASE.replace("  Browse -> Cart\n", ""));
+        let changes = ChangeSet::between(&old, &ir);
+        assert_eq!(changes.removed_edges, vec!["Browse -> Cart"]);
+        assert_eq!(changes.touched_nodes(&ir), vec!["Cart"]);
+    }
+
+    #[test]
+    fn fingerprints_are_stable() {
+        // Changing these values changes every saved snapshot; bump
+        // SNAPSHOT_VERSION along with them
+        let snapshot = GraphSnapshot::of(&lower_one(BASE));
+        assert_eq!(snapshot.nodes["Cart"], "c025de876872e71a");
+        assert_eq!(snapshot.edges["Cart -> Pay"], "eb9ef5bcb5949578");
+        assert_eq!(snapshot.fixtures["Buyer"], "7bfc4f7fee2087bb");
+    }
+
+    #[test]
+    fn fields_do_not_run_together() {
+        let mut joined = Content::default();
+        joined.list(&["ab".to_owned()]);
+        let mut split = Content::default();
+        split.list(&["a".to_owned(), "b".to_owned()]);
+        assert_ne!(joined.fingerprint(), split.fingerprint());
+        let mut absent = Content::default();
+        absent.optional(None);
+        let mut empty = Content::default();
+        empty.optional(Some(""));
+        assert_ne!(absent.fingerprint(), empty.fingerprint());
+    }
+
+    #[test]
+    fn rejects_other_snapshot_versions() {
+        let err = Snapshot::from_yaml("version: 99\ngraphs: []\n").unwrap_err();
+        assert!(err.contains("version 99"), "got: {err}");
+        assert!(Snapshot::from_yaml("nodes: 3").is_err());
+    }
+}
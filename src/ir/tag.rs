@@ -0,0 +1,98 @@
+use crate::util::glob::glob_match;
+
+/// Split a tag into its key and value: `area:checkout` has key `area` and
+/// value `checkout`. Plain and namespaced tags have no key.
+pub fn split_tag(tag: &str) -> (Option<&str>, &str) {
+    match tag.split_once(':') {
+        Some((key, value)) => (Some(key), value),
+        None => (None, tag),
+    }
+}
+
+/// The value of the first `key:value` tag with the given key.
+pub fn tag_value<'a>(tags: &'a [String], key: &str) -> Option<&'a str> {
+    tags.iter().find_map(|tag| match split_tag(tag) {
+        (Some(k), value) if k == key => Some(value),
+        _ => None,
+    })
+}
+
+/// Whether `tag` matches the glob `pattern`.
+///
+/// A pattern matching a namespace also matches everything below it, so
+/// `team/auth` matches `team/auth/login`. A pattern matching the key of a
+/// key-value tag matches the tag whatever its value, so `area` matches
+/// `area:checkout`.
+pub fn tag_matches(tag: &str, pattern: &str) -> bool {
+    if let (Some(key), _) = split_tag(tag) {
+        return glob_match(pattern, tag) || glob_match(pattern, key);
+    }
+    tag.match_indices('/')
+        .map(|(i, _)| &tag[..i])
+        .chain([tag])
+        .any(|namespace| glob_match(pattern, namespace))
+}
+
+/// A node's tags followed by the graph tags it inherits.
+///
+/// Graph tags the node already has are skipped, and so are graph key-value
+/// tags whose key the node sets itself.
+pub fn inherit_tags(node_tags: &[String], graph_tags: &[String]) -> Vec<String> {
+    let mut tags = node_tags.to_vec();
+    for tag in graph_tags {
+        let overridden = match split_tag(tag) {
+            (Some(key), _) => tag_value(node_tags, key).is_some(),
+            (None, _) => node_tags.contains(tag),
+        };
+        if !overridden {
+            tags.push(tag.clone());
+        }
+    }
+    tags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(tags: &[&str]) -> Vec<String> {
+        tags.iter().map(|&t| t.to_owned()).collect()
+    }
+
+    #[test]
+    fn splits_key_value_tags() {
+        assert_eq!(split_tag("area:checkout"), (Some("area"), "checkout"));
+        assert_eq!(split_tag("team/auth"), (None, "team/auth"));
+        let tags = strings(&["smoke", "priority:2"]);
+        assert_eq!(tag_value(&tags, "priority"), Some("2"));
+        assert_eq!(tag_value(&tags, "area"), None);
+    }
+
+    #[test]
+    fn namespaces_match_their_children() {
+        assert!(tag_matches("team/auth/login", "team"));
+        assert!(tag_matches("team/auth/login", "team/auth"));
+        assert!(tag_matches("team/auth/login", "team/auth/login"));
+        assert!(tag_matches("team/auth/login", "team/*/login"));
+        assert!(!tag_matches("team/auth/login", "team/au"));
+        assert!(!tag_matches("team/auth", "team/auth/login"));
+    }
+
+    #[test]
+    fn keys_match_any_value() {
+        assert!(tag_matches("area:checkout", "area"));
+        assert!(tag_matches("area:checkout", "area:checkout"));
+        assert!(!tag_matches("area:checkout", "checkout"));
+        assert!(!tag_matches("area:checkout", "area:search"));
+    }
+
+    #[test]
+    fn node_tags_override_inherited_ones() {
+        let graph = strings(&["smoke", "area:checkout", "owner:qa"]);
+        let node = strings(&["area:payments", "smoke"]);
+        assert_eq!(
+            inherit_tags(&node, &graph),
+            strings(&["area:payments", "smoke", "owner:qa"])
+        );
+    }
+}
@@ -1,4 +1,9 @@
-use crate::ir::IrGraph;
+use petgraph::algo::{has_path_connecting, kosaraju_scc};
+
+use crate::graph::builder::{TestGraph, build};
+use crate::graph::dataflow::{DataFlow, ForwardMode, analyze};
+use crate::graph::schedule::NodeWeight;
+use crate::ir::{IrGraph, IrNode};
 use crate::parser::error::ParseError;
 
 /// Validate semantic correctness of an IR graph.
@@ -7,10 +12,34 @@ use crate::parser::error::ParseError;
 ///
 /// Returns a [`ParseError`] if validation fails:
 /// - Duplicate node names
-/// - Unsatisfied `requires` fields
+/// - Loop bounds on edges that close no cycle, or that share a cycle with
+///   another loop bound (nested loops)
+/// - Unknown `dataflow` mode in the graph config
+/// - Node `priority` or `estimated_duration` config entries that do not parse
+/// - `requires` fields that no path into the node passes (see
+///   [`crate::graph::dataflow`])
+/// - Edges passing a field their source neither `produces` nor receives
 pub fn validate_graph(ir: &IrGraph) -> Result<(), ParseError> {
     check_duplicate_nodes(ir)?;
-    check_requires_satisfied(ir)?;
+    check_loop_bounds(ir)?;
+    check_node_weights(ir)?;
+
+    let mode = ForwardMode::from_config(&ir.config).map_err(|e| ParseError {
+        message: format!("graph config: {e}"),
+        span: ir.span,
+    })?;
+    if ir
+        .nodes
+        .iter()
+        .all(|n| n.requires.is_empty() && n.produces.is_empty())
+    {
+        return Ok(());
+    }
+    let tg = build(&with_external_nodes(ir));
+    let flow = analyze(&tg, mode);
+
+    check_requires_satisfied(ir, &tg, &flow)?;
+    check_passes_produced(ir, &tg, &flow)?;
     Ok(())
 }
 
@@ -27,33 +56,154 @@ fn check_duplicate_nodes(ir: &IrGraph) -> Result<(), ParseError> {
     Ok(())
 }
 
-fn check_requires_satisfied(ir: &IrGraph) -> Result<(), ParseError> {
-    for (i, node) in ir.nodes.iter().enumerate() {
-        if node.requires.is_empty() {
+fn check_node_weights(ir: &IrGraph) -> Result<(), ParseError> {
+    for node in &ir.nodes {
+        NodeWeight::of(node).map_err(|e| ParseError {
+            message: format!("node '{}' config: {e}", node.name),
+            span: node.span,
+        })?;
+    }
+    Ok(())
+}
+
+/// A `repeat`/`max` bound only makes sense on an edge whose target leads back
+/// to its source. Each cycle can carry one bound: with two, one loop would
+/// run inside the other, and plans cannot unroll nested loops.
+fn check_loop_bounds(ir: &IrGraph) -> Result<(), ParseError> {
+    if ir.edges.iter().all(|e| e.bound.is_none()) {
+        return Ok(());
+    }
+    let tg = build(&with_external_nodes(ir));
+    for edge in &ir.edges {
+        let Some(bound) = edge.bound else {
             continue;
+        };
+        let from = tg.node_indices[edge.from_index];
+        let to = tg.node_indices[edge.to_index];
+        if !has_path_connecting(&tg.graph, to, from, None) {
+            return Err(ParseError {
+                message: format!(
+                    "edge '{} -> {}' has loop bound '{}' but closes no cycle",
+                    edge.from, edge.to, bound
+                ),
+                span: edge.span,
+            });
+        }
+    }
+    for component in kosaraju_scc(&tg.graph) {
+        let mut bounded = ir.edges.iter().filter(|e| {
+            e.bound.is_some()
+                && component.contains(&tg.node_indices[e.from_index])
+                && component.contains(&tg.node_indices[e.to_index])
+        });
+        if let (Some(outer), Some(inner)) = (bounded.next(), bounded.next()) {
+            return Err(ParseError {
+                message: format!(
+                    "edge '{} -> {}' has loop bound '{}' inside the loop closed by '{} -> {}'; nested loops are not supported",
+                    inner.from,
+                    inner.to,
+                    inner.bound.unwrap(),
+                    outer.from,
+                    outer.to
+                ),
+                span: inner.span,
+            });
         }
+    }
+    Ok(())
+}
 
-        // Collect all fields passed to this node via incoming edges
-        let mut available: std::collections::HashSet<&str> = std::collections::HashSet::new();
-        for edge in &ir.edges {
-            if edge.to_index == i {
-                for field in &edge.passes {
-                    available.insert(field.as_str());
-                }
-            }
+/// A required field must reach its node along at least one path. Fields that
+/// arrive on some paths but not all are left to the `partial-requires` lint
+/// rule.
+fn check_requires_satisfied(
+    ir: &IrGraph,
+    tg: &TestGraph,
+    flow: &DataFlow,
+) -> Result<(), ParseError> {
+    for (i, node) in ir.nodes.iter().enumerate() {
+        let available = flow.at(tg.node_indices[i]);
+        if let Some(req) = node.requires.iter().find(|r| !available.may.contains(*r)) {
+            return Err(ParseError {
+                message: format!(
+                    "node '{}' has unsatisfied requires field '{}': no path into it passes it",
+                    node.name, req
+                ),
+                span: node.span,
+            });
         }
+    }
+    Ok(())
+}
 
-        for req in &node.requires {
-            if !available.contains(req.as_str()) {
-                return Err(ParseError {
-                    message: format!(
-                        "node '{}' has unsatisfied requires field '{}': no incoming edge passes it",
-                        node.name, req
-                    ),
-                    span: node.span,
-                });
-            }
+/// A node that declares `produces` may only pass on fields it produces or
+/// receives from upstream. Nodes without a `produces` clause are unchecked.
+fn check_passes_produced(ir: &IrGraph, tg: &TestGraph, flow: &DataFlow) -> Result<(), ParseError> {
+    for edge in &ir.edges {
+        if edge.from.contains('.') {
+            continue;
+        }
+        let source = &ir.nodes[edge.from_index];
+        if source.produces.is_empty() {
+            continue;
+        }
+        let received = &flow.at(tg.node_indices[edge.from_index]).may;
+        if let Some(field) = edge
+            .passes
+            .iter()
+            .find(|f| !source.produces.contains(f) && !received.contains(*f))
+        {
+            return Err(ParseError {
+                message: format!(
+                    "edge '{} -> {}' passes '{}', but '{}' does not produce it (produces: {})",
+                    edge.from,
+                    edge.to,
+                    field,
+                    source.name,
+                    source.produces.join(", ")
+                ),
+                span: edge.span,
+            });
         }
     }
     Ok(())
 }
+
+/// Copy of `ir` in which each dotted cross-graph endpoint (e.g. `Auth.Login`)
+/// becomes an empty stand-in node, since imports are not resolved yet.
+fn with_external_nodes(ir: &IrGraph) -> IrGraph {
+    let mut graph = ir.clone();
+    for e in 0..graph.edges.len() {
+        for from_side in [true, false] {
+            let edge = &graph.edges[e];
+            let name = if from_side { &edge.from } else { &edge.to };
+            if !name.contains('.') {
+                continue;
+            }
+            let index = match graph.nodes.iter().position(|n| n.name == *name) {
+                Some(index) => index,
+                None => {
+                    graph.nodes.push(IrNode {
+                        name: name.clone(),
+                        description: None,
+                        steps: Vec::new(),
+                        tags: Vec::new(),
+                        requires: Vec::new(),
+                        produces: Vec::new(),
+                        forwards: Vec::new(),
+                        config: Vec::new(),
+                        span: edge.span,
+                    });
+                    graph.nodes.len() - 1
+                }
+            };
+            let edge = &mut graph.edges[e];
+            if from_side {
+                edge.from_index = index;
+            } else {
+                edge.to_index = index;
+            }
+        }
+    }
+    graph
+}
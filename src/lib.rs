@@ -2,6 +2,7 @@ pub mod cli;
 pub mod emit;
 pub mod graph;
 pub mod ir;
+pub mod lint;
 pub mod parser;
 pub mod plan;
 pub mod util;
@@ -0,0 +1,253 @@
+pub mod rules;
+mod suppress;
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::graph::builder::{TestGraph, build};
+use crate::ir::IrGraph;
+use crate::util::span::Span;
+
+pub use rules::RULES;
+
+/// How seriously a lint finding is treated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The rule is disabled.
+    Off,
+    Info,
+    Warning,
+    /// Always fails `tast lint`.
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Off => "off",
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        })
+    }
+}
+
+impl FromStr for Severity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "off" => Ok(Self::Off),
+            "info" => Ok(Self::Info),
+            "warning" => Ok(Self::Warning),
+            "error" => Ok(Self::Error),
+            other => Err(format!(
+                "unknown severity '{other}' (expected: off, info, warning, error)"
+            )),
+        }
+    }
+}
+
+/// A lint rule in the registry.
+pub struct Rule {
+    /// Stable identifier used in config and suppression comments.
+    pub id: &'static str,
+    /// One-line description of what the rule checks.
+    pub summary: &'static str,
+    pub default_severity: Severity,
+    check: fn(&LintContext) -> Vec<Finding>,
+}
+
+/// Everything a rule may inspect.
+pub struct LintContext<'a> {
+    pub ir: &'a IrGraph,
+    pub graph: TestGraph,
+}
+
+/// A single problem reported by a rule, before severity is applied.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    pub message: String,
+    pub span: Span,
+}
+
+/// A finding with its rule ID and effective severity.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub rule: &'static str,
+    pub severity: Severity,
+    pub message: String,
+    pub span: Span,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {}[{}]: {}",
+            self.span.line, self.span.col, self.severity, self.rule, self.message
+        )
+    }
+}
+
+/// Look up a rule by its ID.
+pub fn find_rule(id: &str) -> Option<&'static Rule> {
+    RULES.iter().find(|r| r.id == id)
+}
+
+/// Per-project severity overrides, keyed by rule ID (the `[lint]` config table).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LintSettings {
+    overrides: BTreeMap<String, Severity>,
+}
+
+impl LintSettings {
+    /// Build settings from a rule-ID → severity table.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the first unknown rule ID.
+    pub fn new(overrides: &BTreeMap<String, Severity>) -> Result<Self, String> {
+        if let Some(unknown) = overrides.keys().find(|id| find_rule(id).is_none()) {
+            return Err(format!("unknown lint rule '{unknown}'"));
+        }
+        Ok(Self {
+            overrides: overrides.clone(),
+        })
+    }
+
+    /// The severity in effect for `rule`.
+    pub fn severity(&self, rule: &Rule) -> Severity {
+        self.overrides
+            .get(rule.id)
+            .copied()
+            .unwrap_or(rule.default_severity)
+    }
+}
+
+/// Run every enabled rule against `ir`, dropping findings suppressed by
+/// `# tast-ignore:` comments in `source`. Results are ordered by position.
+pub fn lint_graph(ir: &IrGraph, source: &str, settings: &LintSettings) -> Vec<Diagnostic> {
+    let ctx = LintContext {
+        ir,
+        graph: build(ir),
+    };
+    let suppressions = suppress::Suppressions::scan(source);
+
+    let mut diagnostics: Vec<Diagnostic> = RULES
+        .iter()
+        .filter_map(|rule| {
+            let severity = settings.severity(rule);
+            (severity != Severity::Off).then_some((rule, severity))
+        })
+        .flat_map(|(rule, severity)| {
+            (rule.check)(&ctx)
+                .into_iter()
+                .filter(|f| !suppressions.is_suppressed(rule.id, f.span.line))
+                .map(move |f| Diagnostic {
+                    rule: rule.id,
+                    severity,
+                    message: f.message,
+                    span: f.span,
+                })
+        })
+        .collect();
+    diagnostics.sort_by_key(|d| (d.span.line, d.span.col, d.rule));
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::lower;
+    use crate::parser::parse::parse;
+
+    fn lint_src(source: &str, settings: &LintSettings) -> Vec<Diagnostic> {
+        let graphs = parse(source).expect("parse failed");
+        let ir = lower(&graphs[0]).expect("lower failed");
+        lint_graph(&ir, source, settings)
+    }
+
+    const ORPHAN: &str = r#"graph G {
+  node A {
+    when something happens
+    then it works
+  }
+  node B {
+    when something happens
+    then it works
+  }
+  node Lonely {
+    when something happens
+    then it works
+  }
+  A -> B
+}"#;
+
+    #[test]
+    fn rule_ids_are_unique() {
+        let mut ids: Vec<&str> = RULES.iter().map(|r| r.id).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(ids.len(), RULES.len());
+    }
+
+    #[test]
+    fn reports_with_default_severity() {
+        let diags = lint_src(ORPHAN, &LintSettings::default());
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].rule, "orphan-node");
+        assert_eq!(diags[0].severity, Severity::Warning);
+        assert_eq!(diags[0].span.line, 10);
+    }
+
+    #[test]
+    fn override_changes_severity_or_disables() {
+        let mut table = BTreeMap::new();
+        table.insert("orphan-node".to_owned(), Severity::Error);
+        let diags = lint_src(ORPHAN, &LintSettings::new(&table).unwrap());
+        assert_eq!(diags[0].severity, Severity::Error);
+
+        table.insert("orphan-node".to_owned(), Severity::Off);
+        let diags = lint_src(ORPHAN, &LintSettings::new(&table).unwrap());
+        assert!(diags.is_empty());
+    }
+
+    #[test]
+    fn unknown_rule_in_settings_is_rejected() {
+        let mut table = BTreeMap::new();
+        table.insert("no-such-rule".to_owned(), Severity::Off);
+        let err = LintSettings::new(&table).unwrap_err();
+        assert!(err.contains("no-such-rule"), "got: {err}");
+    }
+
+    #[test]
+    fn suppression_comment_on_previous_line() {
+        let source = ORPHAN.replace(
+            "  node Lonely {",
+            "  # tast-ignore: orphan-node\n  node Lonely {",
+        );
+        assert!(lint_src(&source, &LintSettings::default()).is_empty());
+    }
+
+    #[test]
+    fn suppression_for_other_rule_does_not_apply() {
+        let source = ORPHAN.replace(
+            "  node Lonely {",
+            "  # tast-ignore: self-loop\n  node Lonely {",
+        );
+        assert_eq!(lint_src(&source, &LintSettings::default()).len(), 1);
+    }
+
+    #[test]
+    fn severity_parses_and_displays() {
+        for s in ["off", "info", "warning", "error"] {
+            assert_eq!(s.parse::<Severity>().unwrap().to_string(), s);
+        }
+        assert!("fatal".parse::<Severity>().is_err());
+    }
+}
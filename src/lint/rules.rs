@@ -0,0 +1,416 @@
+use std::collections::HashSet;
+
+use petgraph::graph::NodeIndex;
+use petgraph::visit::{Bfs, Walker};
+
+use super::{Finding, LintContext, Rule, Severity};
+use crate::graph::analysis::{redundant_edges, root_nodes};
+use crate::graph::dataflow::{ForwardMode, analyze};
+use crate::ir::fixture::extract_fixture_ref;
+use crate::ir::{IrNode, IrStepType};
+
+/// The rule registry. IDs are stable: they appear in `.tastrc.toml` and in
+/// `# tast-ignore:` comments.
+pub static RULES: &[Rule] = &[
+    Rule {
+        id: "orphan-node",
+        summary: "node has no incoming or outgoing edges",
+        default_severity: Severity::Warning,
+        check: orphan_node,
+    },
+    Rule {
+        id: "unreachable-node",
+        summary: "node cannot be reached from any root node",
+        default_severity: Severity::Warning,
+        check: unreachable_node,
+    },
+    Rule {
+        id: "unused-fixture",
+        summary: "fixture is never referenced by a step",
+        default_severity: Severity::Warning,
+        check: unused_fixture,
+    },
+    Rule {
+        id: "unused-passes",
+        summary: "edge passes a field that no downstream node requires",
+        default_severity: Severity::Info,
+        check: unused_passes,
+    },
+    Rule {
+        id: "partial-requires",
+        summary: "required field arrives on some paths into the node but not all",
+        default_severity: Severity::Warning,
+        check: partial_requires,
+    },
+    Rule {
+        id: "missing-then",
+        summary: "node has no `then` step",
+        default_severity: Severity::Warning,
+        check: missing_then,
+    },
+    Rule {
+        id: "then-before-when",
+        summary: "a `when` step follows a `then` step",
+        default_severity: Severity::Warning,
+        check: then_before_when,
+    },
+    Rule {
+        id: "self-loop",
+        summary: "edge connects a node to itself without a loop bound",
+        default_severity: Severity::Error,
+        check: self_loop,
+    },
+    Rule {
+        id: "duplicate-edge",
+        summary: "the same edge is declared more than once",
+        default_severity: Severity::Warning,
+        check: duplicate_edge,
+    },
+    Rule {
+        id: "redundant-edge",
+        summary: "edge is implied by a longer path that also delivers its data",
+        default_severity: Severity::Info,
+        check: redundant_edge,
+    },
+];
+
+/// Nodes copied in from another file by a cross-graph edge are linted in
+/// their own file, not here.
+fn is_imported(node: &IrNode) -> bool {
+    node.name.contains('.')
+}
+
+fn local_nodes<'a>(ctx: &'a LintContext) -> impl Iterator<Item = &'a IrNode> {
+    ctx.ir.nodes.iter().filter(|n| !is_imported(n))
+}
+
+fn orphan_node(ctx: &LintContext) -> Vec<Finding> {
+    if ctx.ir.nodes.len() < 2 {
+        return Vec::new();
+    }
+    ctx.graph
+        .node_indices
+        .iter()
+        .filter(|&&idx| ctx.graph.graph.neighbors_undirected(idx).next().is_none())
+        .map(|&idx| &ctx.graph.graph[idx])
+        .filter(|n| !is_imported(n))
+        .map(|n| Finding {
+            message: format!("node '{}' is not connected to any other node", n.name),
+            span: n.span,
+        })
+        .collect()
+}
+
+fn unreachable_node(ctx: &LintContext) -> Vec<Finding> {
+    let g = &ctx.graph.graph;
+    let mut reached: HashSet<NodeIndex> = HashSet::new();
+    for root in root_nodes(&ctx.graph) {
+        reached.extend(Bfs::new(g, root).iter(g));
+    }
+    ctx.graph
+        .node_indices
+        .iter()
+        .filter(|idx| !reached.contains(idx))
+        .map(|&idx| &g[idx])
+        .filter(|n| !is_imported(n))
+        .map(|n| Finding {
+            message: format!(
+                "node '{}' is unreachable: every path into it starts inside a cycle",
+                n.name
+            ),
+            span: n.span,
+        })
+        .collect()
+}
+
+fn unused_fixture(ctx: &LintContext) -> Vec<Finding> {
+    let referenced: HashSet<String> = ctx
+        .ir
+        .nodes
+        .iter()
+        .flat_map(|n| &n.steps)
+        .filter_map(|s| extract_fixture_ref(&s.text))
+        .collect();
+    ctx.ir
+        .fixtures
+        .iter()
+        .filter(|f| !referenced.contains(&f.name))
+        .map(|f| Finding {
+            message: format!("fixture '{}' is never used", f.name),
+            span: f.span,
+        })
+        .collect()
+}
+
+/// Whether `node` consumes `field`, either via `requires` or a `<field>` step parameter.
+fn consumes(node: &IrNode, field: &str) -> bool {
+    node.requires.iter().any(|r| r == field)
+        || node
+            .steps
+            .iter()
+            .flat_map(|s| &s.parameters)
+            .any(|p| p.name == field)
+}
+
+fn unused_passes(ctx: &LintContext) -> Vec<Finding> {
+    let g = &ctx.graph.graph;
+    let mut findings = Vec::new();
+    for edge in &ctx.ir.edges {
+        if edge.passes.is_empty() {
+            continue;
+        }
+        let target = ctx.graph.node_indices[edge.to_index];
+        let downstream: Vec<&IrNode> = Bfs::new(g, target).iter(g).map(|i| &g[i]).collect();
+        for field in &edge.passes {
+            if !downstream.iter().any(|n| consumes(n, field)) {
+                findings.push(Finding {
+                    message: format!(
+                        "edge '{} -> {}' passes '{field}' but no downstream node requires it",
+                        edge.from, edge.to
+                    ),
+                    span: edge.span,
+                });
+            }
+        }
+    }
+    findings
+}
+
+fn partial_requires(ctx: &LintContext) -> Vec<Finding> {
+    if ctx.ir.nodes.iter().all(|n| n.requires.is_empty()) {
+        return Vec::new();
+    }
+    let mode = ForwardMode::from_config(&ctx.ir.config).unwrap_or_default();
+    let flow = analyze(&ctx.graph, mode);
+    let g = &ctx.graph.graph;
+    let mut findings = Vec::new();
+    for &idx in &ctx.graph.node_indices {
+        let node = &g[idx];
+        if is_imported(node) {
+            continue;
+        }
+        let available = flow.at(idx);
+        for field in &node.requires {
+            if available.must.contains(field) || !available.may.contains(field) {
+                continue;
+            }
+            let Some(path) = flow.missing_path(&ctx.graph, idx, field) else {
+                continue;
+            };
+            let path: Vec<&str> = path.iter().map(|&n| g[n].name.as_str()).collect();
+            findings.push(Finding {
+                message: format!(
+                    "node '{}' requires field '{field}', which is only available on some paths: missing along {}",
+                    node.name,
+                    path.join(" -> ")
+                ),
+                span: node.span,
+            });
+        }
+    }
+    findings
+}
+
+fn missing_then(ctx: &LintContext) -> Vec<Finding> {
+    local_nodes(ctx)
+        .filter(|n| !n.steps.iter().any(|s| s.step_type == IrStepType::Then))
+        .map(|n| Finding {
+            message: format!("node '{}' has no 'then' step", n.name),
+            span: n.span,
+        })
+        .collect()
+}
+
+fn then_before_when(ctx: &LintContext) -> Vec<Finding> {
+    local_nodes(ctx)
+        .filter(|n| {
+            let mut seen_then = false;
+            n.steps.iter().any(|s| match s.step_type {
+                IrStepType::Then => {
+                    seen_then = true;
+                    false
+                }
+                IrStepType::When => seen_then,
+                _ => false,
+            })
+        })
+        .map(|n| Finding {
+            message: format!("node '{}' has a 'when' step after a 'then' step", n.name),
+            span: n.span,
+        })
+        .collect()
+}
+
+fn self_loop(ctx: &LintContext) -> Vec<Finding> {
+    ctx.ir
+        .edges
+        .iter()
+        .filter(|e| e.from == e.to && e.bound.is_none())
+        .map(|e| Finding {
+            message: format!("edge '{} -> {}' connects a node to itself", e.from, e.to),
+            span: e.span,
+        })
+        .collect()
+}
+
+fn duplicate_edge(ctx: &LintContext) -> Vec<Finding> {
+    let mut seen = HashSet::new();
+    ctx.ir
+        .edges
+        .iter()
+        .filter(|e| !seen.insert((e.from.as_str(), e.to.as_str())))
+        .map(|e| Finding {
+            message: format!("edge '{} -> {}' is declared more than once", e.from, e.to),
+            span: e.span,
+        })
+        .collect()
+}
+
+fn redundant_edge(ctx: &LintContext) -> Vec<Finding> {
+    let mode = ForwardMode::from_config(&ctx.ir.config).unwrap_or_default();
+    redundant_edges(&ctx.graph, mode)
+        .into_iter()
+        .map(|e| &ctx.graph.graph[e])
+        .map(|e| Finding {
+            message: format!(
+                "edge '{} -> {}' is redundant: a longer path already orders these nodes and delivers the same data",
+                e.from, e.to
+            ),
+            span: e.span,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ir::lower;
+    use crate::lint::{LintSettings, lint_graph};
+    use crate::parser::parse::parse;
+
+    fn rules_hit(source: &str) -> Vec<&'static str> {
+        let graphs = parse(source).expect("parse failed");
+        let ir = lower(&graphs[0]).expect("lower failed");
+        lint_graph(&ir, source, &LintSettings::default())
+            .into_iter()
+            .map(|d| d.rule)
+            .collect()
+    }
+
+    const OK_NODE: &str = "when it runs\n    then it works";
+
+    #[test]
+    fn clean_graph_has_no_findings() {
+        let src = format!(
+            "graph G {{\n  node A {{\n    {OK_NODE}\n  }}\n  node B {{\n    requires {{ id }}\n    {OK_NODE}\n  }}\n  A -> B {{ passes {{ id }} }}\n}}"
+        );
+        assert!(rules_hit(&src).is_empty(), "got: {:?}", rules_hit(&src));
+    }
+
+    #[test]
+    fn single_node_graph_is_not_orphan() {
+        let src = format!("graph G {{\n  node A {{\n    {OK_NODE}\n  }}\n}}");
+        assert!(rules_hit(&src).is_empty());
+    }
+
+    #[test]
+    fn unreachable_cycle() {
+        let src = format!(
+            "graph G {{\n  node A {{\n    {OK_NODE}\n  }}\n  node B {{\n    {OK_NODE}\n  }}\n  node C {{\n    {OK_NODE}\n  }}\n  node D {{\n    {OK_NODE}\n  }}\n  A -> B\n  C -> D\n  D -> C\n}}"
+        );
+        assert_eq!(
+            rules_hit(&src),
+            vec!["unreachable-node", "unreachable-node"]
+        );
+    }
+
+    #[test]
+    fn unused_fixture() {
+        let src = format!(
+            "graph G {{\n  fixture Admin {{\n    role: \"admin\"\n  }}\n  node A {{\n    {OK_NODE}\n  }}\n}}"
+        );
+        assert_eq!(rules_hit(&src), vec!["unused-fixture"]);
+    }
+
+    #[test]
+    fn used_fixture_is_not_reported() {
+        let src = "graph G {\n  fixture Admin {\n    role: \"admin\"\n  }\n  node A {\n    given a user from fixture Admin\n    then it works\n  }\n}";
+        assert!(rules_hit(src).is_empty());
+    }
+
+    #[test]
+    fn unused_passes_checks_downstream_nodes() {
+        let src = format!(
+            "graph G {{\n  node A {{\n    {OK_NODE}\n  }}\n  node B {{\n    {OK_NODE}\n  }}\n  node C {{\n    requires {{ token }}\n    {OK_NODE}\n  }}\n  A -> B {{ passes {{ token, spare }} }}\n  B -> C {{ passes {{ token }} }}\n}}"
+        );
+        assert_eq!(rules_hit(&src), vec!["unused-passes"]);
+    }
+
+    #[test]
+    fn step_parameter_counts_as_use() {
+        let src = format!(
+            "graph G {{\n  node A {{\n    {OK_NODE}\n  }}\n  node B {{\n    when the user opens <page>\n    then it works\n  }}\n  A -> B {{ passes {{ page }} }}\n}}"
+        );
+        assert!(rules_hit(&src).is_empty());
+    }
+
+    #[test]
+    fn partial_requires_names_the_missing_path() {
+        let src = format!(
+            "graph G {{\n  node Register {{\n    {OK_NODE}\n  }}\n  node Guest {{\n    {OK_NODE}\n  }}\n  node Dashboard {{\n    requires {{ user_id }}\n    {OK_NODE}\n  }}\n  Register -> Dashboard {{ passes {{ user_id }} }}\n  Guest -> Dashboard\n}}"
+        );
+        let graphs = parse(&src).expect("parse failed");
+        let ir = lower(&graphs[0]).expect("partial requires should lower");
+        let diagnostics = lint_graph(&ir, &src, &LintSettings::default());
+        assert_eq!(diagnostics.len(), 1, "got: {diagnostics:?}");
+        assert_eq!(diagnostics[0].rule, "partial-requires");
+        assert!(
+            diagnostics[0]
+                .message
+                .ends_with("only available on some paths: missing along Guest -> Dashboard"),
+            "got: {}",
+            diagnostics[0].message
+        );
+    }
+
+    #[test]
+    fn missing_then_and_ordering() {
+        let src = "graph G {\n  node A {\n    given a thing\n    when it runs\n  }\n}";
+        assert_eq!(rules_hit(src), vec!["missing-then"]);
+
+        let src = "graph G {\n  node A {\n    when it runs\n    then it works\n    when it runs again\n  }\n}";
+        assert_eq!(rules_hit(src), vec!["then-before-when"]);
+
+        let src = "graph G {\n  node A {\n    when it runs\n    then it works\n    and it still works\n  }\n}";
+        assert!(rules_hit(src).is_empty());
+    }
+
+    #[test]
+    fn self_loop_and_duplicate_edge() {
+        let src = format!(
+            "graph G {{\n  node A {{\n    {OK_NODE}\n  }}\n  node B {{\n    {OK_NODE}\n  }}\n  A -> B\n  A -> B\n  B -> B\n}}"
+        );
+        assert_eq!(rules_hit(&src), vec!["duplicate-edge", "self-loop"]);
+    }
+
+    #[test]
+    fn bounded_self_loop_is_allowed() {
+        let src = format!(
+            "graph G {{\n  node A {{\n    {OK_NODE}\n  }}\n  node B {{\n    {OK_NODE}\n  }}\n  A -> B\n  B -> B repeat 2\n}}"
+        );
+        assert!(rules_hit(&src).is_empty());
+    }
+
+    #[test]
+    fn redundant_edge() {
+        let src = format!(
+            "graph G {{\n  node A {{\n    {OK_NODE}\n  }}\n  node B {{\n    {OK_NODE}\n  }}\n  node C {{\n    {OK_NODE}\n  }}\n  A -> B\n  B -> C\n  A -> C\n}}"
+        );
+        assert_eq!(rules_hit(&src), vec!["redundant-edge"]);
+
+        // The shortcut is the only edge bringing the id to C
+        let src = src
+            .replace("A -> C", "A -> C { passes { id } }")
+            .replace("node C {\n", "node C {\n    given the record <id>\n");
+        assert!(rules_hit(&src).is_empty(), "got: {:?}", rules_hit(&src));
+    }
+}
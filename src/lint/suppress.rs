@@ -0,0 +1,93 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::parser::lexer::{TokenKind, tokenize};
+
+/// Marker that starts a suppression comment: `# tast-ignore: rule-a, rule-b`.
+const MARKER: &str = "tast-ignore:";
+
+/// Rule IDs suppressed on each source line.
+///
+/// A suppression comment applies to findings on its own line and on the
+/// line directly after it.
+#[derive(Debug, Default)]
+pub(crate) struct Suppressions {
+    by_line: HashMap<usize, HashSet<String>>,
+}
+
+impl Suppressions {
+    /// Collect every `# tast-ignore:` comment in `source`.
+    ///
+    /// Comments are found by the lexer, so a `#` inside a string does not
+    /// start one. Source that does not lex has no suppressions.
+    pub(crate) fn scan(source: &str) -> Self {
+        let mut by_line = HashMap::new();
+        let tokens = tokenize(source).unwrap_or_default();
+        for token in tokens {
+            let TokenKind::Comment(comment) = token.kind else {
+                continue;
+            };
+            let Some(list) = comment.trim_start().strip_prefix(MARKER) else {
+                continue;
+            };
+            let ids: HashSet<String> = list
+                .split(',')
+                .map(str::trim)
+                .filter(|id| !id.is_empty())
+                .map(str::to_owned)
+                .collect();
+            by_line.insert(token.span.line, ids);
+        }
+        Self { by_line }
+    }
+
+    /// Whether `rule` is suppressed for a finding reported on `line` (1-based).
+    pub(crate) fn is_suppressed(&self, rule: &str, line: usize) -> bool {
+        [line, line.saturating_sub(1)]
+            .iter()
+            .filter_map(|l| self.by_line.get(l))
+            .any(|ids| ids.contains(rule))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_line_and_previous_line() {
+        let s = Suppressions::scan("a\nb # tast-ignore: self-loop\nc\n");
+        assert!(s.is_suppressed("self-loop", 2));
+        assert!(s.is_suppressed("self-loop", 3));
+        assert!(!s.is_suppressed("self-loop", 1));
+        assert!(!s.is_suppressed("self-loop", 4));
+    }
+
+    #[test]
+    fn multiple_ids_and_spacing() {
+        let s = Suppressions::scan("#tast-ignore:orphan-node ,  missing-then\n");
+        assert!(s.is_suppressed("orphan-node", 1));
+        assert!(s.is_suppressed("missing-then", 1));
+        assert!(!s.is_suppressed("self-loop", 1));
+    }
+
+    #[test]
+    fn hashes_inside_strings_do_not_start_comments() {
+        let s = Suppressions::scan(concat!(
+            "fixture F { note: \"# tast-ignore: self-loop\" }\n",
+            "fixture G { note: \"#1\" } # tast-ignore: orphan-node\n",
+            "node A {\n",
+            "  given issue # tast-ignore: missing-then\n",
+            "}\n",
+        ));
+        assert!(!s.is_suppressed("self-loop", 1));
+        assert!(s.is_suppressed("orphan-node", 2));
+        // Step text runs to the end of the line
+        assert!(!s.is_suppressed("missing-then", 4));
+    }
+
+    #[test]
+    fn ordinary_comments_are_ignored() {
+        let s = Suppressions::scan("# tast-ignore is documented elsewhere\n");
+        assert!(!s.is_suppressed("orphan-node", 1));
+    }
+}
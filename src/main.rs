@@ -1,8 +1,9 @@
 use std::path::PathBuf;
 
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 
-use tast::cli::commands::{self, PlanOptions};
+use tast::cli::commands;
+use tast::cli::config::{self, LoadedConfig};
 
 #[derive(Parser)]
 #[command(name = "tast", about = "TAST — Test Abstract Syntax Tree", version)]
@@ -13,34 +14,63 @@ struct Cli {
 
 #[derive(Subcommand)]
 enum Commands {
+    /// Create a .tastrc.toml and an example test graph for this project
+    Init {
+        /// Project directory (default: current directory)
+        #[arg(default_value = ".")]
+        dir: PathBuf,
+
+        /// Overwrite existing files
+        #[arg(long)]
+        force: bool,
+    },
+
     /// Compile test plans from .tast files
-    Plan {
+    Plan(Box<PlanArgs>),
+
+    /// Record node, edge and fixture fingerprints for `tast plan --since`
+    Snapshot {
         /// Input .tast files
         files: Vec<PathBuf>,
 
         /// Output file (default: stdout)
         #[arg(short, long)]
         output: Option<PathBuf>,
+    },
 
-        /// Traversal strategy (topological, dfs, bfs)
-        #[arg(short, long, default_value = "topological")]
-        strategy: String,
+    /// Show what changed between two versions of a set of graphs
+    Diff {
+        /// Old version: a .tast file or a directory
+        old: PathBuf,
 
-        /// Output format (yaml, markdown, junit)
-        #[arg(short = 'F', long, default_value = "yaml")]
+        /// New version: a .tast file or a directory
+        new: PathBuf,
+
+        /// Output format (text, json)
+        #[arg(short = 'F', long, default_value = "text")]
         format: String,
+    },
 
-        /// Filter nodes by tag predicate
-        #[arg(short, long)]
-        filter: Option<String>,
+    /// Report graph metrics and fail when they exceed thresholds
+    Stats {
+        /// Input .tast files
+        files: Vec<PathBuf>,
 
-        /// Start node for path query
+        /// Most nodes allowed on a dependency chain [default: from config]
         #[arg(long)]
-        from: Option<String>,
+        max_depth: Option<usize>,
 
-        /// End node for path query
+        /// Most incoming edges allowed on one node [default: from config]
         #[arg(long)]
-        to: Option<String>,
+        max_fan_in: Option<usize>,
+
+        /// Most outgoing edges allowed on one node [default: from config]
+        #[arg(long)]
+        max_fan_out: Option<usize>,
+
+        /// Largest percentage of steps with unresolved parameters [default: from config]
+        #[arg(long, value_name = "PERCENT")]
+        max_unresolved: Option<f64>,
     },
 
     /// Validate .tast files without compiling
@@ -49,6 +79,16 @@ enum Commands {
         files: Vec<PathBuf>,
     },
 
+    /// Check .tast files against lint rules
+    Lint {
+        /// Input .tast files
+        files: Vec<PathBuf>,
+
+        /// Treat a diagnostic level as fatal (only `warnings` is supported)
+        #[arg(long, value_name = "LEVEL", value_parser = ["warnings"])]
+        deny: Option<String>,
+    },
+
     /// List nodes, edges, or tags from .tast files
     List {
         /// What to list: nodes, edges, or tags
@@ -56,6 +96,10 @@ enum Commands {
 
         /// Input .tast files
         files: Vec<PathBuf>,
+
+        /// Only list nodes selected by a query
+        #[arg(long, value_name = "QUERY")]
+        select: Option<String>,
     },
 
     /// Visualize the test graph (DOT/Mermaid output)
@@ -70,35 +114,254 @@ enum Commands {
         /// Output file (default: stdout)
         #[arg(short, long)]
         output: Option<PathBuf>,
+
+        /// Only draw nodes selected by a query
+        #[arg(long, value_name = "QUERY")]
+        select: Option<String>,
+
+        /// Leave out edges implied by a longer path
+        #[arg(long)]
+        reduced: bool,
+    },
+
+    /// Print the JSON Schema of the plan format written by `tast plan`
+    Schema,
+
+    /// Inspect project configuration (.tastrc.toml)
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
     },
 }
 
+/// Arguments of the `plan` command.
+#[derive(Args)]
+struct PlanArgs {
+    /// Input .tast files
+    files: Vec<PathBuf>,
+
+    /// Output file (default: stdout)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Traversal strategy (topological, dfs, bfs, paths, edge-cover, random, weighted) [default: from config, else topological]
+    #[arg(short, long)]
+    strategy: Option<String>,
+
+    /// Output format (yaml, json, json-compact, markdown, junit) [default: from config, else yaml]
+    #[arg(short = 'F', long)]
+    format: Option<String>,
+
+    /// Filter nodes by tag predicate
+    #[arg(short, long)]
+    filter: Option<String>,
+
+    /// How --filter treats dependencies of kept steps (drop, keep-deps, prune) [default: from config, else drop]
+    #[arg(long)]
+    filter_mode: Option<String>,
+
+    /// Start node for path query
+    #[arg(long)]
+    from: Option<String>,
+
+    /// End node for path query
+    #[arg(long)]
+    to: Option<String>,
+
+    /// Fail on unresolved step parameters instead of warning
+    #[arg(long)]
+    strict: bool,
+
+    /// Show raw step templates next to rendered step text in Markdown and JUnit output
+    #[arg(long)]
+    keep_template: bool,
+
+    /// Maximum number of paths for the paths strategy [default: 100]
+    #[arg(long)]
+    max_paths: Option<usize>,
+
+    /// Maximum path length, in nodes, for the paths strategy [default: 32]
+    #[arg(long)]
+    max_path_length: Option<usize>,
+
+    /// Plan only nodes matching this name or glob, with their ancestors and descendants
+    #[arg(long, value_name = "NODE")]
+    containing: Option<String>,
+
+    /// Plan only nodes matching this name or glob, with their ancestors
+    #[arg(long, value_name = "NODE")]
+    upstream_of: Option<String>,
+
+    /// Plan only nodes matching this name or glob, with their descendants
+    #[arg(long, value_name = "NODE")]
+    downstream_of: Option<String>,
+
+    /// Plan only nodes selected by a query, e.g. 'tag:auth* AND NOT descendants(Login)'
+    #[arg(long, value_name = "QUERY")]
+    select: Option<String>,
+
+    /// Plan only these nodes, their descendants, and the ancestors those need
+    #[arg(long, value_delimiter = ',', value_name = "NODES")]
+    affected_by: Vec<String>,
+
+    /// Plan only what changed since a snapshot taken with `tast snapshot`
+    #[arg(long, value_name = "SNAPSHOT")]
+    since: Option<PathBuf>,
+
+    /// Seed for the random strategy [default: a fresh seed, recorded in the plan]
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Number of distinct random orders to plan (random strategy)
+    #[arg(long, value_name = "N")]
+    permutations: Option<usize>,
+
+    /// Plan only the nodes that fit in a time budget, e.g. 10m, with their ancestors
+    #[arg(long, value_name = "DURATION")]
+    budget: Option<String>,
+
+    /// What a budgeted plan keeps as much of (edges, tags, priority) [default: edges]
+    #[arg(long, value_name = "METRIC")]
+    budget_by: Option<String>,
+
+    /// Plan only shard I of N balanced shards, each with the setup it needs, e.g. 2/5
+    #[arg(long, value_name = "I/N")]
+    shard: Option<String>,
+
+    /// Merge all graphs into one plan, prefixing node names with the graph name
+    #[arg(long)]
+    merge: bool,
+}
+
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Print the effective settings after applying defaults, config file, and environment
+    Show,
+}
+
+/// Load the effective configuration for the current directory, exiting on error.
+fn load_config() -> LoadedConfig {
+    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    config::load_effective(&cwd, |k| std::env::var(k).ok()).unwrap_or_else(|e| {
+        eprintln!("error: {e}");
+        std::process::exit(1);
+    })
+}
+
+/// Use the given files, or discover them under the configured test directory.
+fn input_files(files: Vec<PathBuf>, loaded: &LoadedConfig) -> Vec<PathBuf> {
+    if !files.is_empty() {
+        return files;
+    }
+    match commands::discover_files(loaded) {
+        Ok(found) if !found.is_empty() => found,
+        Ok(_) => {
+            eprintln!("error: no input files provided");
+            std::process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("error: {e}");
+            std::process::exit(1);
+        }
+    }
+}
+
 fn main() {
     let cli = Cli::parse();
 
-    match cli.command {
-        Some(Commands::Plan {
-            files,
-            output,
-            strategy,
-            format,
-            filter,
-            from,
-            to,
-        }) => {
-            if files.is_empty() {
-                eprintln!("error: no input files provided");
+    // `init` must work even when an existing config is broken.
+    if let Some(Commands::Init { dir, force }) = &cli.command {
+        match commands::run_init(dir, *force) {
+            Ok(result) => println!("{result}"),
+            Err(e) => {
+                eprintln!("error: {e}");
                 std::process::exit(1);
             }
-            let options = PlanOptions {
+        }
+        return;
+    }
+
+    let loaded = load_config();
+
+    match cli.command {
+        Some(Commands::Plan(args)) => {
+            let PlanArgs {
+                files,
                 output,
                 strategy,
                 format,
                 filter,
+                filter_mode,
                 from,
                 to,
-            };
+                strict,
+                keep_template,
+                max_paths,
+                max_path_length,
+                containing,
+                upstream_of,
+                downstream_of,
+                select,
+                affected_by,
+                since,
+                seed,
+                permutations,
+                budget,
+                budget_by,
+                shard,
+                merge,
+            } = *args;
+            let files = input_files(files, &loaded);
+            let mut options = loaded.config.plan_options();
+            options.output = output;
+            options.strategy = strategy.unwrap_or(options.strategy);
+            options.format = format.unwrap_or(options.format);
+            options.filter = filter.or(options.filter);
+            options.filter_mode = filter_mode.unwrap_or(options.filter_mode);
+            options.from = from;
+            options.to = to;
+            options.strict |= strict;
+            options.keep_template |= keep_template;
+            options.max_paths = max_paths.unwrap_or(options.max_paths);
+            options.max_path_length = max_path_length.unwrap_or(options.max_path_length);
+            options.affected_by = affected_by;
+            options.since = since;
+            options.containing = containing;
+            options.upstream_of = upstream_of;
+            options.downstream_of = downstream_of;
+            options.select = select;
+            options.seed = seed;
+            options.permutations = permutations.unwrap_or(options.permutations);
+            options.budget = budget;
+            options.budget_by = budget_by.unwrap_or(options.budget_by);
+            options.shard = shard;
+            options.merge = merge;
             match commands::run_plan(&files, &options) {
+                Ok(outcome) => {
+                    for warning in &outcome.warnings {
+                        eprintln!("warning: {warning}");
+                    }
+                    print!("{}", outcome.output);
+                }
+                Err(e) => {
+                    eprintln!("error: {e}");
+                    std::process::exit(1);
+                }
+            }
+        }
+        Some(Commands::Snapshot { files, output }) => {
+            let files = input_files(files, &loaded);
+            match commands::run_snapshot(&files, output.as_ref()) {
+                Ok(result) => print!("{result}"),
+                Err(e) => {
+                    eprintln!("error: {e}");
+                    std::process::exit(1);
+                }
+            }
+        }
+        Some(Commands::Diff { old, new, format }) => {
+            let extension = &loaded.config.project.file_extension;
+            match commands::run_diff(&old, &new, &format, extension) {
                 Ok(result) => print!("{result}"),
                 Err(e) => {
                     eprintln!("error: {e}");
@@ -110,12 +373,17 @@ fn main() {
             files,
             format,
             output,
+            select,
+            reduced,
         }) => {
-            if files.is_empty() {
-                eprintln!("error: no input files provided");
-                std::process::exit(1);
-            }
-            match commands::run_visualize(&files, &format, output.as_ref()) {
+            let files = input_files(files, &loaded);
+            match commands::run_visualize(
+                &files,
+                &format,
+                output.as_ref(),
+                select.as_deref(),
+                reduced,
+            ) {
                 Ok(result) => print!("{result}"),
                 Err(e) => {
                     eprintln!("error: {e}");
@@ -123,12 +391,13 @@ fn main() {
                 }
             }
         }
-        Some(Commands::List { what, files }) => {
-            if files.is_empty() {
-                eprintln!("error: no input files provided");
-                std::process::exit(1);
-            }
-            match commands::run_list(&what, &files) {
+        Some(Commands::List {
+            what,
+            files,
+            select,
+        }) => {
+            let files = input_files(files, &loaded);
+            match commands::run_list(&what, &files, select.as_deref()) {
                 Ok(result) => print!("{result}"),
                 Err(e) => {
                     eprintln!("error: {e}");
@@ -137,10 +406,7 @@ fn main() {
             }
         }
         Some(Commands::Validate { files }) => {
-            if files.is_empty() {
-                eprintln!("error: no input files provided");
-                std::process::exit(1);
-            }
+            let files = input_files(files, &loaded);
             match commands::run_validate(&files) {
                 Ok(result) => println!("{result}"),
                 Err(e) => {
@@ -149,6 +415,68 @@ fn main() {
                 }
             }
         }
+        Some(Commands::Stats {
+            files,
+            max_depth,
+            max_fan_in,
+            max_fan_out,
+            max_unresolved,
+        }) => {
+            let files = input_files(files, &loaded);
+            let mut thresholds = loaded.config.stats.clone();
+            thresholds.max_depth = max_depth.or(thresholds.max_depth);
+            thresholds.max_fan_in = max_fan_in.or(thresholds.max_fan_in);
+            thresholds.max_fan_out = max_fan_out.or(thresholds.max_fan_out);
+            thresholds.max_unresolved = max_unresolved.or(thresholds.max_unresolved);
+            match commands::run_stats(&files, &thresholds) {
+                Ok(outcome) => {
+                    print!("{}", outcome.report);
+                    if outcome.failed {
+                        std::process::exit(1);
+                    }
+                }
+                Err(e) => {
+                    eprintln!("error: {e}");
+                    std::process::exit(1);
+                }
+            }
+        }
+        Some(Commands::Lint { files, deny }) => {
+            let files = input_files(files, &loaded);
+            let result = loaded
+                .config
+                .lint_settings()
+                .and_then(|settings| commands::run_lint(&files, &settings, deny.is_some()));
+            match result {
+                Ok(outcome) => {
+                    println!("{}", outcome.report);
+                    if outcome.failed {
+                        std::process::exit(1);
+                    }
+                }
+                Err(e) => {
+                    eprintln!("error: {e}");
+                    std::process::exit(1);
+                }
+            }
+        }
+        Some(Commands::Schema) => match commands::run_schema() {
+            Ok(result) => print!("{result}"),
+            Err(e) => {
+                eprintln!("error: {e}");
+                std::process::exit(1);
+            }
+        },
+        Some(Commands::Config {
+            action: ConfigAction::Show,
+        }) => match commands::run_config_show(&loaded) {
+            Ok(result) => print!("{result}"),
+            Err(e) => {
+                eprintln!("error: {e}");
+                std::process::exit(1);
+            }
+        },
+        Some(Commands::Init { .. }) => unreachable!("handled before loading config"),
         None => {
             // No subcommand — clap will show help via the derive
             Cli::parse_from(["tast", "--help"]);
@@ -49,7 +49,9 @@ pub struct Step {
     pub span: Span,
 }
 
-/// A tag for filtering: `tags [smoke, critical]`.
+/// A tag for filtering: `tags [smoke, team/auth, priority: 1]`.
+///
+/// Key-value tags are stored as `key:value`.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Tag(pub String);
 
@@ -77,6 +79,10 @@ pub struct Node {
     pub steps: Vec<Step>,
     pub tags: Vec<Tag>,
     pub requires: Vec<String>,
+    /// Fields the node declares it creates (`produces { ... }`).
+    pub produces: Vec<String>,
+    /// Fields passed on to successors under explicit forwarding (`forwards { ... }`).
+    pub forwards: Vec<String>,
     pub config: Option<DataBlock>,
     pub span: Span,
 }
@@ -88,15 +94,46 @@ pub struct Edge {
     pub to: String,
     pub passes: Vec<String>,
     pub description: Option<String>,
+    /// Loop bound for an edge that closes a cycle (`A -> B repeat 3`).
+    pub bound: Option<LoopBound>,
     pub span: Span,
 }
 
+/// How many times a loop closed by a bounded edge runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoopBound {
+    /// `repeat N`: the loop body runs exactly `N` times.
+    Repeat(u32),
+    /// `max N`: the loop body runs at least once and at most `N` times.
+    Max(u32),
+}
+
+impl LoopBound {
+    /// The largest number of times the loop body runs.
+    pub fn count(self) -> u32 {
+        match self {
+            Self::Repeat(n) | Self::Max(n) => n,
+        }
+    }
+}
+
+impl std::fmt::Display for LoopBound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Repeat(n) => write!(f, "repeat {n}"),
+            Self::Max(n) => write!(f, "max {n}"),
+        }
+    }
+}
+
 /// A top-level graph container.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Graph {
     pub name: String,
     pub nodes: Vec<Node>,
     pub edges: Vec<Edge>,
+    /// Graph-level tags, inherited by every node.
+    pub tags: Vec<Tag>,
     pub config: Option<DataBlock>,
     pub imports: Vec<Import>,
     pub fixtures: Vec<Fixture>,
@@ -113,6 +150,7 @@ mod tests {
             name: "Empty".into(),
             nodes: vec![],
             edges: vec![],
+            tags: vec![],
             config: None,
             imports: vec![],
             fixtures: vec![],
@@ -129,6 +167,7 @@ mod tests {
             name: "UserAuthentication".into(),
             nodes: vec![],
             edges: vec![],
+            tags: vec![],
             config: None,
             imports: vec![],
             fixtures: vec![],
@@ -145,6 +184,8 @@ mod tests {
             steps: vec![],
             tags: vec![],
             requires: vec![],
+            produces: vec![],
+            forwards: vec![],
             config: None,
             span: Span::default(),
         };
@@ -175,6 +216,8 @@ mod tests {
             ],
             tags: vec![],
             requires: vec![],
+            produces: vec![],
+            forwards: vec![],
             config: None,
             span: Span::default(),
         };
@@ -250,6 +293,7 @@ mod tests {
             to: "LoginUser".into(),
             passes: vec!["user_id".into(), "email".into()],
             description: None,
+            bound: None,
             span: Span::default(),
         };
         assert_eq!(edge.from, "RegisterUser");
@@ -264,6 +308,7 @@ mod tests {
             to: "B".into(),
             passes: vec![],
             description: Some("A leads to B".into()),
+            bound: None,
             span: Span::default(),
         };
         assert_eq!(edge.description.as_deref(), Some("A leads to B"));
@@ -338,6 +383,8 @@ mod tests {
             steps: vec![],
             tags: vec![Tag("smoke".into()), Tag("critical".into())],
             requires: vec![],
+            produces: vec![],
+            forwards: vec![],
             config: None,
             span: Span::default(),
         };
@@ -354,6 +401,8 @@ mod tests {
             steps: vec![],
             tags: vec![],
             requires: vec!["auth_token".into()],
+            produces: vec![],
+            forwards: vec![],
             config: None,
             span: Span::default(),
         };
@@ -396,6 +445,8 @@ mod tests {
                     }],
                     tags: vec![Tag("smoke".into())],
                     requires: vec![],
+                    produces: vec![],
+                    forwards: vec![],
                     config: None,
                     span: Span::default(),
                 },
@@ -405,6 +456,8 @@ mod tests {
                     steps: vec![],
                     tags: vec![],
                     requires: vec!["session_id".into()],
+                    produces: vec![],
+                    forwards: vec![],
                     config: None,
                     span: Span::default(),
                 },
@@ -414,8 +467,10 @@ mod tests {
                 to: "Logout".into(),
                 passes: vec!["session_id".into()],
                 description: Some("Login to logout flow".into()),
+                bound: None,
                 span: Span::default(),
             }],
+            tags: vec![],
             config: None,
             imports: vec![],
             fixtures: vec![],
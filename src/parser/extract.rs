@@ -62,6 +62,7 @@ pub fn extract_data(text: &str) -> ExtractedData {
                         }
                         // <word> <binding_verb> <quoted_value|number>
                         // e.g., "status is "active""
+                        #[allow(clippy::collapsible_match)]
                         ExtractToken::BindingVerb => {
                             if i + 2 < tokens.len() {
                                 match &tokens[i + 2] {
@@ -15,6 +15,8 @@ pub enum TokenKind {
     But,
     Passes,
     Requires,
+    Produces,
+    Forwards,
     Tags,
     Config,
     Import,
@@ -30,10 +32,13 @@ pub enum TokenKind {
     Colon,
     Comma,
     Dot,
+    Slash,
 
     // Literals & identifiers
     StringLiteral(String),
     Identifier(String),
+    /// A number, kept as written, with an optional unit suffix (`3`, `1.5`, `30s`).
+    Number(String),
 
     /// Free-form text after step keywords (given/when/then/and/but).
     FreeText(String),
@@ -230,6 +235,14 @@ pub fn tokenize(input: &str) -> Result<Vec<Token>, LexError> {
                 chars.next();
                 col += 1;
             }
+            '/' => {
+                tokens.push(Token {
+                    kind: TokenKind::Slash,
+                    span: Span::new(pos, pos + 1, line, col),
+                });
+                chars.next();
+                col += 1;
+            }
 
             // Arrow ->
             '-' => {
@@ -249,6 +262,30 @@ pub fn tokenize(input: &str) -> Result<Vec<Token>, LexError> {
                 }
             }
 
+            // Numbers, with an optional fraction and unit suffix
+            c if c.is_ascii_digit() => {
+                let start_col = col;
+                let mut end = pos;
+                let mut seen_dot = false;
+                while let Some(&(i, c)) = chars.peek() {
+                    let fraction_dot = c == '.'
+                        && !seen_dot
+                        && input[i + 1..].starts_with(|d: char| d.is_ascii_digit());
+                    if c.is_ascii_alphanumeric() || c == '_' || fraction_dot {
+                        seen_dot |= c == '.';
+                        end = i + c.len_utf8();
+                        chars.next();
+                        col += 1;
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push(Token {
+                    kind: TokenKind::Number(input[pos..end].to_owned()),
+                    span: Span::new(pos, end, line, start_col),
+                });
+            }
+
             // Identifiers and keywords
             c if c.is_ascii_alphabetic() || c == '_' => {
                 let start_col = col;
@@ -277,6 +314,8 @@ pub fn tokenize(input: &str) -> Result<Vec<Token>, LexError> {
                     "but" => TokenKind::But,
                     "passes" => TokenKind::Passes,
                     "requires" => TokenKind::Requires,
+                    "produces" => TokenKind::Produces,
+                    "forwards" => TokenKind::Forwards,
                     "tags" => TokenKind::Tags,
                     "config" => TokenKind::Config,
                     "import" => TokenKind::Import,
@@ -463,6 +502,8 @@ mod tests {
         // Non-step keywords
         assert_eq!(kinds("passes"), vec![TokenKind::Passes]);
         assert_eq!(kinds("requires"), vec![TokenKind::Requires]);
+        assert_eq!(kinds("produces"), vec![TokenKind::Produces]);
+        assert_eq!(kinds("forwards"), vec![TokenKind::Forwards]);
         assert_eq!(kinds("tags"), vec![TokenKind::Tags]);
         assert_eq!(kinds("config"), vec![TokenKind::Config]);
         assert_eq!(kinds("import"), vec![TokenKind::Import]);
@@ -837,4 +878,22 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn tokenizes_numbers_with_fractions_and_units() {
+        assert_eq!(
+            kinds_no_newlines("A -> B repeat 3 30s 1.5 2."),
+            vec![
+                TokenKind::Identifier("A".into()),
+                TokenKind::Arrow,
+                TokenKind::Identifier("B".into()),
+                TokenKind::Identifier("repeat".into()),
+                TokenKind::Number("3".into()),
+                TokenKind::Number("30s".into()),
+                TokenKind::Number("1.5".into()),
+                TokenKind::Number("2".into()),
+                TokenKind::Dot,
+            ]
+        );
+    }
 }
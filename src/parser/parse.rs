@@ -1,5 +1,6 @@
 use crate::parser::ast::{
-    DataBlock, Edge, Fixture, Graph, Import, Node, Step, StepFragment, StepType, Tag, Value,
+    DataBlock, Edge, Fixture, Graph, Import, LoopBound, Node, Step, StepFragment, StepType, Tag,
+    Value,
 };
 use crate::parser::error::ParseError;
 use crate::parser::lexer::{Token, TokenKind, tokenize};
@@ -167,6 +168,7 @@ impl<'a> Parser<'a> {
         let mut nodes: Vec<Node> = Vec::new();
         let mut edges: Vec<Edge> = Vec::new();
         let mut fixtures: Vec<Fixture> = Vec::new();
+        let mut tags = Vec::new();
         let mut config = None;
 
         loop {
@@ -209,6 +211,7 @@ impl<'a> Parser<'a> {
                         name,
                         nodes,
                         edges,
+                        tags,
                         config,
                         imports: vec![],
                         fixtures,
@@ -221,6 +224,9 @@ impl<'a> Parser<'a> {
                 Some(TokenKind::Fixture) => {
                     fixtures.push(self.parse_fixture()?);
                 }
+                Some(TokenKind::Tags) => {
+                    tags = self.parse_tags()?;
+                }
                 Some(TokenKind::Config) => {
                     config = Some(self.parse_config_block()?);
                 }
@@ -258,6 +264,8 @@ impl<'a> Parser<'a> {
         let mut steps = Vec::new();
         let mut tags = Vec::new();
         let mut requires = Vec::new();
+        let mut produces = Vec::new();
+        let mut forwards = Vec::new();
         let mut config = None;
 
         loop {
@@ -271,6 +279,8 @@ impl<'a> Parser<'a> {
                         steps,
                         tags,
                         requires,
+                        produces,
+                        forwards,
                         config,
                         span: start_span.merge(end_span),
                     });
@@ -314,7 +324,13 @@ impl<'a> Parser<'a> {
                     tags = self.parse_tags()?;
                 }
                 Some(TokenKind::Requires) => {
-                    requires = self.parse_requires()?;
+                    requires = self.parse_field_list()?;
+                }
+                Some(TokenKind::Produces) => {
+                    produces = self.parse_field_list()?;
+                }
+                Some(TokenKind::Forwards) => {
+                    forwards = self.parse_field_list()?;
                 }
                 Some(TokenKind::Config) => {
                     config = Some(self.parse_config_block()?);
@@ -405,38 +421,70 @@ impl<'a> Parser<'a> {
     }
 
     /// Parse: `tags [tag1, tag2, ...]`
+    ///
+    /// A tag is a name (`smoke`), a `/`-separated namespace path
+    /// (`team/auth/login`) or a `key: value` pair (`priority: 1`), which is
+    /// kept as `key:value`.
     fn parse_tags(&mut self) -> Result<Vec<Tag>, ParseError> {
         self.next_token(); // consume 'tags'
         self.expect(&TokenKind::LBracket)?;
         let mut tags = Vec::new();
+        loop {
+            if self.peek_kind() == Some(&TokenKind::RBracket) {
+                self.next_token();
+                return Ok(tags);
+            }
+            let mut tag = self.parse_tag_path("expected tag name or ']'")?;
+            if self.peek_kind() == Some(&TokenKind::Colon) {
+                self.next_token();
+                let value = if let Some(TokenKind::StringLiteral(s)) = self.peek_kind() {
+                    let s = s.clone();
+                    self.next_token();
+                    s
+                } else {
+                    self.parse_tag_path("expected tag value after ':'")?
+                };
+                tag = format!("{tag}:{value}");
+            }
+            tags.push(Tag(tag));
+            // Optional comma
+            if self.peek_kind() == Some(&TokenKind::Comma) {
+                self.next_token();
+            }
+        }
+    }
+
+    /// Parse a tag name or value: one or more words joined by `/`.
+    fn parse_tag_path(&mut self, expected: &str) -> Result<String, ParseError> {
+        let mut path = String::new();
+        let mut expected = expected;
         loop {
             match self.peek_kind() {
-                Some(TokenKind::RBracket) => {
+                Some(TokenKind::Identifier(name) | TokenKind::Number(name)) => {
+                    path.push_str(name);
                     self.next_token();
-                    return Ok(tags);
-                }
-                Some(TokenKind::Identifier(_)) => {
-                    let (name, _) = self.expect_identifier()?;
-                    tags.push(Tag(name));
-                    // Optional comma
-                    if self.peek_kind() == Some(&TokenKind::Comma) {
-                        self.next_token();
-                    }
                 }
                 _ => {
                     let span = self.peek().map_or(self.eof_span(), |t| t.span);
                     return Err(ParseError {
-                        message: "expected tag name or ']'".to_owned(),
+                        message: expected.to_owned(),
                         span,
                     });
                 }
             }
+            if self.peek_kind() != Some(&TokenKind::Slash) {
+                return Ok(path);
+            }
+            self.next_token();
+            path.push('/');
+            expected = "expected tag name after '/'";
         }
     }
 
-    /// Parse: `requires { field1, field2, ... }`
-    fn parse_requires(&mut self) -> Result<Vec<String>, ParseError> {
-        self.next_token(); // consume 'requires'
+    /// Parse a field list clause: `requires { field1, field2, ... }`,
+    /// `produces { ... }` or `forwards { ... }`.
+    fn parse_field_list(&mut self) -> Result<Vec<String>, ParseError> {
+        self.next_token(); // consume the clause keyword
         self.expect(&TokenKind::LBrace)?;
         let mut fields = Vec::new();
         loop {
@@ -488,6 +536,8 @@ impl<'a> Parser<'a> {
             to
         };
 
+        let bound = self.parse_loop_bound()?;
+
         // Edge body is optional
         let (passes, description, end_span) = if self.peek_kind() == Some(&TokenKind::LBrace) {
             self.expect(&TokenKind::LBrace)?;
@@ -554,10 +604,43 @@ impl<'a> Parser<'a> {
             to,
             passes,
             description,
+            bound,
             span: start_span.merge(end_span),
         })
     }
 
+    /// Parse an optional `repeat N` or `max N` on the same line as an edge.
+    ///
+    /// `repeat` and `max` are contextual, so they stay usable as names elsewhere.
+    fn parse_loop_bound(&mut self) -> Result<Option<LoopBound>, ParseError> {
+        let make: fn(u32) -> LoopBound = match self.tokens.get(self.pos).map(|t| &t.kind) {
+            Some(TokenKind::Identifier(word)) if word == "repeat" => LoopBound::Repeat,
+            Some(TokenKind::Identifier(word)) if word == "max" => LoopBound::Max,
+            _ => return Ok(None),
+        };
+        let keyword = self.tokens[self.pos].span;
+        self.pos += 1;
+        match self.tokens.get(self.pos) {
+            Some(Token {
+                kind: TokenKind::Number(text),
+                span,
+            }) => match text.parse::<u32>() {
+                Ok(n) if n > 0 => {
+                    self.pos += 1;
+                    Ok(Some(make(n)))
+                }
+                _ => Err(ParseError {
+                    message: format!("loop bound must be a positive integer, found '{text}'"),
+                    span: *span,
+                }),
+            },
+            _ => Err(ParseError {
+                message: "expected a count after loop bound".to_owned(),
+                span: keyword,
+            }),
+        }
+    }
+
     /// Parse: `{ ident, ident, ... }`
     fn parse_identifier_list(&mut self) -> Result<Vec<String>, ParseError> {
         self.expect(&TokenKind::LBrace)?;
@@ -620,7 +703,9 @@ impl<'a> Parser<'a> {
         }
     }
 
-    /// Parse a value: string literal, identifier (as string), or boolean/null.
+    /// Parse a value: string literal, number, identifier (as string), or boolean/null.
+    ///
+    /// Numbers with a unit suffix (`30s`) are kept as strings.
     fn parse_value(&mut self) -> Result<Value, ParseError> {
         let tok = self.next_token();
         match tok {
@@ -628,6 +713,12 @@ impl<'a> Parser<'a> {
                 kind: TokenKind::StringLiteral(s),
                 ..
             }) => Ok(Value::String(s.clone())),
+            Some(Token {
+                kind: TokenKind::Number(text),
+                ..
+            }) => Ok(text
+                .parse()
+                .map_or_else(|_| Value::String(text.clone()), Value::Number)),
             Some(Token {
                 kind: TokenKind::Identifier(s),
                 ..
@@ -706,6 +797,8 @@ fn token_name(kind: &TokenKind) -> &'static str {
         TokenKind::But => "'but'",
         TokenKind::Passes => "'passes'",
         TokenKind::Requires => "'requires'",
+        TokenKind::Produces => "'produces'",
+        TokenKind::Forwards => "'forwards'",
         TokenKind::Tags => "'tags'",
         TokenKind::Config => "'config'",
         TokenKind::Import => "'import'",
@@ -719,8 +812,10 @@ fn token_name(kind: &TokenKind) -> &'static str {
         TokenKind::Colon => "':'",
         TokenKind::Comma => "','",
         TokenKind::Dot => "'.'",
+        TokenKind::Slash => "'/'",
         TokenKind::Newline => "newline",
         TokenKind::StringLiteral(_) => "string literal",
+        TokenKind::Number(_) => "number",
         TokenKind::Identifier(_) => "identifier",
         TokenKind::FreeText(_) => "text",
         TokenKind::Comment(_) => "comment",
@@ -907,16 +1002,52 @@ mod tests {
         let graph = parse_one(
             r#"graph G {
                 node A {
-                    tags [smoke, critical]
+                    tags [smoke, critical, 2fa]
                 }
             }"#,
         );
         assert_eq!(
             graph.nodes[0].tags,
-            vec![Tag("smoke".into()), Tag("critical".into())]
+            vec![
+                Tag("smoke".into()),
+                Tag("critical".into()),
+                Tag("2fa".into())
+            ]
         );
     }
 
+    #[test]
+    fn parses_key_value_and_namespaced_tags() {
+        let graph = parse_one(
+            r#"graph G {
+                tags [team/auth]
+                node A {
+                    tags [area: checkout, priority: 1, owner: "Jane Doe", team/auth/login, smoke]
+                }
+            }"#,
+        );
+        assert_eq!(graph.tags, vec![Tag("team/auth".into())]);
+        let tags: Vec<&str> = graph.nodes[0].tags.iter().map(|t| t.0.as_str()).collect();
+        assert_eq!(
+            tags,
+            vec![
+                "area:checkout",
+                "priority:1",
+                "owner:Jane Doe",
+                "team/auth/login",
+                "smoke"
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_dangling_tag_namespace_separator() {
+        let err = parse("graph G {\n  node A {\n    tags [team/]\n  }\n}").unwrap_err();
+        assert!(err.message.contains("after '/'"), "got: {}", err.message);
+        let err = parse("graph G {\n  node A {\n    tags [area:]\n  }\n}").unwrap_err();
+        assert!(err.message.contains("after ':'"), "got: {}", err.message);
+    }
+
     #[test]
     fn parses_node_with_requires() {
         let graph = parse_one(
@@ -929,6 +1060,30 @@ mod tests {
         assert_eq!(graph.nodes[0].requires, vec!["auth_token"]);
     }
 
+    #[test]
+    fn parses_node_with_produces() {
+        let graph = parse_one(
+            r#"graph G {
+                node Login {
+                    produces { auth_token, session_id }
+                }
+            }"#,
+        );
+        assert_eq!(graph.nodes[0].produces, vec!["auth_token", "session_id"]);
+    }
+
+    #[test]
+    fn parses_node_with_forwards() {
+        let graph = parse_one(
+            r#"graph G {
+                node Login {
+                    forwards { user_id, email }
+                }
+            }"#,
+        );
+        assert_eq!(graph.nodes[0].forwards, vec!["user_id", "email"]);
+    }
+
     #[test]
     fn parses_edge_simple() {
         let graph = parse_one(
@@ -943,6 +1098,49 @@ mod tests {
         assert_eq!(graph.edges[0].to, "B");
     }
 
+    #[test]
+    fn parses_edge_with_loop_bound() {
+        let graph = parse_one(
+            r#"graph G {
+                node A {}
+                node B {}
+                A -> B
+                B -> A repeat 3
+                B -> A max 5 { passes { token } }
+            }"#,
+        );
+        assert_eq!(graph.edges[0].bound, None);
+        assert_eq!(graph.edges[1].bound, Some(LoopBound::Repeat(3)));
+        assert_eq!(graph.edges[2].bound, Some(LoopBound::Max(5)));
+        assert_eq!(graph.edges[2].passes, vec!["token"]);
+    }
+
+    #[test]
+    fn loop_bound_words_stay_usable_as_names() {
+        let graph = parse_one(
+            r#"graph G {
+                node A { config { max: 10, repeat: "no" } }
+                node max {}
+                A -> max
+                max -> A repeat 2
+            }"#,
+        );
+        assert_eq!(graph.edges[0].to, "max");
+        assert_eq!(graph.edges[0].bound, None);
+        assert_eq!(graph.edges[1].from, "max");
+        assert_eq!(graph.edges[1].bound, Some(LoopBound::Repeat(2)));
+        let config = graph.nodes[0].config.as_ref().unwrap();
+        assert_eq!(config.fields[0], ("max".into(), Value::Number(10.0)));
+    }
+
+    #[test]
+    fn error_loop_bound_requires_positive_count() {
+        let err = parse("graph G { node A {} A -> A repeat 0 }").unwrap_err();
+        assert!(err.message.contains("positive integer, found '0'"));
+        let err = parse("graph G { node A {} A -> A max }").unwrap_err();
+        assert!(err.message.contains("expected a count after loop bound"));
+    }
+
     #[test]
     fn parses_edge_with_passes() {
         let graph = parse_one(
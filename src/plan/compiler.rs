@@ -1,10 +1,25 @@
+use std::collections::{HashMap, HashSet};
+
 use petgraph::Direction;
+use petgraph::algo::kosaraju_scc;
+use petgraph::graph::NodeIndex;
+use petgraph::visit::EdgeRef;
 
+use crate::graph::analysis::loop_bounds;
 use crate::graph::builder::TestGraph;
-use crate::graph::traversal::{TraversalStrategy, traverse};
+use crate::graph::paths::{common_prefix, path_graph};
+use crate::graph::schedule::node_weights;
+use crate::graph::traversal::{
+    Condensation, TraversalStrategy, path_coverage, random_topological, traverse,
+};
 use crate::ir::IrStepType;
-use crate::ir::params::BindingSource;
-use crate::plan::types::{InputEntry, ParameterEntry, PlanMetadata, PlanStep, StepEntry, TestPlan};
+use crate::ir::params::render_step_text;
+use crate::parser::ast::LoopBound;
+use crate::plan::types::{
+    CoverageEntry, InputEntry, IterationEntry, PLAN_SCHEMA_VERSION, ParameterEntry, PathEntry,
+    PlanMetadata, PlanStep, SharedPrefix, StepEntry, TestPlan,
+};
+use crate::util::duration::format_duration;
 
 /// Compile a test graph into an ordered test plan using topological sort.
 ///
@@ -24,45 +39,188 @@ pub fn compile_with_strategy(
     tg: &TestGraph,
     strategy: TraversalStrategy,
 ) -> Result<TestPlan, String> {
-    let sorted = traverse(tg, strategy)?;
+    let (sorted, uniform) = match strategy {
+        TraversalStrategy::Random { seed } => {
+            let order = random_topological(tg, seed)?;
+            (order.nodes, Some(order.uniform))
+        }
+        _ => (traverse(tg, strategy)?, None),
+    };
+    // dfs and bfs visit each node once; the other orders unroll loops.
+    let bounds = match strategy {
+        TraversalStrategy::DepthFirst | TraversalStrategy::BreadthFirst => HashMap::new(),
+        _ => loop_bounds(tg),
+    };
+    let mut plan = compile_order(tg, &sorted, strategy, &bounds);
+    plan.plan.uniform = uniform;
+    Ok(plan)
+}
 
-    let mut steps = Vec::with_capacity(sorted.len());
+/// Compile up to `count` random plans whose orders all differ.
+///
+/// Plans come from the seeds `seed`, `seed + 1`, and so on, skipping seeds
+/// that repeat an earlier order. Each plan records its seed, so it can be
+/// compiled again on its own. Graphs with fewer distinct orders than `count`
+/// give fewer plans: the search stops after ten seeds per requested plan.
+///
+/// # Errors
+///
+/// Returns an error string if the graph has a cycle without a loop edge.
+pub fn compile_permutations(
+    tg: &TestGraph,
+    seed: u64,
+    count: usize,
+) -> Result<Vec<TestPlan>, String> {
+    let mut seen = HashSet::new();
+    let mut plans = Vec::new();
+    for offset in 0..count.saturating_mul(10) as u64 {
+        if plans.len() == count {
+            break;
+        }
+        let strategy = TraversalStrategy::Random {
+            seed: seed.wrapping_add(offset),
+        };
+        let plan = compile_with_strategy(tg, strategy)?;
+        let order: Vec<String> = plan.steps.iter().map(|s| s.node.clone()).collect();
+        if seen.insert(order) {
+            plans.push(plan);
+        }
+    }
+    Ok(plans)
+}
 
-    for (order, &node_idx) in sorted.iter().enumerate() {
+/// Compile one plan per path, each from the graph of that path alone.
+///
+/// Each plan's metadata records its position among the paths, the longest
+/// prefix it shares with an earlier path, so shared setup can be spotted, and
+/// how much edge and node coverage it adds.
+pub fn compile_paths(
+    tg: &TestGraph,
+    paths: &[Vec<NodeIndex>],
+    strategy: TraversalStrategy,
+) -> Vec<TestPlan> {
+    let coverage = path_coverage(tg, paths);
+    paths
+        .iter()
+        .zip(coverage)
+        .enumerate()
+        .map(|(i, (path, cov))| {
+            let pg = path_graph(tg, path);
+            let mut plan = compile_order(&pg, &pg.node_indices, strategy, &HashMap::new());
+            // Plan totals describe the whole graph, not the single path
+            plan.plan.nodes_total = tg.graph.node_count();
+            plan.plan.edges_total = tg.graph.edge_count();
+            let shared_prefix = paths[..i]
+                .iter()
+                .enumerate()
+                .map(|(j, earlier)| (common_prefix(earlier, path), j))
+                .filter(|&(steps, _)| steps > 0)
+                .max_by_key(|&(steps, j)| (steps, std::cmp::Reverse(j)))
+                .map(|(steps, j)| SharedPrefix { with: j + 1, steps });
+            plan.plan.path = Some(PathEntry {
+                index: i + 1,
+                total: paths.len(),
+                shared_prefix,
+                coverage: CoverageEntry {
+                    edges_added: percent(cov.new_edges, cov.total_edges),
+                    nodes_added: percent(cov.new_nodes, cov.total_nodes),
+                    edges_covered: percent(cov.covered_edges, cov.total_edges),
+                    nodes_covered: percent(cov.covered_nodes, cov.total_nodes),
+                },
+            });
+            plan
+        })
+        .collect()
+}
+
+/// `part` as a percentage of `total`, to one decimal place.
+fn percent(part: usize, total: usize) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+    (part as f64 * 1000.0 / total as f64).round() / 10.0
+}
+
+/// Compile the nodes of `tg` in the given order.
+fn compile_order(
+    tg: &TestGraph,
+    sorted: &[NodeIndex],
+    strategy: TraversalStrategy,
+    bounds: &HashMap<NodeIndex, LoopBound>,
+) -> TestPlan {
+    let mut visits: HashMap<NodeIndex, usize> = HashMap::new();
+    let layering = Layering::new(tg, bounds);
+
+    let mut visited: Vec<(NodeIndex, Option<IterationEntry>, usize)> = sorted
+        .iter()
+        .map(|&node_idx| {
+            let iteration = bounds.get(&node_idx).map(|bound| {
+                let index = visits.entry(node_idx).or_default();
+                *index += 1;
+                IterationEntry {
+                    index: *index,
+                    count: bound.count() as usize,
+                    optional: matches!(bound, LoopBound::Max(_)) && *index > 1,
+                }
+            });
+            let stage = layering.stage(node_idx, iteration.map_or(1, |it| it.index));
+            (node_idx, iteration, stage)
+        })
+        .collect();
+    // Any order of stages is a valid topological order, so topological plans
+    // run stage by stage, keeping the sort order within each stage.
+    if strategy == TraversalStrategy::Topological {
+        visited.sort_by_key(|&(_, _, stage)| stage);
+    }
+
+    let mut steps = Vec::with_capacity(visited.len());
+    let weights = node_weights(tg);
+
+    for (order, (node_idx, iteration, stage)) in visited.into_iter().enumerate() {
         let node = &tg.graph[node_idx];
 
-        // Collect depends_on: names of nodes with edges leading into this one
-        let depends_on: Vec<String> = tg
+        // A loop edge feeds a pass from the previous pass of its source, so
+        // the first pass has nothing to wait for along it
+        let first_pass = iteration.is_none_or(|it| it.index == 1);
+        let incoming: Vec<_> = tg
             .graph
-            .neighbors_directed(node_idx, Direction::Incoming)
-            .map(|pred| tg.graph[pred].name.clone())
+            .edges_directed(node_idx, Direction::Incoming)
+            .filter(|e| !(first_pass && tg.loops.contains(&e.id())))
             .collect();
 
-        // Collect inputs from incoming edges
+        // Collect depends_on: names of nodes with edges leading into this one
+        let depends_on: Vec<String> = incoming
+            .iter()
+            .map(|e| tg.graph[e.source()].name.clone())
+            .collect();
+
+        // Collect inputs from incoming edges, in declaration order
         let mut inputs = Vec::new();
-        for edge_idx in tg.graph.edge_indices() {
-            let (_, target) = tg.graph.edge_endpoints(edge_idx).unwrap();
-            if target == node_idx {
-                let edge = &tg.graph[edge_idx];
-                for field in &edge.passes {
-                    inputs.push(InputEntry {
-                        field: field.clone(),
-                        from: edge.from.clone(),
-                    });
-                }
+        let mut in_edges: Vec<_> = incoming.iter().map(|e| e.id()).collect();
+        in_edges.sort_unstable();
+        for edge_idx in in_edges {
+            let edge = &tg.graph[edge_idx];
+            for field in &edge.passes {
+                inputs.push(InputEntry {
+                    field: field.clone(),
+                    from: edge.from.clone(),
+                });
             }
         }
 
-        // Collect outputs: all fields this node passes via outgoing edges
-        let mut outputs = Vec::new();
-        for edge_idx in tg.graph.edge_indices() {
-            let (source, _) = tg.graph.edge_endpoints(edge_idx).unwrap();
-            if source == node_idx {
-                let edge = &tg.graph[edge_idx];
-                for field in &edge.passes {
-                    if !outputs.contains(field) {
-                        outputs.push(field.clone());
-                    }
+        // Collect outputs: declared `produces` first, then any other
+        // fields this node passes via outgoing edges
+        let mut outputs = node.produces.clone();
+        let mut out_edges: Vec<_> = tg
+            .graph
+            .edges_directed(node_idx, Direction::Outgoing)
+            .map(|e| e.id())
+            .collect();
+        out_edges.sort_unstable();
+        for edge_idx in out_edges {
+            for field in &tg.graph[edge_idx].passes {
+                if !outputs.contains(field) {
+                    outputs.push(field.clone());
                 }
             }
         }
@@ -80,16 +238,15 @@ pub fn compile_with_strategy(
                 .map(|p| ParameterEntry {
                     name: p.name.clone(),
                     value: p.value.clone(),
-                    source: match &p.source {
-                        BindingSource::Fixture(name) => format!("fixture:{name}"),
-                        BindingSource::EdgeData(name) => format!("edge:{name}"),
-                        BindingSource::Unresolved => "unresolved".into(),
-                    },
+                    source: p.source.to_string(),
                 })
                 .collect();
+            let rendered_text = (!step.parameters.is_empty())
+                .then(|| render_step_text(&step.text, &step.parameters));
             let entry = StepEntry {
                 step_type: step_type_str(&step.step_type),
                 text: step.text.clone(),
+                rendered_text,
                 data: step.data.clone(),
                 parameters,
             };
@@ -117,6 +274,7 @@ pub fn compile_with_strategy(
 
         steps.push(PlanStep {
             order: order + 1,
+            stage,
             node: node.name.clone(),
             description: node.description.clone(),
             tags: node.tags.clone(),
@@ -126,18 +284,100 @@ pub fn compile_with_strategy(
             assertions,
             inputs,
             outputs,
+            iteration,
+            included_for: None,
+            estimated_duration: weights[node_idx.index()].duration.map(format_duration),
+            cumulative_duration: None,
         });
     }
 
-    Ok(TestPlan {
+    let mut plan = TestPlan {
+        schema_version: PLAN_SCHEMA_VERSION,
         plan: PlanMetadata {
             name: tg.name.clone(),
             traversal: strategy.to_string(),
             nodes_total: tg.graph.node_count(),
             edges_total: tg.graph.edge_count(),
+            stages: Vec::new(),
+            critical_path: 0,
+            path: None,
+            seed: match strategy {
+                TraversalStrategy::Random { seed } => Some(seed),
+                _ => None,
+            },
+            uniform: None,
+            estimated_duration: None,
+            budget: None,
+            shard: None,
+            graphs: Vec::new(),
         },
         steps,
-    })
+    };
+    plan.recount_stages();
+    plan.recount_durations();
+    plan
+}
+
+/// Longest-path layering of the unrolled graph, independent of plan order.
+///
+/// Pass `i` of a loop node comes after pass `i` of its predecessors in the
+/// same loop, after the last pass of predecessors outside it, and, through
+/// the loop edge, after pass `i - 1` of the loop edge's source.
+struct Layering {
+    stages: HashMap<(NodeIndex, usize), usize>,
+}
+
+impl Layering {
+    fn new(tg: &TestGraph, bounds: &HashMap<NodeIndex, LoopBound>) -> Self {
+        // Within a component, same-pass predecessors come first in a plain
+        // topological order. Only dfs and bfs plan cycles without a loop edge;
+        // those fall back to graph order, and a predecessor not yet layered
+        // counts as stage 0.
+        let order = Condensation::of(tg)
+            .map_or_else(|_| tg.graph.node_indices().collect(), |cond| cond.order);
+        let mut position = vec![0; tg.graph.node_count()];
+        for (i, n) in order.iter().enumerate() {
+            position[n.index()] = i;
+        }
+        let mut component = vec![usize::MAX; tg.graph.node_count()];
+        let mut stages = HashMap::new();
+        // kosaraju_scc lists components after everything they lead to, and
+        // unlike tarjan_scc does not recurse once per chain link
+        for (c, mut members) in kosaraju_scc(&tg.graph).into_iter().rev().enumerate() {
+            members.sort_by_key(|n| position[n.index()]);
+            for n in &members {
+                component[n.index()] = c;
+            }
+            let passes = bounds.get(&members[0]).map_or(1, |b| b.count() as usize);
+            for pass in 1..=passes {
+                for &node in &members {
+                    let mut latest = 0;
+                    for edge in tg.graph.edges_directed(node, Direction::Incoming) {
+                        let pred = edge.source();
+                        let pred_pass = if tg.loops.contains(&edge.id()) {
+                            if pass == 1 {
+                                continue;
+                            }
+                            pass - 1
+                        } else if component[pred.index()] == c {
+                            pass
+                        } else {
+                            bounds.get(&pred).map_or(1, |b| b.count() as usize)
+                        };
+                        latest = latest.max(stages.get(&(pred, pred_pass)).copied().unwrap_or(0));
+                    }
+                    stages.insert((node, pass), latest + 1);
+                }
+            }
+        }
+        Self { stages }
+    }
+
+    /// 1-based stage of the given pass of `node`: one more than the latest
+    /// stage among the steps it depends on.
+    fn stage(&self, node: NodeIndex, pass: usize) -> usize {
+        self.stages.get(&(node, pass)).copied().unwrap_or(1)
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -298,6 +538,21 @@ mod tests {
         assert_eq!(a_step.outputs, vec!["token"]);
     }
 
+    #[test]
+    fn plan_includes_declared_outputs_on_leaf_nodes() {
+        let plan = compile_one(
+            r#"graph G {
+                node A { produces { token, session } }
+                node B { produces { receipt } }
+                A -> B { passes { session } }
+            }"#,
+        );
+        let a_step = plan.steps.iter().find(|s| s.node == "A").unwrap();
+        assert_eq!(a_step.outputs, vec!["token", "session"]);
+        let b_step = plan.steps.iter().find(|s| s.node == "B").unwrap();
+        assert_eq!(b_step.outputs, vec!["receipt"]);
+    }
+
     #[test]
     fn plan_step_order_is_deterministic() {
         let input = r#"graph G {
@@ -359,8 +614,8 @@ mod tests {
             }"#,
         );
         let param = &plan.steps[0].preconditions[0].parameters[0];
-        // Source should indicate edge data (from inline data block)
-        assert!(!param.source.is_empty());
+        // Bound from the step's own inline data block
+        assert_eq!(param.source, "step");
     }
 
     #[test]
@@ -375,4 +630,392 @@ mod tests {
         let precond = &plan.steps[0].preconditions[0];
         assert!(precond.parameters.is_empty());
     }
+
+    #[test]
+    fn plan_step_renders_bound_parameters() {
+        let plan = compile_one(
+            r#"graph G {
+                node A {
+                    given a user with email <email> and <password> {
+                        email: "a@b.com"
+                    }
+                }
+            }"#,
+        );
+        let precond = &plan.steps[0].preconditions[0];
+        assert_eq!(precond.text, "a user with email <email> and <password>");
+        assert_eq!(
+            precond.rendered_text.as_deref(),
+            Some("a user with email a@b.com and <password: UNRESOLVED>")
+        );
+    }
+
+    #[test]
+    fn plan_step_without_parameters_has_no_rendered_text() {
+        let plan = compile_one(
+            r#"graph G {
+                node A {
+                    given a user
+                }
+            }"#,
+        );
+        assert!(plan.steps[0].preconditions[0].rendered_text.is_none());
+    }
+
+    #[test]
+    fn plan_unrolls_bounded_loops_with_iterations() {
+        let plan = compile_one(
+            r#"graph G {
+                node Submit {}
+                node Poll {}
+                node Done {}
+                Submit -> Poll
+                Poll -> Submit max 2
+                Poll -> Done
+            }"#,
+        );
+        let names: Vec<&str> = plan.steps.iter().map(|s| s.node.as_str()).collect();
+        assert_eq!(names, vec!["Submit", "Poll", "Submit", "Poll", "Done"]);
+        let orders: Vec<usize> = plan.steps.iter().map(|s| s.order).collect();
+        assert_eq!(orders, vec![1, 2, 3, 4, 5]);
+        assert_eq!(
+            plan.steps[3].iteration,
+            Some(IterationEntry {
+                index: 2,
+                count: 2,
+                optional: true,
+            })
+        );
+        assert!(!plan.steps[1].iteration.unwrap().optional);
+        assert!(plan.steps[4].iteration.is_none());
+    }
+
+    #[test]
+    fn plan_groups_independent_steps_into_stages() {
+        let plan = compile_one(
+            r#"graph G {
+                node A {}
+                node B {}
+                node C {}
+                node D {}
+                node E {}
+                A -> B
+                A -> C
+                B -> D
+                C -> D
+                A -> D
+            }"#,
+        );
+        let stages: Vec<(&str, usize)> = plan
+            .steps
+            .iter()
+            .map(|s| (s.node.as_str(), s.stage))
+            .collect();
+        for expected in [("A", 1), ("E", 1), ("B", 2), ("C", 2), ("D", 3)] {
+            assert!(stages.contains(&expected), "{expected:?} in {stages:?}");
+        }
+        assert!(
+            stages.windows(2).all(|w| w[0].1 <= w[1].1),
+            "steps should run stage by stage: {stages:?}"
+        );
+        assert_eq!(plan.plan.stages, vec![2, 2, 1]);
+        assert_eq!(plan.plan.critical_path, 3);
+    }
+
+    #[test]
+    fn stages_follow_dependencies_not_visit_order() {
+        let graphs = parse(
+            r#"graph G {
+                node A {}
+                node B {}
+                node C {}
+                A -> C
+                A -> B
+                B -> C
+            }"#,
+        )
+        .unwrap();
+        let tg = build(&lower(&graphs[0]).unwrap());
+        for strategy in [
+            TraversalStrategy::DepthFirst,
+            TraversalStrategy::BreadthFirst,
+        ] {
+            let plan = compile_with_strategy(&tg, strategy).unwrap();
+            let stage = |name: &str| plan.steps.iter().find(|s| s.node == name).unwrap().stage;
+            assert_eq!(
+                (stage("A"), stage("B"), stage("C")),
+                (1, 2, 3),
+                "{strategy}"
+            );
+            assert_eq!(plan.plan.stages, vec![1, 1, 1]);
+            assert_eq!(plan.plan.critical_path, 3);
+        }
+    }
+
+    #[test]
+    fn stages_of_long_chains() {
+        use std::fmt::Write;
+
+        let mut input = String::from("graph Chain {\n");
+        for i in 0..10_000 {
+            writeln!(input, "node N{i} {{}}").unwrap();
+        }
+        for i in 1..10_000 {
+            writeln!(input, "N{} -> N{i}", i - 1).unwrap();
+        }
+        input.push('}');
+        let plan = compile_one(&input);
+        assert_eq!(plan.plan.critical_path, 10_000);
+        assert_eq!(plan.steps[9_999].stage, 10_000);
+    }
+
+    #[test]
+    fn plan_stages_follow_loop_iterations() {
+        let plan = compile_one(
+            r#"graph G {
+                node Submit {}
+                node Poll {}
+                node Done {}
+                Submit -> Poll
+                Poll -> Submit repeat 2
+                Poll -> Done
+            }"#,
+        );
+        let stages: Vec<usize> = plan.steps.iter().map(|s| s.stage).collect();
+        assert_eq!(stages, vec![1, 2, 3, 4, 5]);
+        assert_eq!(plan.plan.critical_path, 5);
+    }
+
+    #[test]
+    fn loop_dependencies_refer_to_earlier_steps() {
+        let graphs = parse(
+            r#"graph G {
+                node Submit {}
+                node Poll {}
+                node Done {}
+                Submit -> Poll
+                Poll -> Submit repeat 2
+                Poll -> Done
+            }"#,
+        )
+        .unwrap();
+        let tg = build(&lower(&graphs[0]).unwrap());
+        for strategy in [
+            TraversalStrategy::Topological,
+            TraversalStrategy::DepthFirst,
+            TraversalStrategy::BreadthFirst,
+            TraversalStrategy::Random { seed: 7 },
+        ] {
+            let plan = compile_with_strategy(&tg, strategy).unwrap();
+            for (i, step) in plan.steps.iter().enumerate() {
+                for dep in &step.depends_on {
+                    assert!(
+                        plan.steps[..i].iter().any(|s| &s.node == dep),
+                        "{strategy}: step {} ({}) depends on {dep}, which has not run",
+                        step.order,
+                        step.node
+                    );
+                }
+            }
+        }
+        let plan = compile_with_strategy(&tg, TraversalStrategy::Topological).unwrap();
+        assert!(plan.steps[0].depends_on.is_empty());
+        assert_eq!(plan.steps[2].node, "Submit");
+        assert_eq!(plan.steps[2].depends_on, vec!["Poll"]);
+    }
+
+    #[test]
+    fn random_and_weighted_unroll_loops_like_topological() {
+        let graphs = parse(
+            r#"graph G {
+                node Submit {}
+                node Poll {}
+                node Done {}
+                node Audit {}
+                Submit -> Poll
+                Poll -> Submit repeat 2
+                Poll -> Done
+            }"#,
+        )
+        .unwrap();
+        let tg = build(&lower(&graphs[0]).unwrap());
+        let steps = |strategy| {
+            let plan = compile_with_strategy(&tg, strategy).unwrap();
+            let mut steps: Vec<(String, Option<usize>)> = plan
+                .steps
+                .iter()
+                .map(|s| (s.node.clone(), s.iteration.map(|it| it.index)))
+                .collect();
+            steps.sort();
+            steps
+        };
+        let expected = steps(TraversalStrategy::Topological);
+        assert_eq!(expected.len(), 6);
+        assert_eq!(steps(TraversalStrategy::Weighted), expected);
+        for seed in 0..5 {
+            assert_eq!(steps(TraversalStrategy::Random { seed }), expected);
+        }
+    }
+
+    #[test]
+    fn non_topological_strategies_keep_their_order() {
+        let graphs = parse(
+            r#"graph G {
+                node A {}
+                node B {}
+                node C {}
+                A -> B
+                B -> C
+                A -> C
+            }"#,
+        )
+        .unwrap();
+        let tg = build(&lower(&graphs[0]).unwrap());
+        let order = traverse(&tg, TraversalStrategy::DepthFirst).unwrap();
+        let plan = compile_with_strategy(&tg, TraversalStrategy::DepthFirst).unwrap();
+        let names: Vec<&str> = plan.steps.iter().map(|s| s.node.as_str()).collect();
+        let expected: Vec<&str> = order.iter().map(|&i| tg.graph[i].name.as_str()).collect();
+        assert_eq!(names, expected);
+        assert_eq!(plan.steps[0].stage, 1);
+        assert_eq!(plan.plan.stages.iter().sum::<usize>(), 3);
+    }
+
+    #[test]
+    fn permutations_have_distinct_orders_and_their_own_seeds() {
+        let graphs = parse(
+            r#"graph G {
+                node A {}
+                node B {}
+                node C {}
+                node D {}
+                A -> B
+                A -> C
+            }"#,
+        )
+        .unwrap();
+        let tg = build(&lower(&graphs[0]).unwrap());
+        let plans = compile_permutations(&tg, 40, 5).unwrap();
+        assert_eq!(plans.len(), 5);
+        assert_eq!(plans[0].plan.seed, Some(40));
+        assert_eq!(plans[0].plan.traversal, "random");
+        assert_eq!(plans[0].plan.title(), "G (seed 40)");
+        let orders: HashSet<Vec<&str>> = plans
+            .iter()
+            .map(|p| p.steps.iter().map(|s| s.node.as_str()).collect())
+            .collect();
+        assert_eq!(orders.len(), 5);
+        for plan in &plans {
+            let seed = plan.plan.seed.unwrap();
+            let again = compile_with_strategy(&tg, TraversalStrategy::Random { seed }).unwrap();
+            assert_eq!(&again, plan);
+        }
+
+        // A chain has only one order
+        let graphs = parse("graph G {\n node A {}\n node B {}\n A -> B\n}").unwrap();
+        let tg = build(&lower(&graphs[0]).unwrap());
+        assert_eq!(compile_permutations(&tg, 0, 3).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn weighted_plans_carry_estimated_durations() {
+        let graphs = parse(
+            r#"graph G {
+                node Login { config { estimated_duration: "20s" } }
+                node Search { config { estimated_duration: "1m" } }
+                node Pay { config { priority: 5, estimated_duration: "40s" } }
+                node Audit {}
+                Login -> Search
+                Login -> Pay
+                Pay -> Audit
+            }"#,
+        )
+        .unwrap();
+        let tg = build(&lower(&graphs[0]).unwrap());
+        let plan = compile_with_strategy(&tg, TraversalStrategy::Weighted).unwrap();
+        let steps: Vec<(&str, Option<&str>, Option<&str>)> = plan
+            .steps
+            .iter()
+            .map(|s| {
+                (
+                    s.node.as_str(),
+                    s.estimated_duration.as_deref(),
+                    s.cumulative_duration.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            steps,
+            vec![
+                ("Login", Some("20s"), Some("20s")),
+                ("Pay", Some("40s"), Some("1m")),
+                ("Audit", None, Some("1m")),
+                ("Search", Some("1m"), Some("2m")),
+            ]
+        );
+        assert_eq!(plan.plan.traversal, "weighted");
+        assert_eq!(plan.plan.estimated_duration.as_deref(), Some("2m"));
+
+        // Without any estimates there is nothing to report
+        let plan = compile_one("graph G { node A {} }");
+        assert_eq!(plan.plan.estimated_duration, None);
+        assert_eq!(plan.steps[0].cumulative_duration, None);
+    }
+
+    #[test]
+    fn plan_rejects_unbounded_cycles() {
+        let graphs = parse(
+            r#"graph G {
+                node A {}
+                node B {}
+                A -> B
+                B -> A
+            }"#,
+        )
+        .unwrap();
+        let tg = build(&lower(&graphs[0]).unwrap());
+        let err = compile(&tg).unwrap_err();
+        assert!(err.contains("unbounded cycle A -> B -> A"), "got: {err}");
+    }
+
+    #[test]
+    fn compile_paths_marks_shared_prefixes() {
+        use crate::graph::paths::{PathLimits, all_paths};
+
+        let graphs = parse(
+            r#"graph G {
+                node A {}
+                node B {}
+                node C {}
+                node D {}
+                A -> B
+                B -> C
+                B -> D
+                A -> D
+            }"#,
+        )
+        .unwrap();
+        let tg = build(&lower(&graphs[0]).unwrap());
+        let set = all_paths(&tg, PathLimits::default());
+        let plans = compile_paths(&tg, &set.paths, TraversalStrategy::Paths);
+        assert_eq!(plans.len(), 3);
+
+        let nodes: Vec<Vec<&str>> = plans
+            .iter()
+            .map(|p| p.steps.iter().map(|s| s.node.as_str()).collect())
+            .collect();
+        assert_eq!(
+            nodes,
+            vec![vec!["A", "B", "C"], vec!["A", "B", "D"], vec!["A", "D"]]
+        );
+        assert_eq!(plans[1].steps[2].depends_on, vec!["B"]);
+
+        let path = plans[1].plan.path.unwrap();
+        assert_eq!((path.index, path.total), (2, 3));
+        assert_eq!(path.shared_prefix, Some(SharedPrefix { with: 1, steps: 2 }));
+        assert_eq!(
+            plans[2].plan.path.unwrap().shared_prefix,
+            Some(SharedPrefix { with: 1, steps: 1 })
+        );
+        assert!(plans[0].plan.path.unwrap().shared_prefix.is_none());
+    }
 }
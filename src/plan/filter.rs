@@ -1,13 +1,28 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
 use petgraph::graph::NodeIndex;
 
 use crate::graph::builder::TestGraph;
-use crate::plan::types::TestPlan;
+use crate::graph::query::CompareOp;
+use crate::ir::tag::{split_tag, tag_matches};
+use crate::plan::types::{Inclusion, TestPlan};
 
 /// A predicate for filtering nodes by tags.
 #[derive(Debug, Clone, PartialEq)]
 pub enum TagPredicate {
+    /// Some tag matches the pattern (see [`tag_matches`]).
     Include(String),
+    /// No tag matches the pattern.
     Exclude(String),
+    /// Some `key:value` tag with this key has a value that compares true.
+    Compare {
+        key: String,
+        op: CompareOp,
+        value: String,
+    },
+    Not(Box<TagPredicate>),
     And(Vec<TagPredicate>),
     Or(Vec<TagPredicate>),
 }
@@ -15,8 +30,13 @@ pub enum TagPredicate {
 impl TagPredicate {
     fn matches(&self, tags: &[String]) -> bool {
         match self {
-            Self::Include(tag) => tags.iter().any(|t| t == tag),
-            Self::Exclude(tag) => !tags.iter().any(|t| t == tag),
+            Self::Include(pattern) => tags.iter().any(|t| tag_matches(t, pattern)),
+            Self::Exclude(pattern) => !tags.iter().any(|t| tag_matches(t, pattern)),
+            Self::Compare { key, op, value } => tags.iter().any(|t| match split_tag(t) {
+                (Some(k), actual) => k == key && op.holds(actual, value),
+                (None, _) => false,
+            }),
+            Self::Not(pred) => !pred.matches(tags),
             Self::And(preds) => preds.iter().all(|p| p.matches(tags)),
             Self::Or(preds) => preds.iter().any(|p| p.matches(tags)),
         }
@@ -30,6 +50,11 @@ impl TagPredicate {
 /// - Comma-separated (OR): `"smoke,critical"` → `Or([Include("smoke"), Include("critical")])`
 /// - NOT prefix: `"NOT slow"` → `Exclude("slow")`
 /// - AND NOT: `"smoke AND NOT slow"` → `And([Include("smoke"), Exclude("slow")])`
+/// - Key-value comparison: `"priority>=2"` → `Compare { key: "priority", op: Ge, value: "2" }`,
+///   also with `=`, `!=`, `<`, `<=` and `>`
+///
+/// Tag names match namespaced children and key-value keys, so `team/auth`
+/// selects `team/auth/login` and `area` selects `area:checkout`.
 ///
 /// # Errors
 ///
@@ -70,10 +95,40 @@ fn parse_single(input: &str) -> Result<TagPredicate, String> {
         if tag.is_empty() {
             return Err("empty tag after NOT".to_owned());
         }
-        Ok(TagPredicate::Exclude(tag.to_owned()))
+        Ok(match parse_comparison(tag)? {
+            Some(compare) => TagPredicate::Not(Box::new(compare)),
+            None => TagPredicate::Exclude(tag.to_owned()),
+        })
     } else {
-        Ok(TagPredicate::Include(input.to_owned()))
+        Ok(parse_comparison(input)?.unwrap_or_else(|| TagPredicate::Include(input.to_owned())))
+    }
+}
+
+/// Parse `key<op>value`, or `None` if the input has no comparison operator.
+fn parse_comparison(input: &str) -> Result<Option<TagPredicate>, String> {
+    let Some(at) = input.find(['=', '!', '<', '>']) else {
+        return Ok(None);
+    };
+    let rest = &input[at..];
+    let (op, len) = match rest.as_bytes() {
+        [b'!', b'=', ..] => (CompareOp::Ne, 2),
+        [b'<', b'=', ..] => (CompareOp::Le, 2),
+        [b'>', b'=', ..] => (CompareOp::Ge, 2),
+        [b'<', ..] => (CompareOp::Lt, 1),
+        [b'>', ..] => (CompareOp::Gt, 1),
+        [b'=', ..] => (CompareOp::Eq, 1),
+        _ => return Err(format!("expected '=' after '!' in '{input}'")),
+    };
+    let key = input[..at].trim();
+    let value = rest[len..].trim();
+    if key.is_empty() || value.is_empty() {
+        return Err(format!("incomplete tag comparison '{input}'"));
     }
+    Ok(Some(TagPredicate::Compare {
+        key: key.to_owned(),
+        op,
+        value: value.to_owned(),
+    }))
 }
 
 /// Return node indices from the graph whose tags match the predicate.
@@ -88,23 +143,137 @@ pub fn filter_nodes(tg: &TestGraph, predicate: &TagPredicate) -> Vec<NodeIndex>
         .collect()
 }
 
-/// Filter a compiled plan, keeping only steps whose tags match the predicate.
-pub fn filter_plan(plan: &TestPlan, predicate: &TagPredicate) -> TestPlan {
-    let filtered_steps: Vec<_> = plan
+/// How tag filtering treats the dependencies of the steps it keeps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FilterMode {
+    /// Drop non-matching steps and leave references to them in place.
+    #[default]
+    Drop,
+    /// Also keep every step a matching step depends on, directly or
+    /// transitively, marked `included_for: dependency`.
+    KeepDeps,
+    /// Drop non-matching steps and remove references to them from the
+    /// `depends_on` and `inputs` of the steps that remain.
+    Prune,
+}
+
+impl fmt::Display for FilterMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Drop => write!(f, "drop"),
+            Self::KeepDeps => write!(f, "keep-deps"),
+            Self::Prune => write!(f, "prune"),
+        }
+    }
+}
+
+impl FromStr for FilterMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "drop" => Ok(Self::Drop),
+            "keep-deps" => Ok(Self::KeepDeps),
+            "prune" => Ok(Self::Prune),
+            other => Err(format!(
+                "unknown filter mode '{other}' (expected: drop, keep-deps, prune)"
+            )),
+        }
+    }
+}
+
+/// A filtered plan, with the references [`FilterMode::Prune`] removed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilteredPlan {
+    pub plan: TestPlan,
+    /// One message per removed reference, e.g.
+    /// `node 'Login': dropped dependency on 'Register'`.
+    pub pruned: Vec<String>,
+}
+
+/// Filter a compiled plan, keeping only steps whose tags match the predicate,
+/// and handling their dependencies according to `mode`.
+///
+/// `tg` is the graph the plan was compiled from. The plan's `nodes_total` and
+/// `edges_total` are recounted from it for the nodes that remain.
+pub fn filter_plan(
+    plan: &TestPlan,
+    tg: &TestGraph,
+    predicate: &TagPredicate,
+    mode: FilterMode,
+) -> FilteredPlan {
+    let mut kept: HashSet<&str> = plan
         .steps
         .iter()
         .filter(|step| predicate.matches(&step.tags))
-        .cloned()
+        .map(|step| step.node.as_str())
         .collect();
+    let selected = kept.clone();
+    if mode == FilterMode::KeepDeps {
+        let mut pending: Vec<&str> = kept.iter().copied().collect();
+        while let Some(name) = pending.pop() {
+            let deps = plan
+                .steps
+                .iter()
+                .filter(|step| step.node == name)
+                .flat_map(|step| &step.depends_on);
+            for dep in deps {
+                if kept.insert(dep.as_str()) {
+                    pending.push(dep.as_str());
+                }
+            }
+        }
+    }
 
     let mut result = plan.clone();
-    result.steps = filtered_steps;
-    // Re-number steps
+    result
+        .steps
+        .retain(|step| kept.contains(step.node.as_str()));
+    let mut pruned = Vec::new();
     for (i, step) in result.steps.iter_mut().enumerate() {
+        // Re-number steps
         step.order = i + 1;
+        if !selected.contains(step.node.as_str()) {
+            step.included_for = Some(Inclusion::Dependency);
+        }
+        if mode == FilterMode::Prune {
+            let node = step.node.clone();
+            step.depends_on.retain(|dep| {
+                let keep = kept.contains(dep.as_str());
+                if !keep {
+                    pruned.push(format!("node '{node}': dropped dependency on '{dep}'"));
+                }
+                keep
+            });
+            step.inputs.retain(|input| {
+                let keep = kept.contains(input.from.as_str());
+                if !keep {
+                    pruned.push(format!(
+                        "node '{node}': dropped input '{}' from '{}'",
+                        input.field, input.from
+                    ));
+                }
+                keep
+            });
+        }
+    }
+
+    let in_plan = |idx: NodeIndex| kept.contains(tg.graph[idx].name.as_str());
+    result.plan.nodes_total = tg.node_indices.iter().filter(|&&idx| in_plan(idx)).count();
+    result.plan.edges_total = tg
+        .graph
+        .edge_indices()
+        .filter(|&e| {
+            let (from, to) = tg.graph.edge_endpoints(e).unwrap();
+            in_plan(from) && in_plan(to)
+        })
+        .count();
+    result.recount_stages();
+    result.recount_durations();
+    FilteredPlan {
+        plan: result,
+        pruned,
     }
-    result.plan.nodes_total = result.steps.len();
-    result
 }
 
 #[cfg(test)]
@@ -159,6 +328,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_filter_comparisons() {
+        assert_eq!(
+            parse_filter("priority>=2").unwrap(),
+            TagPredicate::Compare {
+                key: "priority".into(),
+                op: CompareOp::Ge,
+                value: "2".into(),
+            }
+        );
+        assert_eq!(
+            parse_filter("NOT area = checkout").unwrap(),
+            TagPredicate::Not(Box::new(TagPredicate::Compare {
+                key: "area".into(),
+                op: CompareOp::Eq,
+                value: "checkout".into(),
+            }))
+        );
+        assert!(parse_filter("priority>=").is_err());
+        assert!(parse_filter("priority!2").is_err());
+    }
+
     // ── filter_nodes ───────────────────────────────────────
 
     #[test]
@@ -193,6 +384,32 @@ mod tests {
         assert!(!names.contains(&"B"));
     }
 
+    #[test]
+    fn filter_nodes_by_key_value_and_namespace() {
+        let tg = build_one(
+            r#"graph G {
+                tags [team/shop]
+                node A { tags [priority: 1, team/shop/cart] }
+                node B { tags [priority: 3, area: checkout] }
+                node C { tags [area: search] }
+            }"#,
+        );
+        let names = |filter: &str| -> Vec<String> {
+            let pred = parse_filter(filter).unwrap();
+            filter_nodes(&tg, &pred)
+                .iter()
+                .map(|i| tg.graph[*i].name.clone())
+                .collect()
+        };
+        assert_eq!(names("priority>=2"), vec!["B"]);
+        assert_eq!(names("priority<2"), vec!["A"]);
+        assert_eq!(names("area=checkout"), vec!["B"]);
+        assert_eq!(names("area"), vec!["B", "C"]);
+        assert_eq!(names("team/shop/cart"), vec!["A"]);
+        assert_eq!(names("team"), vec!["A", "B", "C"]);
+        assert_eq!(names("team/shop AND NOT priority>1"), vec!["A", "C"]);
+    }
+
     #[test]
     fn filter_nodes_no_predicate_returns_all() {
         let tg = build_one(
@@ -223,7 +440,7 @@ mod tests {
         );
         let plan = compile(&tg).unwrap();
         let pred = TagPredicate::Include("smoke".into());
-        let filtered = filter_plan(&plan, &pred);
+        let filtered = filter_plan(&plan, &tg, &pred, FilterMode::Drop).plan;
         assert_eq!(filtered.steps.len(), 2);
         assert!(
             filtered
@@ -233,6 +450,32 @@ mod tests {
         );
     }
 
+    #[test]
+    fn filter_plan_recounts_stages() {
+        let tg = build_one(
+            r#"graph G {
+                node A { tags [slow] }
+                node B { tags [smoke] }
+                node C { tags [smoke] }
+                A -> B
+                B -> C
+            }"#,
+        );
+        let plan = compile(&tg).unwrap();
+        assert_eq!(plan.plan.stages, vec![1, 1, 1]);
+        let filtered = filter_plan(
+            &plan,
+            &tg,
+            &TagPredicate::Include("smoke".into()),
+            FilterMode::Drop,
+        )
+        .plan;
+        let stages: Vec<usize> = filtered.steps.iter().map(|s| s.stage).collect();
+        assert_eq!(stages, vec![1, 2]);
+        assert_eq!(filtered.plan.stages, vec![1, 1]);
+        assert_eq!(filtered.plan.critical_path, 2);
+    }
+
     #[test]
     fn filter_plan_preserves_order() {
         let tg = build_one(
@@ -244,7 +487,7 @@ mod tests {
         );
         let plan = compile(&tg).unwrap();
         let pred = TagPredicate::Include("smoke".into());
-        let filtered = filter_plan(&plan, &pred);
+        let filtered = filter_plan(&plan, &tg, &pred, FilterMode::Drop).plan;
         assert_eq!(filtered.steps[0].order, 1);
         assert_eq!(filtered.steps[1].order, 2);
     }
@@ -260,7 +503,82 @@ mod tests {
         );
         let plan = compile(&tg).unwrap();
         let pred = TagPredicate::Include("smoke".into());
-        let filtered = filter_plan(&plan, &pred);
+        let filtered = filter_plan(&plan, &tg, &pred, FilterMode::Drop).plan;
         assert_eq!(filtered.plan.nodes_total, 2);
     }
+
+    // ── filter modes ───────────────────────────────────────
+
+    const SIGNUP: &str = r#"graph G {
+                node Register { tags [setup] }
+                node Verify { tags [setup] }
+                node Login { tags [smoke] }
+                node Audit { tags [slow] }
+                Register -> Verify { passes { user_id } }
+                Verify -> Login { passes { user_id } }
+                Register -> Audit
+            }"#;
+
+    fn smoke(tg: &TestGraph, mode: FilterMode) -> FilteredPlan {
+        let plan = compile(tg).unwrap();
+        filter_plan(&plan, tg, &TagPredicate::Include("smoke".into()), mode)
+    }
+
+    #[test]
+    fn drop_mode_leaves_dangling_references() {
+        let tg = build_one(SIGNUP);
+        let filtered = smoke(&tg, FilterMode::Drop);
+        assert_eq!(filtered.plan.steps.len(), 1);
+        assert_eq!(filtered.plan.steps[0].depends_on, vec!["Verify"]);
+        assert!(filtered.pruned.is_empty());
+        assert_eq!(filtered.plan.plan.nodes_total, 1);
+        assert_eq!(filtered.plan.plan.edges_total, 0);
+    }
+
+    #[test]
+    fn keep_deps_mode_keeps_ancestors_as_dependencies() {
+        let tg = build_one(SIGNUP);
+        let filtered = smoke(&tg, FilterMode::KeepDeps);
+        let steps: Vec<(&str, Option<Inclusion>)> = filtered
+            .plan
+            .steps
+            .iter()
+            .map(|s| (s.node.as_str(), s.included_for))
+            .collect();
+        assert_eq!(
+            steps,
+            vec![
+                ("Register", Some(Inclusion::Dependency)),
+                ("Verify", Some(Inclusion::Dependency)),
+                ("Login", None),
+            ]
+        );
+        assert_eq!(filtered.plan.plan.nodes_total, 3);
+        assert_eq!(filtered.plan.plan.edges_total, 2);
+        assert_eq!(filtered.plan.plan.stages, vec![1, 1, 1]);
+    }
+
+    #[test]
+    fn prune_mode_removes_and_reports_dangling_references() {
+        let tg = build_one(SIGNUP);
+        let filtered = smoke(&tg, FilterMode::Prune);
+        let login = &filtered.plan.steps[0];
+        assert!(login.depends_on.is_empty());
+        assert!(login.inputs.is_empty());
+        assert_eq!(
+            filtered.pruned,
+            vec![
+                "node 'Login': dropped dependency on 'Verify'",
+                "node 'Login': dropped input 'user_id' from 'Verify'",
+            ]
+        );
+    }
+
+    #[test]
+    fn filter_mode_round_trips_through_strings() {
+        for mode in [FilterMode::Drop, FilterMode::KeepDeps, FilterMode::Prune] {
+            assert_eq!(mode.to_string().parse::<FilterMode>().unwrap(), mode);
+        }
+        assert!("keep".parse::<FilterMode>().is_err());
+    }
 }
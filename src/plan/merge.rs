@@ -0,0 +1,211 @@
+use std::collections::HashSet;
+
+use crate::graph::traversal::TraversalStrategy;
+use crate::plan::types::{GraphEntry, PLAN_SCHEMA_VERSION, PlanMetadata, TestPlan};
+
+/// Combine the plans of several graphs into one plan.
+///
+/// Every node name is prefixed with its graph's name, as in
+/// `Checkout.Login`, and so are the nodes a step depends on or takes inputs
+/// from. Graphs do not depend on each other, so steps keep their stage and
+/// the stages of different graphs run side by side: the merged plan has as
+/// many stages as its deepest graph. Topological plans are listed stage by
+/// stage, keeping graph order within a stage; other plans are listed one
+/// graph after another. The metadata adds up the node and edge counts,
+/// records a [`GraphEntry`] per graph, and keeps a seed only when every
+/// graph was planned with it. The order counts as uniform only if every
+/// graph's was.
+///
+/// # Errors
+///
+/// Returns an error if `plans` is empty or if two plans come from graphs with
+/// the same name.
+pub fn merge_plans(plans: Vec<TestPlan>) -> Result<TestPlan, String> {
+    let Some(first) = plans.first() else {
+        return Err("no plans to merge".to_owned());
+    };
+    let traversal = first.plan.traversal.clone();
+    let seed = first
+        .plan
+        .seed
+        .filter(|&seed| plans.iter().all(|p| p.plan.seed == Some(seed)));
+    let uniform = plans
+        .iter()
+        .filter_map(|p| p.plan.uniform)
+        .reduce(|a, b| a && b);
+
+    let mut seen = HashSet::new();
+    let mut names = Vec::new();
+    let mut graphs = Vec::new();
+    let mut steps = Vec::new();
+    for plan in plans {
+        let graph = plan.plan.name;
+        if !seen.insert(graph.clone()) {
+            return Err(format!(
+                "cannot merge: graph '{graph}' appears more than once"
+            ));
+        }
+        let prefixed = |node: &str| format!("{graph}.{node}");
+        graphs.push(GraphEntry {
+            name: graph.clone(),
+            nodes_total: plan.plan.nodes_total,
+            edges_total: plan.plan.edges_total,
+            steps: plan.steps.len(),
+            estimated_duration: plan.plan.estimated_duration,
+            budget: plan.plan.budget,
+            shard: plan.plan.shard,
+        });
+        for mut step in plan.steps {
+            step.node = prefixed(&step.node);
+            for dep in &mut step.depends_on {
+                *dep = prefixed(dep);
+            }
+            for input in &mut step.inputs {
+                input.from = prefixed(&input.from);
+            }
+            steps.push(step);
+        }
+        names.push(graph);
+    }
+
+    // Topological plans run stage by stage, so the merged plan does too.
+    // Other orders stay grouped by graph.
+    if traversal.parse() == Ok(TraversalStrategy::Topological) {
+        steps.sort_by_key(|s| s.stage);
+    }
+    for (i, step) in steps.iter_mut().enumerate() {
+        step.order = i + 1;
+    }
+
+    let mut merged = TestPlan {
+        schema_version: PLAN_SCHEMA_VERSION,
+        plan: PlanMetadata {
+            name: names.join(" + "),
+            traversal,
+            nodes_total: graphs.iter().map(|g| g.nodes_total).sum(),
+            edges_total: graphs.iter().map(|g| g.edges_total).sum(),
+            stages: Vec::new(),
+            critical_path: 0,
+            path: None,
+            seed,
+            uniform,
+            estimated_duration: None,
+            budget: None,
+            shard: None,
+            graphs,
+        },
+        steps,
+    };
+    merged.recount_stages();
+    merged.recount_durations();
+    Ok(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::builder::build;
+    use crate::ir::lower;
+    use crate::parser::parse::parse;
+    use crate::plan::compiler::compile_with_strategy;
+
+    fn plan_of(input: &str, strategy: TraversalStrategy) -> TestPlan {
+        let graphs = parse(input).expect("parse failed");
+        let tg = build(&lower(&graphs[0]).expect("lower failed"));
+        compile_with_strategy(&tg, strategy).expect("compile failed")
+    }
+
+    const CHECKOUT: &str = r#"graph Checkout {
+        node Login { config { estimated_duration: "20s" } }
+        node Cart { config { estimated_duration: "30s" } }
+        node Pay {}
+        Login -> Cart
+        Cart -> Pay
+    }"#;
+
+    const SEARCH: &str = r#"graph Search {
+        node Login { config { estimated_duration: "10s" } }
+        node Query {}
+        Login -> Query
+    }"#;
+
+    #[test]
+    fn prefixes_nodes_and_shares_stages() {
+        let merged = merge_plans(vec![
+            plan_of(CHECKOUT, TraversalStrategy::Topological),
+            plan_of(SEARCH, TraversalStrategy::Topological),
+        ])
+        .unwrap();
+        let order: Vec<(&str, usize)> = merged
+            .steps
+            .iter()
+            .map(|s| (s.node.as_str(), s.stage))
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                ("Checkout.Login", 1),
+                ("Search.Login", 1),
+                ("Checkout.Cart", 2),
+                ("Search.Query", 2),
+                ("Checkout.Pay", 3),
+            ]
+        );
+        assert_eq!(merged.steps[3].depends_on, vec!["Search.Login"]);
+        assert_eq!(merged.steps[3].order, 4);
+        assert!(merged.in_stage_order());
+        assert_eq!(merged.plan.stages, vec![2, 2, 1]);
+        assert_eq!(merged.plan.critical_path, 3);
+    }
+
+    #[test]
+    fn aggregates_metadata() {
+        let merged = merge_plans(vec![
+            plan_of(CHECKOUT, TraversalStrategy::Topological),
+            plan_of(SEARCH, TraversalStrategy::Topological),
+        ])
+        .unwrap();
+        let meta = &merged.plan;
+        assert_eq!(meta.name, "Checkout + Search");
+        assert_eq!((meta.nodes_total, meta.edges_total), (5, 3));
+        assert_eq!(meta.estimated_duration.as_deref(), Some("1m"));
+        let graphs: Vec<(&str, usize)> = meta
+            .graphs
+            .iter()
+            .map(|g| (g.name.as_str(), g.steps))
+            .collect();
+        assert_eq!(graphs, vec![("Checkout", 3), ("Search", 2)]);
+        assert_eq!(meta.graphs[1].estimated_duration.as_deref(), Some("10s"));
+    }
+
+    #[test]
+    fn keeps_other_orders_graph_by_graph() {
+        let merged = merge_plans(vec![
+            plan_of(CHECKOUT, TraversalStrategy::DepthFirst),
+            plan_of(SEARCH, TraversalStrategy::DepthFirst),
+        ])
+        .unwrap();
+        let nodes: Vec<&str> = merged.steps.iter().map(|s| s.node.as_str()).collect();
+        assert_eq!(
+            nodes,
+            vec![
+                "Checkout.Login",
+                "Checkout.Cart",
+                "Checkout.Pay",
+                "Search.Login",
+                "Search.Query",
+            ]
+        );
+        assert!(!merged.in_stage_order());
+        assert_eq!(merged.graph_of(&merged.steps[2]), Some("Checkout"));
+        assert_eq!(merged.graph_of(&merged.steps[3]), Some("Search"));
+    }
+
+    #[test]
+    fn rejects_repeated_graph_names() {
+        let plan = plan_of(SEARCH, TraversalStrategy::Topological);
+        let err = merge_plans(vec![plan.clone(), plan]).unwrap_err();
+        assert_eq!(err, "cannot merge: graph 'Search' appears more than once");
+        assert!(merge_plans(Vec::new()).is_err());
+    }
+}
@@ -1,3 +1,5 @@
 pub mod compiler;
 pub mod filter;
+pub mod merge;
+pub mod schema;
 pub mod types;
@@ -0,0 +1,435 @@
+use serde_json::{Value, json};
+
+use crate::plan::types::PLAN_SCHEMA_VERSION;
+
+/// JSON Schema (draft 2020-12) of `tast plan` JSON output: an array of
+/// `TestPlan`s, one per compiled plan. Each YAML document is a single
+/// `#/$defs/TestPlan`.
+///
+/// Properties listed as `required` are always written; the others are left
+/// out when empty or unset. Objects may carry properties the schema does not
+/// list, since a later tast can add optional fields without bumping
+/// `schema_version`.
+pub fn plan_schema() -> Value {
+    let text = json!({ "type": "string" });
+    let count = json!({ "type": "integer", "minimum": 0 });
+    let percent = json!({ "type": "number", "minimum": 0, "maximum": 100 });
+    let duration = json!({
+        "type": "string",
+        "description": "Duration such as \"1m30s\"."
+    });
+    let names = json!({ "type": "array", "items": { "type": "string" } });
+    let entries = json!({ "type": "array", "items": { "$ref": "#/$defs/StepEntry" } });
+
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "tast plan output",
+        "description": "The compiled test plans.",
+        "type": "array",
+        "items": { "$ref": "#/$defs/TestPlan" },
+        "$defs": {
+            "TestPlan": {
+                "description": "A compiled test plan.",
+                "type": "object",
+                "required": ["schema_version", "plan", "steps"],
+                "properties": {
+                    "schema_version": { "const": PLAN_SCHEMA_VERSION },
+                    "plan": { "$ref": "#/$defs/PlanMetadata" },
+                    "steps": { "type": "array", "items": { "$ref": "#/$defs/PlanStep" } }
+                }
+            },
+            "PlanMetadata": {
+                "description": "Metadata about the plan.",
+                "type": "object",
+                "required": ["name", "traversal", "nodes_total", "edges_total", "critical_path"],
+                "properties": {
+                    "name": text,
+                    "traversal": {
+                        "description": "Strategy the plan was compiled with.",
+                        "type": "string"
+                    },
+                    "nodes_total": count,
+                    "edges_total": count,
+                    "stages": {
+                        "description": "Number of steps in each stage.",
+                        "type": "array",
+                        "items": count
+                    },
+                    "critical_path": count,
+                    "path": { "$ref": "#/$defs/PathEntry" },
+                    "seed": count,
+                    "uniform": { "type": "boolean" },
+                    "estimated_duration": duration,
+                    "budget": { "$ref": "#/$defs/BudgetEntry" },
+                    "shard": { "$ref": "#/$defs/ShardEntry" },
+                    "graphs": { "type": "array", "items": { "$ref": "#/$defs/GraphEntry" } }
+                }
+            },
+            "PathEntry": {
+                "description": "Where a path plan sits among the paths of its graph.",
+                "type": "object",
+                "required": ["index", "total", "coverage"],
+                "properties": {
+                    "index": count,
+                    "total": count,
+                    "shared_prefix": { "$ref": "#/$defs/SharedPrefix" },
+                    "coverage": { "$ref": "#/$defs/CoverageEntry" }
+                }
+            },
+            "SharedPrefix": {
+                "description": "Leading steps shared with an earlier path.",
+                "type": "object",
+                "required": ["with", "steps"],
+                "properties": { "with": count, "steps": count }
+            },
+            "CoverageEntry": {
+                "description": "Share of the graph covered, in percent.",
+                "type": "object",
+                "required": ["edges_added", "nodes_added", "edges_covered", "nodes_covered"],
+                "properties": {
+                    "edges_added": percent,
+                    "nodes_added": percent,
+                    "edges_covered": percent,
+                    "nodes_covered": percent
+                }
+            },
+            "BudgetEntry": {
+                "description": "How a time budget narrowed the plan.",
+                "type": "object",
+                "required": ["limit", "coverage_by", "coverage", "selected"],
+                "properties": {
+                    "limit": duration,
+                    "coverage_by": { "enum": ["edges", "tags", "priority"] },
+                    "coverage": percent,
+                    "selected": names,
+                    "dropped": names
+                }
+            },
+            "ShardEntry": {
+                "description": "Which shard of its graph the plan is.",
+                "type": "object",
+                "required": ["index", "total", "balanced_by", "owned", "setup", "overhead"],
+                "properties": {
+                    "index": count,
+                    "total": count,
+                    "balanced_by": { "enum": ["duration", "nodes"] },
+                    "owned": count,
+                    "setup": count,
+                    "overhead": { "type": "number", "minimum": 0 }
+                }
+            },
+            "GraphEntry": {
+                "description": "One graph of a merged plan.",
+                "type": "object",
+                "required": ["name", "nodes_total", "edges_total", "steps"],
+                "properties": {
+                    "name": text,
+                    "nodes_total": count,
+                    "edges_total": count,
+                    "steps": count,
+                    "estimated_duration": duration,
+                    "budget": { "$ref": "#/$defs/BudgetEntry" },
+                    "shard": { "$ref": "#/$defs/ShardEntry" }
+                }
+            },
+            "PlanStep": {
+                "description": "A single step in the plan.",
+                "type": "object",
+                "required": ["order", "stage", "node"],
+                "properties": {
+                    "order": count,
+                    "stage": count,
+                    "node": text,
+                    "description": text,
+                    "tags": names,
+                    "depends_on": names,
+                    "preconditions": entries,
+                    "actions": entries,
+                    "assertions": entries,
+                    "inputs": { "type": "array", "items": { "$ref": "#/$defs/InputEntry" } },
+                    "outputs": names,
+                    "iteration": { "$ref": "#/$defs/IterationEntry" },
+                    "included_for": { "enum": ["dependency"] },
+                    "estimated_duration": duration,
+                    "cumulative_duration": duration
+                }
+            },
+            "IterationEntry": {
+                "description": "Which pass of an unrolled loop the step belongs to.",
+                "type": "object",
+                "required": ["index", "count"],
+                "properties": {
+                    "index": count,
+                    "count": count,
+                    "optional": { "type": "boolean" }
+                }
+            },
+            "StepEntry": {
+                "description": "A given/when/then entry of a step.",
+                "type": "object",
+                "required": ["type", "text"],
+                "properties": {
+                    "type": text,
+                    "text": text,
+                    "rendered_text": text,
+                    "data": {
+                        "description": "Key-value pairs.",
+                        "type": "array",
+                        "items": {
+                            "type": "array",
+                            "items": { "type": "string" },
+                            "minItems": 2,
+                            "maxItems": 2
+                        }
+                    },
+                    "parameters": {
+                        "type": "array",
+                        "items": { "$ref": "#/$defs/ParameterEntry" }
+                    }
+                }
+            },
+            "ParameterEntry": {
+                "description": "A parameter binding of a step entry.",
+                "type": "object",
+                "required": ["name", "source"],
+                "properties": { "name": text, "value": text, "source": text }
+            },
+            "InputEntry": {
+                "description": "An input passed from an upstream node.",
+                "type": "object",
+                "required": ["field", "from"],
+                "properties": { "field": text, "from": text }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+    use crate::emit::test_plans::empty_plan;
+    use crate::plan::types::{
+        BudgetEntry, CoverageEntry, GraphEntry, Inclusion, InputEntry, IterationEntry,
+        ParameterEntry, PathEntry, PlanMetadata, PlanStep, ShardEntry, SharedPrefix, StepEntry,
+        TestPlan,
+    };
+
+    /// Check `value` against the subset of JSON Schema that `plan_schema`
+    /// uses. Records every property it sees in `seen`, and with `exact` also
+    /// requires objects to have no more than their required properties.
+    fn check(
+        value: &Value,
+        schema: &Value,
+        root: &Value,
+        at: &str,
+        exact: bool,
+        seen: &mut BTreeSet<String>,
+    ) {
+        if let Some(reference) = schema["$ref"].as_str() {
+            let def = reference.trim_start_matches("#/$defs/");
+            let resolved = &root["$defs"][def];
+            return check(value, resolved, root, &format!("#{def}"), exact, seen);
+        }
+        if let Some(constant) = schema.get("const") {
+            assert_eq!(value, constant, "{at}");
+        }
+        if let Some(allowed) = schema["enum"].as_array() {
+            assert!(allowed.contains(value), "{at}: {value} not in {allowed:?}");
+        }
+        let matches_type = match schema["type"].as_str() {
+            Some("object") => value.is_object(),
+            Some("array") => value.is_array(),
+            Some("string") => value.is_string(),
+            Some("integer") => value.is_u64(),
+            Some("number") => value.is_number(),
+            Some("boolean") => value.is_boolean(),
+            _ => true,
+        };
+        assert!(matches_type, "{at}: {value} is not {}", schema["type"]);
+
+        if let Some(object) = value.as_object() {
+            let required: BTreeSet<&str> = schema["required"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .collect();
+            let present: BTreeSet<&str> = object.keys().map(String::as_str).collect();
+            assert!(required.is_subset(&present), "{at}: missing {required:?}");
+            if exact {
+                assert_eq!(present, required, "{at}: optional properties written");
+            }
+            for (key, field) in object {
+                let property = &schema["properties"][key];
+                assert!(!property.is_null(), "{at}: unexpected property '{key}'");
+                seen.insert(format!("{at}/{key}"));
+                check(field, property, root, &format!("{at}/{key}"), exact, seen);
+            }
+        }
+        if let (Some(items), Some(schema)) = (value.as_array(), schema.get("items")) {
+            for item in items {
+                check(item, schema, root, at, exact, seen);
+            }
+        }
+    }
+
+    /// Reference to the schema of a single plan.
+    fn test_plan() -> Value {
+        json!({ "$ref": "#/$defs/TestPlan" })
+    }
+
+    /// Every property the schema declares, as `#Def/property`.
+    fn declared(schema: &Value) -> BTreeSet<String> {
+        let mut all = BTreeSet::new();
+        let objects = schema["$defs"]
+            .as_object()
+            .unwrap()
+            .iter()
+            .map(|(name, def)| (format!("#{name}"), def));
+        for (at, def) in objects {
+            for key in def["properties"].as_object().unwrap().keys() {
+                all.insert(format!("{at}/{key}"));
+            }
+        }
+        all
+    }
+
+    /// A plan with every optional field set and every list filled.
+    fn full_plan() -> TestPlan {
+        let entry = |step_type: &str| StepEntry {
+            step_type: step_type.into(),
+            text: "a user <id>".into(),
+            rendered_text: Some("a user 7".into()),
+            data: vec![("email".into(), "a@b.com".into())],
+            parameters: vec![ParameterEntry {
+                name: "id".into(),
+                value: Some("7".into()),
+                source: "fixture".into(),
+            }],
+        };
+        let budget = BudgetEntry {
+            limit: "10m".into(),
+            coverage_by: "edges".into(),
+            coverage: 75.0,
+            selected: vec!["Login".into()],
+            dropped: vec!["Audit".into()],
+        };
+        let shard = ShardEntry {
+            index: 1,
+            total: 2,
+            balanced_by: "duration".into(),
+            owned: 1,
+            setup: 0,
+            overhead: 0.0,
+        };
+        TestPlan {
+            schema_version: PLAN_SCHEMA_VERSION,
+            plan: PlanMetadata {
+                name: "Shop".into(),
+                traversal: "random".into(),
+                nodes_total: 2,
+                edges_total: 1,
+                stages: vec![1],
+                critical_path: 1,
+                path: Some(PathEntry {
+                    index: 2,
+                    total: 2,
+                    shared_prefix: Some(SharedPrefix { with: 1, steps: 1 }),
+                    coverage: CoverageEntry {
+                        edges_added: 50.0,
+                        nodes_added: 0.0,
+                        edges_covered: 100.0,
+                        nodes_covered: 100.0,
+                    },
+                }),
+                seed: Some(42),
+                uniform: Some(true),
+                estimated_duration: Some("30s".into()),
+                budget: Some(budget.clone()),
+                shard: Some(shard.clone()),
+                graphs: vec![GraphEntry {
+                    name: "Shop".into(),
+                    nodes_total: 2,
+                    edges_total: 1,
+                    steps: 1,
+                    estimated_duration: Some("30s".into()),
+                    budget: Some(budget),
+                    shard: Some(shard),
+                }],
+            },
+            steps: vec![PlanStep {
+                order: 1,
+                stage: 1,
+                node: "Login".into(),
+                description: Some("User logs in".into()),
+                tags: vec!["smoke".into()],
+                depends_on: vec!["Register".into()],
+                preconditions: vec![entry("given")],
+                actions: vec![entry("when")],
+                assertions: vec![entry("then")],
+                inputs: vec![InputEntry {
+                    field: "user_id".into(),
+                    from: "Register".into(),
+                }],
+                outputs: vec!["token".into()],
+                iteration: Some(IterationEntry {
+                    index: 2,
+                    count: 3,
+                    optional: true,
+                }),
+                included_for: Some(Inclusion::Dependency),
+                estimated_duration: Some("30s".into()),
+                cumulative_duration: Some("30s".into()),
+            }],
+        }
+    }
+
+    #[test]
+    fn schema_covers_every_plan_field() {
+        let schema = plan_schema();
+        // Serialization skips unset and empty fields, which would hide them
+        // from the check below
+        let debug = format!("{:?}", full_plan());
+        for unset in ["None", "[]", "\"\"", "false"] {
+            assert!(!debug.contains(unset), "full_plan has a {unset} field");
+        }
+        let plan = serde_json::to_value(full_plan()).unwrap();
+        let mut seen = BTreeSet::new();
+        check(&plan, &test_plan(), &schema, "", false, &mut seen);
+        // The output itself is an array of plans
+        check(&json!([plan]), &schema, &schema, "", false, &mut seen);
+        // A property the serde types never write means the schema is stale
+        let unused: Vec<_> = declared(&schema).difference(&seen).cloned().collect();
+        assert!(
+            unused.is_empty(),
+            "schema properties never written: {unused:?}"
+        );
+    }
+
+    #[test]
+    fn schema_requires_exactly_what_is_always_written() {
+        let schema = plan_schema();
+        let mut plan = empty_plan();
+        plan.steps.push(PlanStep {
+            order: 1,
+            stage: 1,
+            node: "Login".into(),
+            description: None,
+            tags: Vec::new(),
+            depends_on: Vec::new(),
+            preconditions: Vec::new(),
+            actions: Vec::new(),
+            assertions: Vec::new(),
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            iteration: None,
+            included_for: None,
+            estimated_duration: None,
+            cumulative_duration: None,
+        });
+        let plan = serde_json::to_value(plan).unwrap();
+        check(&plan, &test_plan(), &schema, "", true, &mut BTreeSet::new());
+    }
+}
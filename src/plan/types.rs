@@ -1,8 +1,20 @@
+use std::time::Duration;
+
 use serde::{Deserialize, Serialize};
 
+use crate::util::duration::{format_duration, parse_duration};
+
+/// Plan format version, written to every plan as `schema_version`.
+///
+/// Adding an optional field keeps the version. Removing or renaming a field,
+/// changing its type or meaning, or making it required bumps it.
+pub const PLAN_SCHEMA_VERSION: u32 = 1;
+
 /// A compiled test plan, ready for output.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct TestPlan {
+    /// Always [`PLAN_SCHEMA_VERSION`] when written by this version of tast.
+    pub schema_version: u32,
     pub plan: PlanMetadata,
     pub steps: Vec<PlanStep>,
 }
@@ -14,12 +26,210 @@ pub struct PlanMetadata {
     pub traversal: String,
     pub nodes_total: usize,
     pub edges_total: usize,
+    /// Number of steps in each stage, from the first stage on.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stages: Vec<usize>,
+    /// Number of steps on the longest dependency chain, which is also the
+    /// number of stages.
+    #[serde(default)]
+    pub critical_path: usize,
+    /// Set on plans compiled from a single path by the `paths` strategy.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathEntry>,
+    /// Seed of the `random` strategy; compiling with it again gives the
+    /// same order.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    /// Whether the `random` strategy drew the order uniformly from all valid
+    /// orders. Set for random plans; false when the graph has too many
+    /// partial orders to count and a cheaper, biased draw was used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uniform: Option<bool>,
+    /// Sum of the steps' estimated durations, e.g. `"4m30s"`. Set when any
+    /// step has one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimated_duration: Option<String>,
+    /// Set on plans whose nodes were chosen to fit a time budget.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget: Option<BudgetEntry>,
+    /// Set on plans for one shard of a graph.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shard: Option<ShardEntry>,
+    /// Set on plans merged from several graphs: one entry per graph, in
+    /// input order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub graphs: Vec<GraphEntry>,
+}
+
+impl PlanMetadata {
+    /// Plan title: the graph name, plus the path number for path plans, the
+    /// shard number for shards and the seed for random plans.
+    pub fn title(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(path) = &self.path {
+            parts.push(format!("path {}/{}", path.index, path.total));
+        }
+        if let Some(shard) = &self.shard {
+            parts.push(format!("shard {}/{}", shard.index, shard.total));
+        }
+        if let Some(seed) = self.seed {
+            parts.push(format!("seed {seed}"));
+        }
+        if parts.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{} ({})", self.name, parts.join(", "))
+        }
+    }
+}
+
+/// How a time budget narrowed a plan.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BudgetEntry {
+    /// The budget, e.g. `"10m"`.
+    pub limit: String,
+    /// What the selection kept as much of: `edges`, `tags` or `priority`.
+    pub coverage_by: String,
+    /// Share of the graph's coverage the selected nodes keep, in percent.
+    pub coverage: f64,
+    pub selected: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dropped: Vec<String>,
+}
+
+/// Which shard of its graph a plan is.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ShardEntry {
+    /// 1-based shard number.
+    pub index: usize,
+    pub total: usize,
+    /// How shards were balanced: `duration` or `nodes`.
+    pub balanced_by: String,
+    /// Nodes this shard is responsible for.
+    pub owned: usize,
+    /// Nodes another shard owns that run here as setup, marked
+    /// `included_for: dependency`.
+    pub setup: usize,
+    /// Work repeated as setup across all shards, as a percentage of the
+    /// graph's work.
+    pub overhead: f64,
+}
+
+/// One graph of a merged plan. Its steps are the ones whose node starts with
+/// `name.`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GraphEntry {
+    pub name: String,
+    pub nodes_total: usize,
+    pub edges_total: usize,
+    pub steps: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimated_duration: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget: Option<BudgetEntry>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shard: Option<ShardEntry>,
+}
+
+/// Where a path plan sits among the paths of its graph.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct PathEntry {
+    /// 1-based path number.
+    pub index: usize,
+    pub total: usize,
+    /// Longest run of leading steps shared with an earlier path.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shared_prefix: Option<SharedPrefix>,
+    pub coverage: CoverageEntry,
+}
+
+/// Share of the graph's edges and nodes covered by a path plan, in percent.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct CoverageEntry {
+    /// Covered by this path and not by any earlier one.
+    pub edges_added: f64,
+    pub nodes_added: f64,
+    /// Covered by this path and all earlier ones.
+    pub edges_covered: f64,
+    pub nodes_covered: f64,
+}
+
+/// Leading steps a path plan has in common with an earlier one.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SharedPrefix {
+    /// Number of the earlier path.
+    pub with: usize,
+    pub steps: usize,
+}
+
+impl TestPlan {
+    /// Renumber stages to `1..=n`, dropping stages left empty, and recompute
+    /// the stage widths and critical path in the metadata.
+    ///
+    /// Called after compiling and whenever steps are removed from a plan.
+    pub fn recount_stages(&mut self) {
+        let mut used: Vec<usize> = self.steps.iter().map(|s| s.stage).collect();
+        used.sort_unstable();
+        used.dedup();
+        let mut widths = vec![0; used.len()];
+        for step in &mut self.steps {
+            let index = used.binary_search(&step.stage).unwrap_or_default();
+            step.stage = index + 1;
+            widths[index] += 1;
+        }
+        self.plan.critical_path = widths.len();
+        self.plan.stages = widths;
+    }
+
+    /// Whether every step's stage is at least that of the step before it, so
+    /// that the steps of a stage are listed together.
+    pub fn in_stage_order(&self) -> bool {
+        self.steps.windows(2).all(|w| w[0].stage <= w[1].stage)
+    }
+
+    /// The graph a step of a merged plan comes from, going by its node's
+    /// prefix. `None` for plans that are not merged.
+    pub fn graph_of(&self, step: &PlanStep) -> Option<&str> {
+        self.plan
+            .graphs
+            .iter()
+            .map(|g| g.name.as_str())
+            .find(|name| {
+                step.node
+                    .strip_prefix(name)
+                    .is_some_and(|rest| rest.starts_with('.'))
+            })
+    }
+
+    /// Recompute each step's cumulative duration and the plan total from the
+    /// steps' estimated durations.
+    ///
+    /// Called after compiling and whenever steps are removed from a plan.
+    pub fn recount_durations(&mut self) {
+        if self.steps.iter().all(|s| s.estimated_duration.is_none()) {
+            self.plan.estimated_duration = None;
+            for step in &mut self.steps {
+                step.cumulative_duration = None;
+            }
+            return;
+        }
+        let mut total = Duration::ZERO;
+        for step in &mut self.steps {
+            total += step.duration();
+            step.cumulative_duration = Some(format_duration(total));
+        }
+        self.plan.estimated_duration = Some(format_duration(total));
+    }
 }
 
 /// A single step in the compiled plan.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct PlanStep {
     pub order: usize,
+    /// 1-based execution stage. Steps in the same stage do not depend on each
+    /// other and can run in parallel.
+    #[serde(default)]
+    pub stage: usize,
     pub node: String,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
@@ -37,6 +247,49 @@ pub struct PlanStep {
     pub inputs: Vec<InputEntry>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub outputs: Vec<String>,
+    /// Position within an unrolled loop; `None` outside loops.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iteration: Option<IterationEntry>,
+    /// Why a step that was not selected is in the plan anyway.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub included_for: Option<Inclusion>,
+    /// The node's declared `estimated_duration`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimated_duration: Option<String>,
+    /// Estimated time from the start of the plan until this step is done,
+    /// running the steps one after another. Set when any step has an
+    /// estimated duration; steps without one count as taking no time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cumulative_duration: Option<String>,
+}
+
+impl PlanStep {
+    /// The estimated duration, zero when unknown.
+    pub fn duration(&self) -> Duration {
+        self.estimated_duration
+            .as_deref()
+            .and_then(|d| parse_duration(d).ok())
+            .unwrap_or_default()
+    }
+}
+
+/// Reason a step is in a plan although it was not selected.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Inclusion {
+    /// A selected step depends on it, directly or through other steps.
+    Dependency,
+}
+
+/// Which pass of an unrolled loop a plan step belongs to.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct IterationEntry {
+    /// 1-based iteration number.
+    pub index: usize,
+    pub count: usize,
+    /// Set for passes of a `max N` loop after the first, which may not run.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub optional: bool,
 }
 
 /// A given/when/then entry in a plan step.
@@ -45,12 +298,30 @@ pub struct StepEntry {
     #[serde(rename = "type")]
     pub step_type: String,
     pub text: String,
+    /// `text` with bound parameters substituted; set only for steps with parameters.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rendered_text: Option<String>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub data: Vec<(String, String)>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub parameters: Vec<ParameterEntry>,
 }
 
+impl StepEntry {
+    /// The text to show a reader: the rendered text when there is one.
+    pub fn display_text(&self) -> &str {
+        self.rendered_text.as_deref().unwrap_or(&self.text)
+    }
+
+    /// The raw template, if it differs from [`Self::display_text`].
+    pub fn template(&self) -> Option<&str> {
+        self.rendered_text
+            .as_deref()
+            .filter(|r| *r != self.text)
+            .map(|_| self.text.as_str())
+    }
+}
+
 /// A parameter binding in a plan step.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ParameterEntry {
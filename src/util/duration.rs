@@ -0,0 +1,127 @@
+use std::time::Duration;
+
+/// Parse a human-readable duration such as `"500ms"`, `"30s"`, `"10m"` or `"1h"`.
+///
+/// A bare number is interpreted as seconds. Compound forms like `"1m30s"`
+/// are accepted and summed.
+///
+/// # Errors
+///
+/// Returns an error if the input is empty, has an unknown unit, or is not a number.
+pub fn parse_duration(input: &str) -> Result<Duration, String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err("empty duration".to_owned());
+    }
+
+    if let Ok(secs) = input.parse::<f64>() {
+        return seconds(secs, input);
+    }
+
+    let mut total = Duration::ZERO;
+    let mut rest = input;
+    while !rest.is_empty() {
+        let num_len = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        if num_len == 0 {
+            return Err(format!("invalid duration '{input}'"));
+        }
+        let value: f64 = rest[..num_len]
+            .parse()
+            .map_err(|_| format!("invalid duration '{input}'"))?;
+        rest = &rest[num_len..];
+
+        let unit_len = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        let factor = match &rest[..unit_len] {
+            "ms" => 0.001,
+            "s" => 1.0,
+            "m" => 60.0,
+            "h" => 3600.0,
+            "" => return Err(format!("missing unit in duration '{input}'")),
+            other => {
+                return Err(format!(
+                    "unknown unit '{other}' in duration '{input}' (expected: ms, s, m, h)"
+                ));
+            }
+        };
+        rest = &rest[unit_len..];
+        total += seconds(value * factor, input)?;
+    }
+
+    Ok(total)
+}
+
+fn seconds(secs: f64, input: &str) -> Result<Duration, String> {
+    Duration::try_from_secs_f64(secs).map_err(|_| format!("invalid duration '{input}'"))
+}
+
+/// Format a duration in the compact form accepted by [`parse_duration`].
+pub fn format_duration(d: Duration) -> String {
+    let millis = d.as_millis();
+    if millis == 0 {
+        return "0s".to_owned();
+    }
+    if !millis.is_multiple_of(1000) {
+        return format!("{millis}ms");
+    }
+
+    let mut secs = d.as_secs();
+    let mut out = String::new();
+    for (unit, size) in [("h", 3600), ("m", 60), ("s", 1)] {
+        if secs >= size {
+            out.push_str(&format!("{}{unit}", secs / size));
+            secs %= size;
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_seconds() {
+        assert_eq!(parse_duration("30s").unwrap(), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn parses_bare_number_as_seconds() {
+        assert_eq!(parse_duration("45").unwrap(), Duration::from_secs(45));
+    }
+
+    #[test]
+    fn parses_milliseconds_minutes_hours() {
+        assert_eq!(parse_duration("250ms").unwrap(), Duration::from_millis(250));
+        assert_eq!(parse_duration("10m").unwrap(), Duration::from_secs(600));
+        assert_eq!(parse_duration("2h").unwrap(), Duration::from_secs(7200));
+    }
+
+    #[test]
+    fn parses_compound_duration() {
+        assert_eq!(parse_duration("1m30s").unwrap(), Duration::from_secs(90));
+    }
+
+    #[test]
+    fn rejects_unknown_unit() {
+        let err = parse_duration("5d").unwrap_err();
+        assert!(err.contains("unknown unit 'd'"), "got: {err}");
+    }
+
+    #[test]
+    fn rejects_empty_and_garbage() {
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("soon").is_err());
+    }
+
+    #[test]
+    fn formats_round_trip() {
+        for text in ["0s", "250ms", "45s", "1m30s", "2h", "1h1m1s"] {
+            let d = parse_duration(text).unwrap();
+            assert_eq!(format_duration(d), text);
+        }
+    }
+}
@@ -0,0 +1,78 @@
+/// Match `text` against a shell-style glob pattern.
+///
+/// `*` matches any run of characters (including none) and `?` matches exactly
+/// one character. Every other character matches itself. Matching is
+/// case-sensitive.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` and the text position it was tried at
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                // Let the last `*` swallow one more character
+                Some((star, at)) => {
+                    p = star + 1;
+                    t = at + 1;
+                    backtrack = Some((star, at + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Whether `pattern` contains glob wildcards.
+pub fn is_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?'])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn literal_patterns_match_exactly() {
+        assert!(glob_match("Login", "Login"));
+        assert!(!glob_match("Login", "LoginUser"));
+        assert!(!glob_match("login", "Login"));
+        assert!(glob_match("", ""));
+    }
+
+    #[test]
+    fn star_matches_any_run() {
+        assert!(glob_match("Login*", "Login"));
+        assert!(glob_match("Login*", "LoginUser"));
+        assert!(glob_match("*User", "RegisterUser"));
+        assert!(glob_match("*ser*", "RegisterUserEmail"));
+        assert!(glob_match("A*B*C", "AxxBxxBxxC"));
+        assert!(!glob_match("A*B*C", "AxxBxx"));
+        assert!(glob_match("*", ""));
+    }
+
+    #[test]
+    fn question_mark_matches_one_character() {
+        assert!(glob_match("Step?", "Step1"));
+        assert!(!glob_match("Step?", "Step"));
+        assert!(!glob_match("Step?", "Step12"));
+    }
+
+    #[test]
+    fn detects_wildcards() {
+        assert!(is_glob("Login*"));
+        assert!(is_glob("Step?"));
+        assert!(!is_glob("Login"));
+    }
+}
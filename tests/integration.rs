@@ -1,6 +1,13 @@
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
-use tast::cli::commands::{PlanOptions, run_list, run_plan, run_validate, run_visualize};
+use tast::cli::commands::{
+    PlanOptions, discover_files, run_config_show, run_diff, run_init, run_lint, run_list, run_plan,
+    run_schema, run_snapshot, run_stats, run_validate, run_visualize,
+};
+use tast::cli::config::{Config, LoadedConfig, SettingSource};
+use tast::graph::stats::Thresholds;
+use tast::lint::LintSettings;
 
 fn fixture(name: &str) -> PathBuf {
     PathBuf::from(env!("CARGO_MANIFEST_DIR"))
@@ -18,7 +25,7 @@ fn default_opts() -> PlanOptions {
 #[test]
 fn cli_plan_reads_file_and_outputs_yaml() {
     let result = run_plan(&[fixture("single_node.tast")], &default_opts());
-    let yaml = result.expect("plan should succeed");
+    let yaml = result.expect("plan should succeed").output;
     assert!(yaml.contains("name: SingleNode"));
     assert!(yaml.contains("node: Register"));
     assert!(yaml.contains("traversal: topological"));
@@ -43,7 +50,7 @@ fn cli_plan_with_output_flag_writes_file() {
 #[test]
 fn cli_plan_full_auth_graph() {
     let result = run_plan(&[fixture("full_auth.tast")], &default_opts());
-    let yaml = result.expect("plan should succeed");
+    let yaml = result.expect("plan should succeed").output;
     assert!(yaml.contains("name: UserAuthentication"));
     assert!(yaml.contains("node: RegisterUser"));
     assert!(yaml.contains("node: LoginUser"));
@@ -56,7 +63,7 @@ fn cli_plan_full_auth_graph() {
 #[test]
 fn cli_plan_empty_graph() {
     let result = run_plan(&[fixture("empty_graph.tast")], &default_opts());
-    let yaml = result.expect("plan should succeed");
+    let yaml = result.expect("plan should succeed").output;
     assert!(yaml.contains("name: Empty"));
     assert!(yaml.contains("steps: []"));
 }
@@ -64,7 +71,7 @@ fn cli_plan_empty_graph() {
 #[test]
 fn cli_plan_simple_edge() {
     let result = run_plan(&[fixture("simple_edge.tast")], &default_opts());
-    let yaml = result.expect("plan should succeed");
+    let yaml = result.expect("plan should succeed").output;
     assert!(yaml.contains("name: SimpleEdge"));
     assert!(yaml.contains("depends_on:"));
 }
@@ -121,7 +128,7 @@ fn cli_plan_with_strategy_dfs() {
         ..PlanOptions::default()
     };
     let result = run_plan(&[fixture("full_auth.tast")], &opts);
-    let yaml = result.expect("plan should succeed");
+    let yaml = result.expect("plan should succeed").output;
     assert!(yaml.contains("traversal: dfs"));
     assert!(yaml.contains("node: RegisterUser"));
 }
@@ -133,7 +140,7 @@ fn cli_plan_with_strategy_bfs() {
         ..PlanOptions::default()
     };
     let result = run_plan(&[fixture("full_auth.tast")], &opts);
-    let yaml = result.expect("plan should succeed");
+    let yaml = result.expect("plan should succeed").output;
     assert!(yaml.contains("traversal: bfs"));
     assert!(yaml.contains("node: RegisterUser"));
 }
@@ -146,7 +153,7 @@ fn cli_plan_with_filter_tag() {
     };
     // full_auth doesn't have tags, so all steps get filtered out
     let result = run_plan(&[fixture("full_auth.tast")], &opts);
-    let yaml = result.expect("plan should succeed");
+    let yaml = result.expect("plan should succeed").output;
     assert!(yaml.contains("steps: []"));
 }
 
@@ -158,7 +165,7 @@ fn cli_plan_with_from_to() {
         ..PlanOptions::default()
     };
     let result = run_plan(&[fixture("full_auth.tast")], &opts);
-    let yaml = result.expect("plan should succeed");
+    let yaml = result.expect("plan should succeed").output;
     assert!(yaml.contains("node: RegisterUser"));
     assert!(yaml.contains("node: LoginUser"));
     // Should NOT contain AccessDashboard or LogoutUser
@@ -185,7 +192,7 @@ fn cli_plan_with_from_only() {
 
 #[test]
 fn cli_visualize_dot_output() {
-    let result = run_visualize(&[fixture("full_auth.tast")], "dot", None);
+    let result = run_visualize(&[fixture("full_auth.tast")], "dot", None, None, false);
     let dot = result.expect("visualize should succeed");
     assert!(dot.contains("digraph \"UserAuthentication\""));
     assert!(dot.contains("\"RegisterUser\""));
@@ -194,7 +201,7 @@ fn cli_visualize_dot_output() {
 
 #[test]
 fn cli_visualize_mermaid_output() {
-    let result = run_visualize(&[fixture("full_auth.tast")], "mermaid", None);
+    let result = run_visualize(&[fixture("full_auth.tast")], "mermaid", None, None, false);
     let md = result.expect("visualize should succeed");
     assert!(md.contains("graph TD"));
     assert!(md.contains("RegisterUser"));
@@ -205,7 +212,7 @@ fn cli_visualize_mermaid_output() {
 
 #[test]
 fn list_nodes_shows_all() {
-    let result = run_list("nodes", &[fixture("full_auth.tast")]);
+    let result = run_list("nodes", &[fixture("full_auth.tast")], None);
     let output = result.expect("list should succeed");
     assert!(output.contains("RegisterUser"));
     assert!(output.contains("LoginUser"));
@@ -215,14 +222,14 @@ fn list_nodes_shows_all() {
 
 #[test]
 fn list_nodes_includes_descriptions() {
-    let result = run_list("nodes", &[fixture("full_auth.tast")]);
+    let result = run_list("nodes", &[fixture("full_auth.tast")], None);
     let output = result.expect("list should succeed");
     assert!(output.contains("A new user registers with valid credentials"));
 }
 
 #[test]
 fn list_edges_shows_all() {
-    let result = run_list("edges", &[fixture("full_auth.tast")]);
+    let result = run_list("edges", &[fixture("full_auth.tast")], None);
     let output = result.expect("list should succeed");
     assert!(output.contains("RegisterUser -> LoginUser"));
     assert!(output.contains("LoginUser -> AccessDashboard"));
@@ -231,22 +238,43 @@ fn list_edges_shows_all() {
 
 #[test]
 fn list_edges_includes_passes() {
-    let result = run_list("edges", &[fixture("full_auth.tast")]);
+    let result = run_list("edges", &[fixture("full_auth.tast")], None);
     let output = result.expect("list should succeed");
     assert!(output.contains("[passes: user_id, email]"));
 }
 
 #[test]
 fn list_tags_unique() {
-    let result = run_list("tags", &[fixture("empty_graph.tast")]);
+    let result = run_list("tags", &[fixture("empty_graph.tast")], None);
     let output = result.expect("list should succeed");
     // empty graph has no tags
     assert_eq!(output.trim(), "");
 }
 
+#[test]
+fn list_tags_groups_key_value_tags_by_key() {
+    let path = write_scratch(
+        "list_tags.tast",
+        r#"graph Shop {
+  tags [team/shop, area: checkout]
+  node Cart {
+    tags [smoke, priority: 2]
+  }
+  node Search {
+    tags [area: search, priority: 1]
+  }
+}"#,
+    );
+    let output = run_list("tags", &[path], None).expect("list should succeed");
+    assert_eq!(
+        output,
+        "smoke\nteam/shop\narea: checkout, search\npriority: 1, 2\n"
+    );
+}
+
 #[test]
 fn list_invalid_what_errors() {
-    let result = run_list("foobar", &[fixture("full_auth.tast")]);
+    let result = run_list("foobar", &[fixture("full_auth.tast")], None);
     assert!(result.is_err());
     assert!(result.unwrap_err().contains("unknown list target"));
 }
@@ -256,7 +284,7 @@ fn list_invalid_what_errors() {
 #[test]
 fn cli_plan_with_import() {
     let result = run_plan(&[fixture("imports_auth.tast")], &default_opts());
-    let yaml = result.expect("plan should succeed");
+    let yaml = result.expect("plan should succeed").output;
     assert!(yaml.contains("name: OrderFlow"));
     assert!(yaml.contains("node: PlaceOrder"));
 }
@@ -273,7 +301,7 @@ fn cli_validate_with_import() {
 #[test]
 fn cli_plan_cross_graph_edge() {
     let result = run_plan(&[fixture("cross_graph_order.tast")], &default_opts());
-    let yaml = result.expect("plan should succeed");
+    let yaml = result.expect("plan should succeed").output;
     assert!(yaml.contains("name: OrderFlow"));
     assert!(yaml.contains("node: PlaceOrder"));
     // The imported Auth.Login node should appear in the plan
@@ -286,7 +314,7 @@ fn cli_plan_cross_graph_edge() {
 
 #[test]
 fn list_fixtures_shows_all() {
-    let result = run_list("fixtures", &[fixture("with_fixtures.tast")]);
+    let result = run_list("fixtures", &[fixture("with_fixtures.tast")], None);
     let output = result.expect("list should succeed");
     assert!(output.contains("AdminUser"));
     assert!(output.contains("GuestUser"));
@@ -294,7 +322,7 @@ fn list_fixtures_shows_all() {
 
 #[test]
 fn list_fixtures_includes_fields() {
-    let result = run_list("fixtures", &[fixture("with_fixtures.tast")]);
+    let result = run_list("fixtures", &[fixture("with_fixtures.tast")], None);
     let output = result.expect("list should succeed");
     assert!(output.contains("role: admin"));
     assert!(output.contains("email: admin@example.com"));
@@ -302,7 +330,7 @@ fn list_fixtures_includes_fields() {
 
 #[test]
 fn list_fixtures_empty_when_none() {
-    let result = run_list("fixtures", &[fixture("empty_graph.tast")]);
+    let result = run_list("fixtures", &[fixture("empty_graph.tast")], None);
     let output = result.expect("list should succeed");
     assert_eq!(output.trim(), "");
 }
@@ -316,7 +344,7 @@ fn cli_plan_format_markdown() {
         ..PlanOptions::default()
     };
     let result = run_plan(&[fixture("full_auth.tast")], &opts);
-    let output = result.expect("plan should succeed");
+    let output = result.expect("plan should succeed").output;
     assert!(output.contains("# Test Plan:"));
     assert!(output.contains("## Step"));
 }
@@ -324,7 +352,7 @@ fn cli_plan_format_markdown() {
 #[test]
 fn cli_plan_format_yaml_default() {
     let result = run_plan(&[fixture("single_node.tast")], &default_opts());
-    let output = result.expect("plan should succeed");
+    let output = result.expect("plan should succeed").output;
     // Default format is YAML
     assert!(output.contains("plan:"));
     assert!(output.contains("steps:"));
@@ -350,7 +378,7 @@ fn cli_plan_format_junit() {
         ..PlanOptions::default()
     };
     let result = run_plan(&[fixture("full_auth.tast")], &opts);
-    let output = result.expect("plan should succeed");
+    let output = result.expect("plan should succeed").output;
     assert!(output.contains("<?xml"));
     assert!(output.contains("<testsuites"));
     assert!(output.contains("<testcase"));
@@ -378,9 +406,1374 @@ fn cli_plan_format_junit_valid_xml() {
         ..PlanOptions::default()
     };
     let result = run_plan(&[fixture("full_auth.tast")], &opts);
-    let output = result.expect("plan should succeed");
+    let output = result.expect("plan should succeed").output;
     // Verify basic XML well-formedness: every open tag has a close
     assert!(output.contains("</testcase>"));
     assert!(output.contains("</testsuite>"));
     assert!(output.contains("</testsuites>"));
 }
+
+// ── Project configuration ────────────────────────────────
+
+fn fixtures_as_test_dir() -> LoadedConfig {
+    let mut config = Config::default();
+    config.project.test_dir = "tests/fixtures".to_owned();
+    LoadedConfig {
+        config,
+        path: None,
+        root: PathBuf::from(env!("CARGO_MANIFEST_DIR")),
+        sources: BTreeMap::new(),
+    }
+}
+
+#[test]
+fn config_discovers_files_under_test_dir() {
+    let files = discover_files(&fixtures_as_test_dir()).expect("discovery should succeed");
+    assert!(files.contains(&fixture("single_node.tast")));
+    assert!(
+        files
+            .iter()
+            .all(|f| f.extension().is_some_and(|e| e == "tast"))
+    );
+    let mut sorted = files.clone();
+    sorted.sort();
+    assert_eq!(files, sorted);
+}
+
+#[test]
+fn config_show_reports_defaults_source() {
+    let output = run_config_show(&fixtures_as_test_dir()).expect("show should succeed");
+    assert!(
+        output.starts_with("# source: built-in defaults"),
+        "got: {output}"
+    );
+    assert!(output.contains("file_extension = \"tast\"  # default\n"));
+    assert!(output.contains("[runner]\n"));
+
+    let mut loaded = fixtures_as_test_dir();
+    loaded.sources.insert(
+        "project.test_dir".to_owned(),
+        SettingSource::Env("TAST_TEST_DIR"),
+    );
+    let output = run_config_show(&loaded).expect("show should succeed");
+    assert!(
+        output.contains("test_dir = \"tests/fixtures\"  # from TAST_TEST_DIR\n"),
+        "got: {output}"
+    );
+}
+
+// ── Project scaffolding ──────────────────────────────────
+
+fn init_scratch(name: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("target")
+        .join("init_integration")
+        .join(name);
+    std::fs::remove_dir_all(&dir).ok();
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn init_scaffold_validates() {
+    let dir = init_scratch("validates");
+    std::fs::write(dir.join("go.mod"), "module example.com/demo\n").unwrap();
+    let report = run_init(&dir, false).expect("init should succeed");
+    assert!(report.contains("Detected Go"), "got: {report}");
+
+    let config = std::fs::read_to_string(dir.join(".tastrc.toml")).unwrap();
+    assert!(config.contains("command = \"go test\""));
+
+    let example = dir.join("tests").join("tast").join("example.tast");
+    let result = run_validate(&[example]).expect("example should validate");
+    assert!(result.contains("is valid"));
+}
+
+#[test]
+fn init_refuses_to_overwrite_without_force() {
+    let dir = init_scratch("refuses");
+    std::fs::write(dir.join(".tastrc.toml"), "# mine\n").unwrap();
+
+    let err = run_init(&dir, false).unwrap_err();
+    assert!(err.contains("--force"), "got: {err}");
+    assert_eq!(
+        std::fs::read_to_string(dir.join(".tastrc.toml")).unwrap(),
+        "# mine\n"
+    );
+    assert!(
+        !dir.join("tests").exists(),
+        "refused init must write nothing"
+    );
+
+    let report = run_init(&dir, true).expect("forced init should succeed");
+    assert!(report.contains("Overwrote .tastrc.toml"), "got: {report}");
+}
+
+// ── Lint ─────────────────────────────────────────────────
+
+#[test]
+fn lint_reports_rule_ids_with_file_positions() {
+    let outcome = run_lint(
+        &[fixture("with_fixtures.tast")],
+        &LintSettings::default(),
+        false,
+    )
+    .expect("lint should run");
+    assert!(
+        outcome
+            .report
+            .contains("with_fixtures.tast:7:5: warning[unused-fixture]: fixture 'GuestUser'"),
+        "got: {}",
+        outcome.report
+    );
+    assert!(
+        outcome
+            .report
+            .ends_with("1 file checked: 0 error(s), 1 warning(s), 0 info")
+    );
+    assert!(!outcome.failed, "warnings alone should not fail");
+}
+
+#[test]
+fn lint_deny_warnings_fails_the_run() {
+    let outcome = run_lint(
+        &[fixture("with_fixtures.tast")],
+        &LintSettings::default(),
+        true,
+    )
+    .expect("lint should run");
+    assert!(outcome.failed);
+}
+
+#[test]
+fn lint_respects_config_overrides() {
+    let config = Config::from_toml(
+        "[lint]\nunused-fixture = \"off\"\n",
+        std::path::Path::new(".tastrc.toml"),
+    )
+    .unwrap();
+    let settings = config.lint_settings().unwrap();
+    let outcome =
+        run_lint(&[fixture("with_fixtures.tast")], &settings, true).expect("lint should run");
+    assert!(!outcome.failed, "got: {}", outcome.report);
+}
+
+#[test]
+fn lint_parse_error_is_an_error() {
+    let result = run_lint(
+        &[fixture("invalid_syntax.tast")],
+        &LintSettings::default(),
+        false,
+    );
+    assert!(result.is_err());
+}
+
+// ── Parameter binding ────────────────────────────────────
+
+fn write_scratch(name: &str, contents: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("target")
+        .join("integration_scratch");
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+const UPSTREAM_PARAMS: &str = r#"graph Params {
+  node Register {
+    given a user with email "a@b.com"
+  }
+  node Login {
+    when the user logs in with <email> and <password>
+  }
+  Register -> Login { passes { email } }
+}"#;
+
+#[test]
+fn cli_plan_binds_parameters_from_upstream_edges() {
+    let file = write_scratch("upstream_params.tast", UPSTREAM_PARAMS);
+    let outcome =
+        run_plan(std::slice::from_ref(&file), &default_opts()).expect("plan should succeed");
+    let yaml = outcome.output;
+    assert!(yaml.contains("source: edge:Register.email"), "got: {yaml}");
+    assert!(yaml.contains("value: a@b.com"));
+    assert!(yaml.contains("source: unresolved"));
+    assert_eq!(
+        outcome.warnings,
+        vec![format!(
+            "{}: node 'Login': parameter <password> is unresolved",
+            file.display()
+        )]
+    );
+}
+
+#[test]
+fn cli_plan_strict_rejects_unresolved_parameters() {
+    let file = write_scratch("strict_params.tast", UPSTREAM_PARAMS);
+    let opts = PlanOptions {
+        strict: true,
+        ..PlanOptions::default()
+    };
+    let err = run_plan(&[file], &opts).unwrap_err();
+    assert!(
+        err.contains("node 'Login': parameter <password> is unresolved"),
+        "got: {err}"
+    );
+}
+
+#[test]
+fn cli_plan_renders_step_text_with_bound_parameters() {
+    let file = write_scratch("rendered_params.tast", UPSTREAM_PARAMS);
+    let yaml = run_plan(std::slice::from_ref(&file), &default_opts())
+        .expect("plan should succeed")
+        .output;
+    assert!(
+        yaml.contains("text: the user logs in with <email> and <password>\n"),
+        "got: {yaml}"
+    );
+    assert!(
+        yaml.contains("rendered_text: 'the user logs in with a@b.com and <password: UNRESOLVED>'"),
+        "got: {yaml}"
+    );
+
+    let markdown = |keep_template| {
+        let opts = PlanOptions {
+            keep_template,
+            format: "markdown".to_owned(),
+            ..PlanOptions::default()
+        };
+        run_plan(std::slice::from_ref(&file), &opts)
+            .expect("plan should succeed")
+            .output
+    };
+    let md = markdown(false);
+    assert!(md.contains("- **When** the user logs in with a@b.com and <password: UNRESOLVED>"));
+    assert!(!md.contains("template:"));
+    let md = markdown(true);
+    assert!(md.contains("  - template: `the user logs in with <email> and <password>`"));
+}
+
+// ── Loops ───────────────────────────────────────────────────
+
+#[test]
+fn cli_plan_unrolls_bounded_loops() {
+    let file = write_scratch(
+        "bounded_loop.tast",
+        r#"graph Polling {
+  node Submit { }
+  node Poll { }
+  node Done { }
+  Submit -> Poll
+  Poll -> Submit repeat 2
+  Poll -> Done
+}"#,
+    );
+    let opts = PlanOptions {
+        format: "markdown".to_owned(),
+        ..PlanOptions::default()
+    };
+    let md = run_plan(&[file], &opts)
+        .expect("bounded loop should plan")
+        .output;
+    assert!(
+        md.contains("## Step 3: Submit (iteration 2/2)"),
+        "got: {md}"
+    );
+    assert!(md.contains("## Step 4: Poll (iteration 2/2)"));
+    assert!(md.contains("## Step 5: Done\n"));
+}
+
+#[test]
+fn cli_plan_rejects_unbounded_cycle_with_path() {
+    let file = write_scratch(
+        "unbounded_loop.tast",
+        r#"graph Spin {
+  node A { }
+  node B { }
+  A -> B
+  B -> A
+}"#,
+    );
+    let err = run_plan(&[file], &default_opts()).unwrap_err();
+    assert!(err.contains("unbounded cycle A -> B -> A"), "got: {err}");
+}
+
+// ── Path plans ──────────────────────────────────────────────
+
+const CHECKOUT: &str = r#"graph Checkout {
+  node Browse { }
+  node Cart { }
+  node Guest { }
+  node Login { }
+  node Pay { }
+  Browse -> Cart
+  Cart -> Guest
+  Cart -> Login
+  Guest -> Pay
+  Login -> Pay
+}"#;
+
+#[test]
+fn cli_plan_paths_emits_one_plan_per_journey() {
+    let file = write_scratch("paths.tast", CHECKOUT);
+    let opts = PlanOptions {
+        strategy: "paths".to_owned(),
+        ..PlanOptions::default()
+    };
+    let yaml = run_plan(&[file], &opts)
+        .expect("paths plan should succeed")
+        .output;
+    let docs: Vec<&str> = yaml.split("---\n").collect();
+    assert_eq!(docs.len(), 2, "got: {yaml}");
+    assert!(docs[0].contains("node: Guest"));
+    assert!(!docs[0].contains("node: Login"));
+    assert!(docs[1].contains("node: Login"));
+    assert!(docs[1].contains("shared_prefix:\n      with: 1\n      steps: 2"));
+}
+
+#[test]
+fn cli_plan_paths_between_nodes_respects_cap() {
+    let file = write_scratch("paths_between.tast", CHECKOUT);
+    let opts = PlanOptions {
+        strategy: "paths".to_owned(),
+        from: Some("Cart".to_owned()),
+        to: Some("Pay".to_owned()),
+        max_paths: 1,
+        format: "markdown".to_owned(),
+        ..PlanOptions::default()
+    };
+    let outcome = run_plan(std::slice::from_ref(&file), &opts).expect("paths plan should succeed");
+    let md = outcome.output;
+    assert!(md.contains("# Test Plan: Checkout (path 1/1)"), "got: {md}");
+    assert!(md.contains("## Step 1: Cart"));
+    assert!(!md.contains("Browse"));
+    assert_eq!(
+        outcome.warnings,
+        vec![format!(
+            "{}: graph 'Checkout': stopped after 1 paths (raise --max-paths)",
+            file.display()
+        )]
+    );
+}
+
+#[test]
+fn cli_plan_paths_rejects_unbounded_cycles_and_zero_limits() {
+    let file = write_scratch(
+        "paths_cycle.tast",
+        "graph Cycle {\n  node A { }\n  node B { }\n  A -> B\n  B -> A\n}",
+    );
+    let opts = PlanOptions {
+        strategy: "paths".to_owned(),
+        ..PlanOptions::default()
+    };
+    let err = run_plan(std::slice::from_ref(&file), &opts).unwrap_err();
+    assert!(err.contains("unbounded cycle"), "got: {err}");
+
+    let file = write_scratch("paths_limits.tast", CHECKOUT);
+    let opts = PlanOptions {
+        strategy: "paths".to_owned(),
+        max_paths: 0,
+        ..PlanOptions::default()
+    };
+    let err = run_plan(std::slice::from_ref(&file), &opts).unwrap_err();
+    assert_eq!(err, "--max-paths must be at least 1");
+    let opts = PlanOptions {
+        strategy: "paths".to_owned(),
+        max_path_length: 0,
+        ..PlanOptions::default()
+    };
+    let err = run_plan(&[file], &opts).unwrap_err();
+    assert_eq!(err, "--max-path-length must be at least 1");
+}
+
+#[test]
+fn cli_plan_edge_cover_reports_coverage() {
+    let file = write_scratch("edge_cover.tast", CHECKOUT);
+    let opts = PlanOptions {
+        strategy: "edge-cover".to_owned(),
+        ..PlanOptions::default()
+    };
+    let yaml = run_plan(std::slice::from_ref(&file), &opts)
+        .expect("edge cover should succeed")
+        .output;
+    let docs: Vec<&str> = yaml.split("---\n").collect();
+    assert_eq!(docs.len(), 2, "got: {yaml}");
+    assert!(docs[0].contains("traversal: edge-cover"));
+    assert!(docs[0].contains("edges_added: 60.0"));
+    assert!(docs[1].contains("edges_covered: 100.0"));
+    assert!(docs[1].contains("nodes_covered: 100.0"));
+
+    let opts = PlanOptions {
+        strategy: "edge-cover".to_owned(),
+        from: Some("Cart".to_owned()),
+        to: Some("Pay".to_owned()),
+        ..PlanOptions::default()
+    };
+    assert!(run_plan(&[file], &opts).is_err());
+}
+
+#[test]
+fn cli_plan_random_records_seed_and_emits_permutations() {
+    let file = write_scratch("random.tast", CHECKOUT);
+    let opts = PlanOptions {
+        strategy: "random".to_owned(),
+        seed: Some(11),
+        ..PlanOptions::default()
+    };
+    let yaml = run_plan(std::slice::from_ref(&file), &opts)
+        .expect("random should succeed")
+        .output;
+    assert!(yaml.contains("traversal: random\n"), "got: {yaml}");
+    assert!(yaml.contains("seed: 11\n  uniform: true\n"));
+    assert_eq!(
+        run_plan(std::slice::from_ref(&file), &opts).unwrap().output,
+        yaml,
+        "the same seed gives the same plan"
+    );
+
+    // Guest and Login can run in either order, and that is all
+    let opts = PlanOptions {
+        permutations: 5,
+        ..opts
+    };
+    let yaml = run_plan(std::slice::from_ref(&file), &opts)
+        .expect("permutations should succeed")
+        .output;
+    let docs: Vec<&str> = yaml.split("---\n").collect();
+    assert_eq!(docs.len(), 2, "got: {yaml}");
+    assert!(docs[0].contains("seed: 11\n"));
+    let guest_first = |doc: &str| doc.find("node: Guest") < doc.find("node: Login");
+    assert_ne!(guest_first(docs[0]), guest_first(docs[1]));
+
+    let opts = PlanOptions {
+        seed: Some(11),
+        ..PlanOptions::default()
+    };
+    let err = run_plan(&[file], &opts).unwrap_err();
+    assert_eq!(err, "--seed can only be used with the random strategy");
+}
+
+#[test]
+fn cli_plan_random_warns_when_orders_are_not_uniform() {
+    // 20 independent nodes have too many partial orders to count
+    let nodes: String = (0..20).map(|i| format!("  node N{i} {{ }}\n")).collect();
+    let file = write_scratch("random_large.tast", &format!("graph Wide {{\n{nodes}}}"));
+    let opts = PlanOptions {
+        strategy: "random".to_owned(),
+        seed: Some(3),
+        ..PlanOptions::default()
+    };
+    let outcome = run_plan(std::slice::from_ref(&file), &opts).expect("random should succeed");
+    assert!(
+        outcome.output.contains("seed: 3\n  uniform: false\n"),
+        "got: {}",
+        outcome.output
+    );
+    assert_eq!(
+        outcome.warnings,
+        vec![format!(
+            "{}: graph 'Wide': too many orders to count, so random orders are not drawn uniformly",
+            file.display()
+        )]
+    );
+}
+
+#[test]
+fn cli_plan_weighted_reports_estimated_durations() {
+    let file = write_scratch(
+        "weighted.tast",
+        r#"graph Checkout {
+  node Browse { config { estimated_duration: "30s" } }
+  node Search { config { estimated_duration: "2m" } }
+  node Pay { config { priority: 2, estimated_duration: "1m" } }
+  Browse -> Search
+  Browse -> Pay
+}"#,
+    );
+    let opts = PlanOptions {
+        strategy: "weighted".to_owned(),
+        format: "markdown".to_owned(),
+        ..PlanOptions::default()
+    };
+    let md = run_plan(&[file], &opts)
+        .expect("weighted plan should succeed")
+        .output;
+    assert!(md.contains("**Traversal:** weighted"), "got: {md}");
+    assert!(md.contains("**Estimated duration:** 3m30s"));
+    assert!(md.contains("## Step 2: Pay (estimated 1m, done at 1m30s)"));
+    assert!(md.contains("## Step 3: Search (estimated 2m, done at 3m30s)"));
+}
+
+#[test]
+fn cli_plan_budget_selects_nodes_that_fit() {
+    let file = write_scratch(
+        "budget.tast",
+        r#"graph Checkout {
+  node Browse { config { estimated_duration: "2m" } }
+  node Cart { config { estimated_duration: "3m" } }
+  node Pay { config { estimated_duration: "4m" } }
+  node Search { config { estimated_duration: "6m" } }
+  Browse -> Cart
+  Cart -> Pay
+  Browse -> Search
+}"#,
+    );
+    let opts = PlanOptions {
+        budget: Some("10m".to_owned()),
+        ..PlanOptions::default()
+    };
+    let yaml = run_plan(std::slice::from_ref(&file), &opts)
+        .expect("budgeted plan should succeed")
+        .output;
+    assert!(
+        yaml.contains(
+            "  budget:\n    limit: 10m\n    coverage_by: edges\n    coverage: 66.7\n    selected:\n    - Browse\n    - Cart\n    - Pay\n    dropped:\n    - Search\n"
+        ),
+        "got: {yaml}"
+    );
+    assert!(yaml.contains("  estimated_duration: 9m\n"));
+    assert!(!yaml.contains("node: Search"));
+
+    let opts = PlanOptions {
+        budget: Some("10m".to_owned()),
+        budget_by: "tags".to_owned(),
+        format: "markdown".to_owned(),
+        ..PlanOptions::default()
+    };
+    let md = run_plan(std::slice::from_ref(&file), &opts)
+        .expect("budgeted plan should succeed")
+        .output;
+    assert!(
+        md.contains("**Budget:** 10m (100% of tags kept) | **Dropped:** "),
+        "got: {md}"
+    );
+
+    let opts = PlanOptions {
+        budget: Some("soon".to_owned()),
+        ..PlanOptions::default()
+    };
+    let err = run_plan(&[file], &opts).unwrap_err();
+    assert_eq!(err, "--budget: invalid duration 'soon'");
+
+    let opts = PlanOptions {
+        budget: Some("1s".to_owned()),
+        ..PlanOptions::default()
+    };
+    let auth = fixture("full_auth.tast");
+    let err = run_plan(std::slice::from_ref(&auth), &opts).unwrap_err();
+    assert_eq!(
+        err,
+        format!(
+            "{}: graph 'UserAuthentication': cannot select within a budget: no node declares an estimated_duration",
+            auth.display()
+        )
+    );
+
+    let file = write_scratch(
+        "budget_unestimated.tast",
+        r#"graph Checkout {
+  node Browse { config { estimated_duration: "2m" } }
+  node Cart { }
+  Browse -> Cart
+}"#,
+    );
+    let outcome = run_plan(std::slice::from_ref(&file), &opts).expect("plan should succeed");
+    assert_eq!(
+        outcome.warnings,
+        Vec::<String>::new(),
+        "nothing selected, nothing counted as free"
+    );
+    let opts = PlanOptions {
+        budget: Some("5m".to_owned()),
+        ..PlanOptions::default()
+    };
+    let outcome = run_plan(std::slice::from_ref(&file), &opts).expect("plan should succeed");
+    assert_eq!(
+        outcome.warnings,
+        vec![format!(
+            "{}: graph 'Checkout': --budget counted 1 node(s) without an estimated_duration as free: Cart",
+            file.display()
+        )]
+    );
+}
+
+#[test]
+fn cli_plan_shards_carry_their_setup() {
+    let file = write_scratch(
+        "shard.tast",
+        r#"graph Checkout {
+  node Login { config { estimated_duration: "20s" } }
+  node Cart { config { estimated_duration: "30s" } }
+  node Pay { config { estimated_duration: "1m" } }
+  node Search { config { estimated_duration: "2m" } }
+  Login -> Cart
+  Cart -> Pay
+  Login -> Search
+}"#,
+    );
+    let shard = |spec: &str| {
+        let opts = PlanOptions {
+            shard: Some(spec.to_owned()),
+            ..PlanOptions::default()
+        };
+        run_plan(std::slice::from_ref(&file), &opts)
+            .expect("shard should succeed")
+            .output
+    };
+    let first = shard("1/2");
+    assert!(
+        first.contains(
+            "  shard:\n    index: 1\n    total: 2\n    balanced_by: duration\n    owned: 1\n    setup: 1\n    overhead: 8.7\n"
+        ),
+        "got: {first}"
+    );
+    assert!(first.contains("node: Login\n  included_for: dependency\n"));
+    assert!(first.contains("node: Search\n"));
+    assert!(first.contains("  estimated_duration: 2m20s\n"));
+    let second = shard("2/2");
+    assert!(second.contains("node: Login\n"));
+    assert!(!second.contains("included_for"));
+    assert!(second.contains("node: Pay\n"));
+    assert!(!second.contains("node: Search"));
+
+    let opts = PlanOptions {
+        shard: Some("3/2".to_owned()),
+        ..PlanOptions::default()
+    };
+    let err = run_plan(std::slice::from_ref(&file), &opts).unwrap_err();
+    assert_eq!(err, "--shard: expected I/N with 1 <= I <= N, got '3/2'");
+
+    let opts = PlanOptions {
+        shard: Some("1/2".to_owned()),
+        format: "junit".to_owned(),
+        ..PlanOptions::default()
+    };
+    let xml = run_plan(&[file], &opts)
+        .expect("shard should succeed")
+        .output;
+    assert!(
+        xml.contains(r#"<testsuites name="Checkout (shard 1/2)""#),
+        "got: {xml}"
+    );
+}
+
+#[test]
+fn cli_plan_merge_emits_one_document() {
+    let files = [fixture("simple_edge.tast"), fixture("single_node.tast")];
+    let merged = |format: &str| {
+        let opts = PlanOptions {
+            format: format.to_owned(),
+            merge: true,
+            ..default_opts()
+        };
+        run_plan(&files, &opts)
+            .expect("merge should succeed")
+            .output
+    };
+
+    let yaml = merged("yaml");
+    assert!(!yaml.contains("---"), "got: {yaml}");
+    assert!(yaml.contains("  name: SimpleEdge + SingleNode\n"));
+    assert!(yaml.contains("  nodes_total: 3\n  edges_total: 1\n"));
+    assert!(yaml.contains("node: SimpleEdge.B\n"));
+    assert!(yaml.contains("  depends_on:\n  - SimpleEdge.A\n"));
+    assert!(yaml.contains("  from: SimpleEdge.A\n"));
+    assert!(yaml.contains("  graphs:\n  - name: SimpleEdge\n"));
+    // Topological plans are merged stage by stage
+    assert!(
+        yaml.contains("# stage 1\n- order: 1\n  stage: 1\n  node: SimpleEdge.A\n"),
+        "got: {yaml}"
+    );
+    assert!(yaml.contains("- order: 2\n  stage: 1\n  node: SingleNode.Register\n"));
+    assert!(yaml.contains("# stage 2\n- order: 3\n  stage: 2\n  node: SimpleEdge.B\n"));
+
+    // Other orders stay graph by graph, without stage markers
+    let opts = PlanOptions {
+        strategy: "dfs".to_owned(),
+        merge: true,
+        ..default_opts()
+    };
+    let yaml = run_plan(&files, &opts)
+        .expect("merge should succeed")
+        .output;
+    assert!(!yaml.contains("# stage"), "got: {yaml}");
+    assert!(yaml.contains("# graph SimpleEdge\n- order: 1\n"));
+    assert!(yaml.contains("# graph SingleNode\n- order: 3\n"));
+    let opts = PlanOptions {
+        format: "markdown".to_owned(),
+        ..opts
+    };
+    let md = run_plan(&files, &opts)
+        .expect("merge should succeed")
+        .output;
+    assert!(!md.contains("**Stage 1**"), "got: {md}");
+    assert!(md.contains("**Graph SingleNode**\n\n## Step 3: SingleNode.Register (stage 1)"));
+
+    let xml = merged("junit");
+    assert_eq!(xml.matches("<?xml").count(), 1, "got: {xml}");
+    assert_eq!(xml.matches("<testsuites ").count(), 1);
+    assert!(xml.contains(r#"<testsuites name="SimpleEdge + SingleNode" tests="3""#));
+    assert!(xml.contains(r#"<testcase name="SingleNode.Register""#));
+
+    let opts = PlanOptions {
+        strategy: "paths".to_owned(),
+        merge: true,
+        ..default_opts()
+    };
+    let err = run_plan(&files, &opts).unwrap_err();
+    assert_eq!(
+        err,
+        "--merge cannot be combined with --permutations or the paths and edge-cover strategies"
+    );
+
+    let opts = PlanOptions {
+        merge: true,
+        ..default_opts()
+    };
+    let twice = [fixture("single_node.tast"), fixture("single_node.tast")];
+    let err = run_plan(&twice, &opts).unwrap_err();
+    assert_eq!(
+        err,
+        "cannot merge: graph 'SingleNode' appears more than once"
+    );
+}
+
+#[test]
+fn cli_plan_json_follows_the_schema() {
+    let json = |format: &str| {
+        let opts = PlanOptions {
+            format: format.to_owned(),
+            ..default_opts()
+        };
+        run_plan(&[fixture("simple_edge.tast")], &opts)
+            .expect("plan should succeed")
+            .output
+    };
+    let pretty = json("json");
+    let plans: serde_json::Value = serde_json::from_str(&pretty).expect("invalid json");
+    // A single plan is still an array, so there is only one shape to parse
+    assert_eq!(plans.as_array().map(Vec::len), Some(1), "got: {pretty}");
+    let plan = &plans[0];
+    assert_eq!(plan["schema_version"], 1);
+    assert_eq!(plan["plan"]["name"], "SimpleEdge");
+    assert_eq!(plan["steps"][1]["depends_on"][0], "A");
+    let compact = json("json-compact");
+    assert_eq!(compact.lines().count(), 1);
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&compact).unwrap(),
+        plans
+    );
+
+    let schema: serde_json::Value =
+        serde_json::from_str(&run_schema().expect("schema should print")).expect("invalid json");
+    assert_eq!(
+        schema["$defs"]["TestPlan"]["properties"]["schema_version"]["const"],
+        1
+    );
+    let step = &schema["$defs"]["PlanStep"]["properties"];
+    for key in plan["steps"][1].as_object().unwrap().keys() {
+        assert!(!step[key].is_null(), "schema lacks step property '{key}'");
+    }
+    assert_valid(&schema, &plans);
+    assert!(jsonschema::validate(&schema, plan).is_err());
+}
+
+fn assert_valid(schema: &serde_json::Value, instance: &serde_json::Value) {
+    if let Err(e) = jsonschema::validate(schema, instance) {
+        panic!("{e} at {}: {instance}", e.instance_path());
+    }
+}
+
+#[test]
+fn cli_plan_json_is_always_one_array() {
+    let files = [fixture("simple_edge.tast"), fixture("single_node.tast")];
+    let opts = PlanOptions {
+        format: "json".to_owned(),
+        ..default_opts()
+    };
+    let json = run_plan(&files, &opts).expect("plan should succeed").output;
+    let plans: serde_json::Value = serde_json::from_str(&json).expect("invalid json");
+    let names: Vec<&str> = plans
+        .as_array()
+        .expect("expected an array of plans")
+        .iter()
+        .map(|plan| plan["plan"]["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, vec!["SimpleEdge", "SingleNode"]);
+    let schema: serde_json::Value =
+        serde_json::from_str(&run_schema().expect("schema should print")).expect("invalid json");
+    assert_valid(&schema, &plans);
+
+    let merged = PlanOptions {
+        merge: true,
+        ..opts
+    };
+    let json = run_plan(&files, &merged)
+        .expect("plan should succeed")
+        .output;
+    let plans: serde_json::Value = serde_json::from_str(&json).expect("invalid json");
+    assert_eq!(plans[0]["plan"]["name"], "SimpleEdge + SingleNode");
+    assert_eq!(plans.as_array().map(Vec::len), Some(1));
+    assert_valid(&schema, &plans);
+}
+
+// ── Stages ──────────────────────────────────────────────────
+
+#[test]
+fn cli_plan_groups_steps_into_parallel_stages() {
+    let file = write_scratch("stages.tast", CHECKOUT);
+    let yaml = run_plan(std::slice::from_ref(&file), &default_opts())
+        .expect("plan should succeed")
+        .output;
+    assert!(
+        yaml.contains("stages:\n  - 1\n  - 1\n  - 2\n  - 1\n"),
+        "got: {yaml}"
+    );
+    assert!(yaml.contains("critical_path: 4"));
+    assert!(yaml.contains("- order: 3\n  stage: 3\n"));
+
+    let opts = PlanOptions {
+        format: "markdown".to_owned(),
+        ..PlanOptions::default()
+    };
+    let md = run_plan(&[file], &opts)
+        .expect("plan should succeed")
+        .output;
+    assert!(md.contains("**Stages:** 4 (widths 1, 1, 2, 1) | **Critical path:** 4 steps"));
+    assert!(
+        md.contains("**Stage 3** (2 steps, can run in parallel)"),
+        "got: {md}"
+    );
+}
+
+#[test]
+fn cli_plan_orders_independent_chains_stage_by_stage() {
+    let file = write_scratch(
+        "chains.tast",
+        r#"graph Chains {
+            node A {}
+            node B {}
+            node C {}
+            node E {}
+            node F {}
+            A -> B
+            B -> C
+            E -> F
+        }"#,
+    );
+    let yaml = run_plan(std::slice::from_ref(&file), &default_opts())
+        .expect("plan should succeed")
+        .output;
+    assert_eq!(yaml.matches("# stage 1\n").count(), 1, "got: {yaml}");
+    let stages: Vec<&str> = yaml.lines().filter(|l| l.starts_with("# stage ")).collect();
+    assert_eq!(stages, ["# stage 1", "# stage 2", "# stage 3"]);
+
+    let opts = PlanOptions {
+        format: "markdown".to_owned(),
+        ..PlanOptions::default()
+    };
+    let md = run_plan(&[file], &opts)
+        .expect("plan should succeed")
+        .output;
+    let steps: Vec<&str> = md
+        .lines()
+        .filter(|l| l.starts_with("## Step "))
+        .map(|l| l.split(": ").next().unwrap())
+        .collect();
+    assert_eq!(
+        steps,
+        [
+            "## Step 1",
+            "## Step 2",
+            "## Step 3",
+            "## Step 4",
+            "## Step 5"
+        ],
+        "got: {md}"
+    );
+}
+
+// ── Change impact ───────────────────────────────────────────
+
+#[test]
+fn cli_plan_affected_by_keeps_descendants_and_their_setup() {
+    let file = write_scratch("affected.tast", CHECKOUT);
+    let opts = PlanOptions {
+        affected_by: vec!["Guest".to_owned()],
+        ..PlanOptions::default()
+    };
+    let yaml = run_plan(std::slice::from_ref(&file), &opts)
+        .expect("plan should succeed")
+        .output;
+    for node in ["Browse", "Cart", "Guest", "Login", "Pay"] {
+        assert!(
+            yaml.contains(&format!("node: {node}\n")),
+            "missing {node}: {yaml}"
+        );
+    }
+
+    let opts = PlanOptions {
+        affected_by: vec!["Cart".to_owned(), "Nope".to_owned()],
+        ..PlanOptions::default()
+    };
+    let err = run_plan(&[file], &opts).unwrap_err();
+    assert!(err.contains("unknown node 'Nope'"), "got: {err}");
+}
+
+#[test]
+fn cli_plan_since_snapshot_plans_only_changed_nodes() {
+    let file = write_scratch(
+        "since.tast",
+        r#"graph Shop {
+  node Browse { }
+  node Search { }
+  node Cart { }
+  Browse -> Cart
+}"#,
+    );
+    let snapshot = write_scratch("since.snapshot.yaml", "");
+    run_snapshot(std::slice::from_ref(&file), Some(&snapshot)).expect("snapshot should succeed");
+
+    let opts = PlanOptions {
+        since: Some(snapshot),
+        ..PlanOptions::default()
+    };
+    let yaml = run_plan(std::slice::from_ref(&file), &opts)
+        .expect("plan should succeed")
+        .output;
+    assert!(
+        yaml.contains("  name: Shop\n"),
+        "nothing changed, got: {yaml}"
+    );
+    assert!(yaml.contains("steps: []\n"));
+
+    std::fs::write(
+        &file,
+        r#"graph Shop {
+  node Browse { }
+  node Search { }
+  node Cart {
+    then the cart is shown
+  }
+  Browse -> Cart
+}"#,
+    )
+    .unwrap();
+    let yaml = run_plan(&[file], &opts)
+        .expect("plan should succeed")
+        .output;
+    assert!(yaml.contains("node: Browse\n"), "got: {yaml}");
+    assert!(yaml.contains("node: Cart\n"));
+    assert!(!yaml.contains("node: Search"));
+}
+
+// ── Graph metrics ───────────────────────────────────────────
+
+#[test]
+fn cli_stats_reports_metrics_and_fails_on_thresholds() {
+    let file = fixture("full_auth.tast");
+    let outcome = run_stats(std::slice::from_ref(&file), &Thresholds::default())
+        .expect("stats should succeed");
+    assert!(!outcome.failed);
+    assert!(
+        outcome.report.contains("  nodes: 4, edges: 3\n"),
+        "got: {}",
+        outcome.report
+    );
+    assert!(
+        outcome
+            .report
+            .ends_with("1 graph(s) checked: 0 threshold(s) exceeded\n")
+    );
+
+    let thresholds = Thresholds {
+        max_depth: Some(2),
+        ..Thresholds::default()
+    };
+    let outcome = run_stats(&[file], &thresholds).expect("stats should succeed");
+    assert!(outcome.failed);
+    assert!(
+        outcome
+            .report
+            .contains("  exceeded: depth 3 exceeds max-depth 2\n"),
+        "got: {}",
+        outcome.report
+    );
+}
+
+// ── Semantic diff ───────────────────────────────────────────
+
+#[test]
+fn cli_diff_reports_graph_changes_and_plan_order() {
+    let old = write_scratch(
+        "diff_old.tast",
+        r#"graph Shop {
+  node Browse {
+    given a visitor
+  }
+  node Cart {
+    given a cart
+  }
+  node Search {
+    when the visitor searches
+  }
+  Browse -> Cart
+}"#,
+    );
+    let new = write_scratch(
+        "diff_new.tast",
+        r#"graph Shop {
+  node Browse {
+    given a visitor
+  }
+  node Basket {
+    given a cart
+  }
+  node Search {
+    tags [smoke]
+    when the visitor searches
+  }
+  Browse -> Basket
+  Basket -> Search { passes { cart_id } }
+}"#,
+    );
+    let report = run_diff(&old, &new, "text", "tast").expect("diff should succeed");
+    assert_eq!(
+        report,
+        "graph Shop
+  ~ node Cart renamed to Basket
+  ~ node Search
+      + tag smoke
+  + edge Basket -> Search
+  ~ plan order
+      Search: step 1 -> 3
+"
+    );
+
+    let json = run_diff(&old, &new, "json", "tast").expect("diff should succeed");
+    assert!(
+        json.contains("\"added_edges\": [\n        \"Basket -> Search\"\n      ]"),
+        "got: {json}"
+    );
+    assert!(json.contains("\"old_position\": 1"));
+
+    let err = run_diff(&old, &new, "html", "tast").unwrap_err();
+    assert!(err.contains("unknown diff format 'html'"), "got: {err}");
+}
+
+#[test]
+fn cli_diff_still_compares_graphs_that_do_not_compile() {
+    let cycle =
+        "graph Loop {\n  node A {\n    given a start\n  }\n  node B {}\n  A -> B\n  B -> A\n}\n";
+    let old = write_scratch("diff_cycle_old.tast", cycle);
+    let new = write_scratch(
+        "diff_cycle_new.tast",
+        &cycle
+            .replace("given a start", "given a fresh start")
+            .replace("B -> A\n", "B -> A repeat 3\n"),
+    );
+    let report = run_diff(&old, &new, "text", "tast").expect("diff should succeed");
+    assert!(
+        report.contains("      + step given a fresh start\n"),
+        "got: {report}"
+    );
+    assert!(
+        report.contains("  ~ edge B -> A unbounded -> repeat 3\n"),
+        "got: {report}"
+    );
+    assert!(
+        report.contains("  ! plan order not compared: old version: "),
+        "got: {report}"
+    );
+}
+
+#[test]
+fn cli_diff_compares_directories_by_graph_name() {
+    let root = write_scratch("diff_dirs.txt", "").with_file_name("diff_dirs");
+    for (dir, graphs) in [("old", "Auth Shop"), ("new", "Shop Admin")] {
+        let dir = root.join(dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in graphs.split(' ') {
+            let body = format!("graph {name} {{\n  node Start {{ }}\n}}\n");
+            std::fs::write(dir.join(format!("{name}.tast")), body).unwrap();
+        }
+    }
+    let report = run_diff(&root.join("old"), &root.join("new"), "text", "tast")
+        .expect("diff should succeed");
+    assert_eq!(report, "+ graph Admin\n- graph Auth\n");
+
+    let same = run_diff(&root.join("old"), &root.join("old"), "text", "tast").unwrap();
+    assert_eq!(same, "no changes\n");
+}
+
+// ── Subgraph queries ────────────────────────────────────────
+
+fn plan_nodes(yaml: &str) -> Vec<&str> {
+    yaml.lines()
+        .filter_map(|l| l.trim().strip_prefix("node: "))
+        .collect()
+}
+
+#[test]
+fn cli_plan_containing_keeps_ancestors_and_descendants() {
+    let file = write_scratch("containing.tast", CHECKOUT);
+    let opts = PlanOptions {
+        containing: Some("Guest".to_owned()),
+        ..PlanOptions::default()
+    };
+    let yaml = run_plan(&[file], &opts)
+        .expect("plan should succeed")
+        .output;
+    assert_eq!(plan_nodes(&yaml), vec!["Browse", "Cart", "Guest", "Pay"]);
+    assert!(yaml.contains("nodes_total: 4"));
+}
+
+#[test]
+fn cli_plan_upstream_and_downstream_queries_narrow_each_other() {
+    let file = write_scratch("up_down.tast", CHECKOUT);
+    let opts = PlanOptions {
+        upstream_of: Some("Pay".to_owned()),
+        downstream_of: Some("Cart".to_owned()),
+        strategy: "dfs".to_owned(),
+        ..PlanOptions::default()
+    };
+    let yaml = run_plan(std::slice::from_ref(&file), &opts)
+        .expect("plan should succeed")
+        .output;
+    let mut nodes = plan_nodes(&yaml);
+    nodes.sort_unstable();
+    assert_eq!(nodes, vec!["Cart", "Guest", "Login", "Pay"]);
+
+    let opts = PlanOptions {
+        downstream_of: Some("G*".to_owned()),
+        ..PlanOptions::default()
+    };
+    let yaml = run_plan(std::slice::from_ref(&file), &opts)
+        .expect("plan should succeed")
+        .output;
+    assert_eq!(plan_nodes(&yaml), vec!["Guest", "Pay"]);
+
+    let opts = PlanOptions {
+        upstream_of: Some("Guest".to_owned()),
+        downstream_of: Some("Login".to_owned()),
+        ..PlanOptions::default()
+    };
+    let yaml = run_plan(std::slice::from_ref(&file), &opts)
+        .expect("plan should succeed")
+        .output;
+    assert!(yaml.contains("  name: Checkout\n"), "got: {yaml}");
+    assert!(yaml.contains("steps: []\n"));
+
+    let opts = PlanOptions {
+        containing: Some("Ship*".to_owned()),
+        ..PlanOptions::default()
+    };
+    let err = run_plan(&[file], &opts).unwrap_err();
+    assert!(
+        err.contains("--containing: no node matches 'Ship*'"),
+        "got: {err}"
+    );
+}
+
+// ── Selection queries ───────────────────────────────────────
+
+#[test]
+fn cli_plan_select_query_picks_nodes() {
+    let file = write_scratch("select.tast", CHECKOUT);
+    let opts = PlanOptions {
+        select: Some("path(Cart, Pay) AND NOT name:L*".to_owned()),
+        ..PlanOptions::default()
+    };
+    let yaml = run_plan(std::slice::from_ref(&file), &opts)
+        .expect("plan should succeed")
+        .output;
+    assert_eq!(plan_nodes(&yaml), vec!["Cart", "Guest", "Pay"]);
+
+    let opts = PlanOptions {
+        select: Some("roots() OR (leaves()".to_owned()),
+        ..PlanOptions::default()
+    };
+    let err = run_plan(&[file], &opts).unwrap_err();
+    assert!(
+        err.contains(
+            "--select: invalid query at column 21: expected ')' to close '(' at column 12"
+        ),
+        "got: {err}"
+    );
+    assert!(err.ends_with("\n  roots() OR (leaves()\n                      ^"));
+}
+
+#[test]
+fn cli_select_reports_unknown_node_names() {
+    let file = write_scratch("select_unknown.tast", CHECKOUT);
+    for (query, message) in [
+        ("descendants(Nope)", "--select: unknown node 'Nope'"),
+        ("name:Nope", "--select: unknown node 'Nope'"),
+        ("path(Cart, Pya)", "--select: unknown node 'Pya'"),
+        ("smoke OR name:Nope*", "--select: no node matches 'Nope*'"),
+    ] {
+        let opts = PlanOptions {
+            select: Some(query.to_owned()),
+            ..PlanOptions::default()
+        };
+        let err = run_plan(std::slice::from_ref(&file), &opts).unwrap_err();
+        assert_eq!(err, message, "{query}");
+    }
+    let err = run_list(
+        "nodes",
+        std::slice::from_ref(&file),
+        Some("ancestors(Nope)"),
+    )
+    .unwrap_err();
+    assert_eq!(err, "--select: unknown node 'Nope'");
+    let err = run_visualize(
+        std::slice::from_ref(&file),
+        "mermaid",
+        None,
+        Some("ancestors(Nope)"),
+        false,
+    )
+    .unwrap_err();
+    assert_eq!(err, "--select: unknown node 'Nope'");
+
+    // A name found in one file is not unknown because another lacks it
+    let other = write_scratch("select_other.tast", "graph Other {\n  node Solo {}\n}\n");
+    let opts = PlanOptions {
+        select: Some("descendants(Guest)".to_owned()),
+        ..PlanOptions::default()
+    };
+    let yaml = run_plan(&[file, other], &opts)
+        .expect("plan should succeed")
+        .output;
+    assert_eq!(plan_nodes(&yaml), vec!["Guest", "Pay"]);
+}
+
+#[test]
+fn cli_select_matching_nothing_writes_an_empty_plan() {
+    let file = write_scratch("select_empty.tast", CHECKOUT);
+    let opts = PlanOptions {
+        select: Some("tag:nothing".to_owned()),
+        ..PlanOptions::default()
+    };
+    let yaml = run_plan(std::slice::from_ref(&file), &opts)
+        .expect("plan should succeed")
+        .output;
+    assert!(yaml.contains("steps: []\n"), "got: {yaml}");
+
+    let filtered = PlanOptions {
+        filter: Some("nothing".to_owned()),
+        ..PlanOptions::default()
+    };
+    let expected = run_plan(&[file], &filtered)
+        .expect("plan should succeed")
+        .output;
+    assert_eq!(yaml, expected);
+}
+
+#[test]
+fn cli_list_and_visualize_accept_select_queries() {
+    let file = write_scratch("select_list.tast", CHECKOUT);
+    let nodes = run_list(
+        "nodes",
+        std::slice::from_ref(&file),
+        Some("descendants(Guest)"),
+    )
+    .expect("list should succeed");
+    assert_eq!(nodes, "Guest\nPay\n");
+    let edges = run_list(
+        "edges",
+        std::slice::from_ref(&file),
+        Some("NOT (name:Login OR name:Pay)"),
+    )
+    .expect("list should succeed");
+    assert_eq!(edges, "Browse -> Cart\nCart -> Guest\n");
+
+    let dot = run_visualize(&[file], "mermaid", None, Some("ancestors(Cart)"), false)
+        .expect("visualize should succeed");
+    assert!(dot.contains("Browse --> Cart"), "got: {dot}");
+    assert!(!dot.contains("Guest"));
+}
+
+#[test]
+fn cli_visualize_reduced_drops_implied_edges() {
+    let file = write_scratch(
+        "reduced.tast",
+        "graph G {\n  node A {}\n  node B {}\n  node C {}\n  A -> B\n  B -> C\n  A -> C\n}\n",
+    );
+    let full = run_visualize(std::slice::from_ref(&file), "mermaid", None, None, false)
+        .expect("visualize should succeed");
+    assert!(full.contains("A --> C"), "got: {full}");
+    let reduced =
+        run_visualize(&[file], "mermaid", None, None, true).expect("visualize should succeed");
+    assert!(reduced.contains("A --> B") && reduced.contains("B --> C"));
+    assert!(!reduced.contains("A --> C"), "got: {reduced}");
+}
+
+// ── Filter modes ────────────────────────────────────────────
+
+const TAGGED_CHECKOUT: &str = r#"graph Checkout {
+  node Browse { tags [setup] }
+  node Cart { tags [setup] }
+  node Pay { tags [smoke] }
+  Browse -> Cart
+  Cart -> Pay { passes { cart_id } }
+}"#;
+
+#[test]
+fn cli_plan_filter_keep_deps_includes_ancestors() {
+    let file = write_scratch("filter_keep_deps.tast", TAGGED_CHECKOUT);
+    let opts = PlanOptions {
+        filter: Some("smoke".to_owned()),
+        filter_mode: "keep-deps".to_owned(),
+        ..PlanOptions::default()
+    };
+    let yaml = run_plan(&[file], &opts)
+        .expect("plan should succeed")
+        .output;
+    assert_eq!(plan_nodes(&yaml), vec!["Browse", "Cart", "Pay"]);
+    assert_eq!(
+        yaml.matches("included_for: dependency").count(),
+        2,
+        "got: {yaml}"
+    );
+    assert!(yaml.contains("nodes_total: 3"));
+    assert!(yaml.contains("edges_total: 2"));
+}
+
+#[test]
+fn cli_plan_filter_prune_drops_dangling_references() {
+    let file = write_scratch("filter_prune.tast", TAGGED_CHECKOUT);
+    let opts = PlanOptions {
+        filter: Some("smoke".to_owned()),
+        filter_mode: "prune".to_owned(),
+        ..PlanOptions::default()
+    };
+    let outcome = run_plan(std::slice::from_ref(&file), &opts).expect("plan should succeed");
+    let yaml = outcome.output;
+    assert_eq!(plan_nodes(&yaml), vec!["Pay"]);
+    assert!(!yaml.contains("depends_on"), "got: {yaml}");
+    assert!(!yaml.contains("inputs"));
+    assert!(yaml.contains("edges_total: 0"));
+    assert_eq!(
+        outcome.warnings,
+        vec![
+            format!(
+                "{}: node 'Pay': dropped dependency on 'Cart'",
+                file.display()
+            ),
+            format!(
+                "{}: node 'Pay': dropped input 'cart_id' from 'Cart'",
+                file.display()
+            ),
+        ]
+    );
+
+    let opts = PlanOptions {
+        filter: Some("smoke".to_owned()),
+        filter_mode: "loose".to_owned(),
+        ..PlanOptions::default()
+    };
+    let err = run_plan(&[file], &opts).unwrap_err();
+    assert!(err.contains("unknown filter mode 'loose'"), "got: {err}");
+}
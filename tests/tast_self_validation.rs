@@ -56,7 +56,8 @@ fn tast_validate_full_pipeline() {
 #[test]
 fn tast_plan_parser_pipeline_produces_valid_yaml() {
     let yaml = run_plan(&[tast_file("parser_pipeline.tast")], &default_opts())
-        .expect("plan should succeed");
+        .expect("plan should succeed")
+        .output;
     assert!(yaml.contains("name: ParserPipeline"));
     assert!(yaml.contains("traversal: topological"));
     let deserialized: serde_yaml::Value =
@@ -68,7 +69,8 @@ fn tast_plan_parser_pipeline_produces_valid_yaml() {
 #[test]
 fn tast_plan_graph_pipeline_produces_valid_yaml() {
     let yaml = run_plan(&[tast_file("graph_pipeline.tast")], &default_opts())
-        .expect("plan should succeed");
+        .expect("plan should succeed")
+        .output;
     assert!(yaml.contains("name: GraphPipeline"));
     let deserialized: serde_yaml::Value =
         serde_yaml::from_str(&yaml).expect("output should be valid YAML");
@@ -77,8 +79,9 @@ fn tast_plan_graph_pipeline_produces_valid_yaml() {
 
 #[test]
 fn tast_plan_plan_pipeline_produces_valid_yaml() {
-    let yaml =
-        run_plan(&[tast_file("plan_pipeline.tast")], &default_opts()).expect("plan should succeed");
+    let yaml = run_plan(&[tast_file("plan_pipeline.tast")], &default_opts())
+        .expect("plan should succeed")
+        .output;
     assert!(yaml.contains("name: PlanPipeline"));
     let deserialized: serde_yaml::Value =
         serde_yaml::from_str(&yaml).expect("output should be valid YAML");
@@ -87,8 +90,9 @@ fn tast_plan_plan_pipeline_produces_valid_yaml() {
 
 #[test]
 fn tast_plan_full_pipeline_produces_valid_yaml() {
-    let yaml =
-        run_plan(&[tast_file("full_pipeline.tast")], &default_opts()).expect("plan should succeed");
+    let yaml = run_plan(&[tast_file("full_pipeline.tast")], &default_opts())
+        .expect("plan should succeed")
+        .output;
     assert!(yaml.contains("name: FullPipeline"));
     let deserialized: serde_yaml::Value =
         serde_yaml::from_str(&yaml).expect("output should be valid YAML");
@@ -99,8 +103,9 @@ fn tast_plan_full_pipeline_produces_valid_yaml() {
 
 #[test]
 fn tast_plan_full_pipeline_has_correct_step_count() {
-    let yaml =
-        run_plan(&[tast_file("full_pipeline.tast")], &default_opts()).expect("plan should succeed");
+    let yaml = run_plan(&[tast_file("full_pipeline.tast")], &default_opts())
+        .expect("plan should succeed")
+        .output;
     let plan: serde_yaml::Value = serde_yaml::from_str(&yaml).expect("valid YAML");
     let steps = plan.get("steps").and_then(|s| s.as_sequence()).unwrap();
     assert_eq!(steps.len(), 6, "full pipeline should have 6 steps");
@@ -108,8 +113,9 @@ fn tast_plan_full_pipeline_has_correct_step_count() {
 
 #[test]
 fn tast_plan_full_pipeline_preserves_data_flow() {
-    let yaml =
-        run_plan(&[tast_file("full_pipeline.tast")], &default_opts()).expect("plan should succeed");
+    let yaml = run_plan(&[tast_file("full_pipeline.tast")], &default_opts())
+        .expect("plan should succeed")
+        .output;
 
     // Verify data flows through the linear chain via passes → inputs/outputs
     assert!(yaml.contains("source_text"), "should pass source_text");